@@ -0,0 +1,20 @@
+-- RedefineTables
+PRAGMA foreign_keys=OFF;
+CREATE TABLE "new_YandexAuth" (
+    "token_type" TEXT NOT NULL,
+    "access_token" TEXT NOT NULL,
+    "expires_in" INTEGER NOT NULL,
+    "refresh_token" TEXT NOT NULL,
+    "scope" TEXT NOT NULL,
+    "issued_at" DATETIME DEFAULT CURRENT_TIMESTAMP,
+    "userId" TEXT,
+    CONSTRAINT "YandexAuth_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE SET NULL ON UPDATE CASCADE
+);
+-- Когда были выданы существующие токены, неизвестно: issued_at остаётся NULL, и такие токены обновляются первыми.
+INSERT INTO "new_YandexAuth" ("token_type", "access_token", "expires_in", "refresh_token", "scope", "issued_at", "userId") SELECT "token_type", "access_token", "expires_in", "refresh_token", "scope", NULL, "userId" FROM "YandexAuth";
+DROP TABLE "YandexAuth";
+ALTER TABLE "new_YandexAuth" RENAME TO "YandexAuth";
+CREATE UNIQUE INDEX "YandexAuth_access_token_key" ON "YandexAuth"("access_token");
+CREATE UNIQUE INDEX "YandexAuth_userId_key" ON "YandexAuth"("userId");
+PRAGMA foreign_key_check;
+PRAGMA foreign_keys=ON;
@@ -0,0 +1,30 @@
+-- CreateTable
+CREATE TABLE "UserIdentity" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "provider" TEXT NOT NULL,
+    "subject" TEXT NOT NULL,
+    "login" TEXT,
+    "token_type" TEXT,
+    "access_token" TEXT,
+    "refresh_token" TEXT,
+    "expires_in" INTEGER,
+    "scope" TEXT,
+    "issued_at" DATETIME,
+    "created_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "UserIdentity_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- Yandex-аккаунты идентифицировались по логину, он же становится subject.
+INSERT INTO "UserIdentity" ("id", "provider", "subject", "login", "token_type", "access_token", "refresh_token", "expires_in", "scope", "issued_at", "userId")
+SELECT lower(hex(randomblob(12))), 'yandex', "User"."login", "User"."login", "YandexAuth"."token_type", "YandexAuth"."access_token", "YandexAuth"."refresh_token", "YandexAuth"."expires_in", "YandexAuth"."scope", "YandexAuth"."issued_at", "YandexAuth"."userId"
+FROM "YandexAuth" JOIN "User" ON "User"."id" = "YandexAuth"."userId";
+
+-- DropTable
+DROP TABLE "YandexAuth";
+
+-- CreateIndex
+CREATE UNIQUE INDEX "UserIdentity_provider_subject_key" ON "UserIdentity"("provider", "subject");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "UserIdentity_userId_provider_key" ON "UserIdentity"("userId", "provider");
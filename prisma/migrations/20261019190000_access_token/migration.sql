@@ -0,0 +1,18 @@
+-- CreateTable
+CREATE TABLE "AccessToken" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "name" TEXT NOT NULL,
+    "prefix" TEXT NOT NULL,
+    "token_hash" TEXT NOT NULL,
+    "scopes" TEXT NOT NULL,
+    "created_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "last_used" DATETIME,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "AccessToken_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "AccessToken_prefix_key" ON "AccessToken"("prefix");
+
+-- CreateIndex
+CREATE INDEX "AccessToken_userId_idx" ON "AccessToken"("userId");
@@ -0,0 +1,18 @@
+-- AlterTable
+ALTER TABLE "User" ADD COLUMN "avatar_url" TEXT;
+ALTER TABLE "User" ADD COLUMN "display_name" TEXT;
+ALTER TABLE "User" ADD COLUMN "locale" TEXT NOT NULL DEFAULT 'ru';
+ALTER TABLE "User" ADD COLUMN "timezone" TEXT NOT NULL DEFAULT 'UTC';
+
+-- CreateTable
+CREATE TABLE "UserSettings" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "document" TEXT NOT NULL,
+    "version" INTEGER NOT NULL DEFAULT 1,
+    "updated_at" DATETIME NOT NULL,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "UserSettings_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "UserSettings_userId_key" ON "UserSettings"("userId");
@@ -0,0 +1,30 @@
+-- CreateTable
+CREATE TABLE "LibraryEntry" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "game_id" INTEGER NOT NULL,
+    "status" TEXT NOT NULL,
+    "installed_version" TEXT,
+    "install_path_hash" TEXT,
+    "playtime" INTEGER NOT NULL DEFAULT 0,
+    "last_played" DATETIME,
+    "added_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" DATETIME NOT NULL,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "LibraryEntry_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateTable
+CREATE TABLE "PlaySession" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "started_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "ended_at" DATETIME,
+    "duration" INTEGER,
+    "entryId" TEXT NOT NULL,
+    CONSTRAINT "PlaySession_entryId_fkey" FOREIGN KEY ("entryId") REFERENCES "LibraryEntry" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "LibraryEntry_userId_game_id_key" ON "LibraryEntry"("userId", "game_id");
+
+-- CreateIndex
+CREATE INDEX "PlaySession_entryId_started_at_idx" ON "PlaySession"("entryId", "started_at");
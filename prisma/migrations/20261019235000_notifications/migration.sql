@@ -0,0 +1,29 @@
+-- CreateTable
+CREATE TABLE "WishlistGame" (
+    "game_id" INTEGER NOT NULL PRIMARY KEY,
+    "name" TEXT,
+    "released" TEXT,
+    "price" TEXT,
+    "repackers" TEXT,
+    "checked_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- CreateTable
+CREATE TABLE "Notification" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "kind" TEXT NOT NULL,
+    "key" TEXT NOT NULL,
+    "game_id" INTEGER NOT NULL,
+    "title" TEXT NOT NULL,
+    "body" TEXT,
+    "read_at" DATETIME,
+    "created_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "Notification_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE INDEX "Notification_userId_created_at_idx" ON "Notification"("userId", "created_at");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "Notification_userId_game_id_key_key" ON "Notification"("userId", "game_id", "key");
@@ -0,0 +1,16 @@
+-- CreateTable
+CREATE TABLE "CloudSave" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "game_id" INTEGER NOT NULL,
+    "version" INTEGER NOT NULL,
+    "hash" TEXT NOT NULL,
+    "size" INTEGER NOT NULL,
+    "device" TEXT,
+    "storage_key" TEXT NOT NULL,
+    "created_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "CloudSave_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "CloudSave_userId_game_id_version_key" ON "CloudSave"("userId", "game_id", "version");
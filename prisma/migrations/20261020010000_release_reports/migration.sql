@@ -0,0 +1,25 @@
+-- CreateTable
+CREATE TABLE "ReleaseReport" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "link" TEXT NOT NULL,
+    "kind" TEXT NOT NULL,
+    "notes" TEXT,
+    "created_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" DATETIME NOT NULL,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "ReleaseReport_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateTable
+CREATE TABLE "HiddenRelease" (
+    "link" TEXT NOT NULL PRIMARY KEY,
+    "reason" TEXT,
+    "hidden_by" TEXT NOT NULL,
+    "hidden_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- CreateIndex
+CREATE INDEX "ReleaseReport_link_idx" ON "ReleaseReport"("link");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "ReleaseReport_userId_link_key" ON "ReleaseReport"("userId", "link");
@@ -0,0 +1,18 @@
+-- CreateTable
+CREATE TABLE "LinuxReport" (
+    "id" TEXT NOT NULL PRIMARY KEY,
+    "game_id" INTEGER NOT NULL,
+    "release" TEXT,
+    "runner" TEXT NOT NULL,
+    "runner_version" TEXT NOT NULL,
+    "distro" TEXT NOT NULL,
+    "gpu_vendor" TEXT NOT NULL,
+    "rating" TEXT NOT NULL,
+    "notes" TEXT,
+    "created_at" DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "userId" TEXT NOT NULL,
+    CONSTRAINT "LinuxReport_userId_fkey" FOREIGN KEY ("userId") REFERENCES "User" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+-- CreateIndex
+CREATE INDEX "LinuxReport_game_id_created_at_idx" ON "LinuxReport"("game_id", "created_at");
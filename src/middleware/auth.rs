@@ -1,69 +1,279 @@
+use crate::model::dto::token::TokenScope;
+use crate::model::error::{ErrorCode, ErrorResponse};
+use crate::prisma::{user_identity, PrismaClient};
+use crate::service::auth::{YANDEX_CLIENT_ID, YANDEX_LOGIN_URL};
+use crate::service::token::{TokenService, TOKEN_PREFIX};
 use actix_identity::Identity;
-use actix_web::{dev::Payload, Error, FromRequest, HttpRequest};
-use std::future::ready;
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::InternalError;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{web, Error, FromRequest, HttpMessage, HttpRequest};
+use futures::future::LocalBoxFuture;
+use lazy_static::lazy_static;
 use log::{error, warn};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde::Deserialize;
-use std::future::Future;
-use std::pin::Pin;
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Deserialize)]
-struct YandexTokenInfo {
+#[doc = "How long a Yandex token validation result is reused"]
+const TOKEN_CACHE_TTL: Duration = Duration::from_secs(60);
+
+lazy_static! {
+  static ref TOKEN_CACHE: RwLock<HashMap<String, (Instant, Option<YandexTokenInfo>)>> = RwLock::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YandexTokenInfo {
   pub id: String,
   pub login: String,
+  #[serde(default)]
   pub client_id: String,
+  #[serde(default)]
   pub psuid: String,
 }
 
+impl YandexTokenInfo {
+  #[doc = "Tokens issued to other Yandex apps must not sign users in here, without `VEK_YANDEX_CLIENT_ID` none are accepted"]
+  fn issued_to(&self, client_id: Option<&str>) -> bool {
+    client_id.map(|client_id| !client_id.is_empty() && self.client_id == client_id).unwrap_or(false)
+  }
+
+  #[doc = "Validate an access token against Yandex ID, reusing recent results"]
+  pub async fn validate(access_token: &str) -> Result<YandexTokenInfo, ErrorCode> {
+    let cached = TOKEN_CACHE
+      .read()
+      .ok()
+      .and_then(|cache| cache.get(access_token).cloned())
+      .filter(|(checked_at, _)| checked_at.elapsed() < TOKEN_CACHE_TTL);
+    if let Some((_, result)) = cached {
+      return result.ok_or(ErrorCode::AUTH003);
+    }
+
+    // Сбой Yandex — не повод считать токен недействительным: отвечаем 503 и не кэшируем результат.
+    let request_url = format!("{}/info?oauth_token={}", *YANDEX_LOGIN_URL, access_token);
+    let result = match Client::new().get(&request_url).send().await {
+      Ok(resp) if resp.status().is_success() => match resp.json::<YandexTokenInfo>().await {
+        Ok(token_info) if token_info.issued_to(YANDEX_CLIENT_ID.as_deref()) => Some(token_info),
+        Ok(token_info) => {
+          warn!("Токен Yandex пользователя {} выдан другому приложению: {}", token_info.login, token_info.client_id);
+          None
+        }
+        Err(e) => {
+          error!("Не удалось десериализовать ответ от Yandex API: {:?}", e.without_url());
+          return Err(ErrorCode::AUTH006);
+        }
+      },
+      Ok(resp) if resp.status().is_server_error() || resp.status() == StatusCode::TOO_MANY_REQUESTS => {
+        error!("Yandex API недоступен. Статус: {}", resp.status());
+        return Err(ErrorCode::AUTH006);
+      }
+      Ok(resp) => {
+        warn!("Недействительный ответ токена от Yandex API. Статус: {}", resp.status());
+        None
+      }
+      Err(e) => {
+        error!("Не удалось подключиться к Yandex API: {:?}", e.without_url());
+        return Err(ErrorCode::AUTH006);
+      }
+    };
+
+    if let Ok(mut cache) = TOKEN_CACHE.write() {
+      cache.retain(|_, (checked_at, _)| checked_at.elapsed() < TOKEN_CACHE_TTL);
+      cache.insert(access_token.to_string(), (Instant::now(), result.clone()));
+    }
+
+    result.ok_or(ErrorCode::AUTH003)
+  }
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+  req
+    .headers()
+    .get(AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(|token| token.trim().to_string())
+    .filter(|token| !token.is_empty())
+}
+
+fn unauthorized(code: ErrorCode) -> Error {
+  InternalError::from_response("Unauthorized", ErrorResponse::build(code)).into()
+}
+
 impl FromRequest for YandexTokenInfo {
   type Error = Error;
-  type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+  type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
   fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-    let identity_result = Identity::from_request(req, &mut Payload::None).into_inner();
-
-    if let Ok(identity) = identity_result {
-      if let Some(access_token) = identity.id().ok() {
-        let client = Client::new();
-        let request_url = format!("https://login.yandex.ru/info?oauth_token={}", access_token);
-
-        return Box::pin(async move {
-          match client.get(&request_url).send().await {
-            Ok(resp) => {
-              if resp.status().is_success() {
-                match resp.json::<YandexTokenInfo>().await {
-                  Ok(token_info) => {
-                    Ok(YandexTokenInfo {
-                      id: token_info.id,
-                      login: token_info.login,
-                      client_id: token_info.client_id,
-                      psuid: token_info.psuid,
-                    })
-                  }
-                  Err(e) => {
-                    error!("Не удалось десериализовать ответ от Yandex API: {:?}", e);
-                    Err(actix_web::error::ErrorInternalServerError("Не удалось разобрать ответ от Yandex API"))
-                  }
-                }
-              } else {
-                warn!("Недействительный ответ токена от Yandex API. Статус: {}", resp.status());
-                Err(actix_web::error::ErrorUnauthorized("Недействительный ответ от Yandex API"))
-              }
-            }
-            Err(e) => {
-              error!("Не удалось подключиться к Yandex API: {:?}", e);
-              Err(actix_web::error::ErrorInternalServerError("Не удалось подключиться к Yandex API"))
-            }
-          }
-        });
-      } else {
-        warn!("Найдена идентификация, но токен доступа отсутствует.");
+    let access_token = bearer_token(req);
+    Box::pin(async move {
+      match access_token {
+        Some(access_token) => YandexTokenInfo::validate(&access_token).await.map_err(unauthorized),
+        None => Err(unauthorized(ErrorCode::AUTH003)),
+      }
+    })
+  }
+}
+
+#[doc = "User resolved by `AuthGuard`, available to handlers inside guarded scopes"]
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+  pub id: String,
+  #[doc = "Scopes of the personal access token, `None` for a session or Yandex token"]
+  pub scopes: Option<Vec<TokenScope>>,
+}
+
+impl AuthenticatedUser {
+  #[doc = "`admin` implies every scope, any scope implies `read`"]
+  pub fn allows(&self, scope: TokenScope) -> bool {
+    match &self.scopes {
+      None => true,
+      Some(scopes) => {
+        scopes.contains(&scope) || scopes.contains(&TokenScope::Admin) || (scope == TokenScope::Read && !scopes.is_empty())
       }
-    } else {
-      warn!("Не удалось получить идентификацию.");
     }
+  }
+
+  #[doc = "Token management and account linking need an interactive session"]
+  pub fn require_session(&self) -> Result<(), ErrorCode> {
+    match self.scopes {
+      None => Ok(()),
+      Some(_) => Err(ErrorCode::AUTH004),
+    }
+  }
+}
+
+impl FromRequest for AuthenticatedUser {
+  type Error = Error;
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+    ready(
+      req
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .cloned()
+        .ok_or_else(|| unauthorized(ErrorCode::AUTH003)),
+    )
+  }
+}
+
+async fn authenticate_request(req: &HttpRequest) -> Result<AuthenticatedUser, ErrorCode> {
+  if let Ok(identity) = Identity::from_request(req, &mut Payload::None).into_inner() {
+    if let Ok(id) = identity.id() {
+      return Ok(AuthenticatedUser { id, scopes: None });
+    }
+  }
+
+  let access_token = match bearer_token(req) {
+    Some(access_token) => access_token,
+    None => {
+      warn!("Идентификация не найдена, несанкционированный доступ.");
+      return Err(ErrorCode::AUTH003);
+    }
+  };
+
+  let client = req.app_data::<web::Data<PrismaClient>>().ok_or(ErrorCode::INTERNAL001)?;
+  if access_token.starts_with(TOKEN_PREFIX) {
+    let owner = TokenService::authenticate(client, &access_token).await?;
+    let user = AuthenticatedUser { id: owner.user_id, scopes: Some(owner.scopes) };
+    // Токен только для чтения не может менять данные.
+    if !req.method().is_safe() && !user.allows(TokenScope::Library) {
+      return Err(ErrorCode::AUTH004);
+    }
+    return Ok(user);
+  }
+
+  let token_info = YandexTokenInfo::validate(&access_token).await?;
+  let identity = client
+    .user_identity()
+    .find_first(vec![
+      user_identity::provider::equals("yandex".to_string()),
+      user_identity::subject::equals(token_info.login.clone()),
+    ])
+    .exec()
+    .await?;
+  match identity {
+    Some(identity) => Ok(AuthenticatedUser { id: identity.user_id, scopes: None }),
+    None => {
+      warn!("Токен Yandex действителен, но пользователь {} не зарегистрирован", token_info.login);
+      Err(ErrorCode::AUTH003)
+    }
+  }
+}
+
+#[doc = "Rejects requests without a session identity, a personal access token or a valid Yandex bearer token"]
+pub struct AuthGuard;
+
+impl<S, B> Transform<S, ServiceRequest> for AuthGuard
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Transform = AuthGuardMiddleware<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(AuthGuardMiddleware { service: Rc::new(service) }))
+  }
+}
+
+pub struct AuthGuardMiddleware<S> {
+  service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthGuardMiddleware<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let service = self.service.clone();
+    Box::pin(async move {
+      match authenticate_request(req.request()).await {
+        Ok(user) => {
+          req.extensions_mut().insert(user);
+          service.call(req).await.map(ServiceResponse::map_into_left_body)
+        }
+        Err(code) => Ok(req.into_response(ErrorResponse::build(code)).map_into_right_body()),
+      }
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn token_info(client_id: &str) -> YandexTokenInfo {
+    YandexTokenInfo {
+      id: "1".to_string(),
+      login: "user".to_string(),
+      client_id: client_id.to_string(),
+      psuid: String::new(),
+    }
+  }
 
-    warn!("Идентификация не найдена, несанкционированный доступ.");
-    Box::pin(ready(Err(actix_web::error::ErrorUnauthorized("Несанкционированный доступ"))))
+  #[test]
+  fn only_tokens_issued_to_our_client_are_accepted() {
+    assert!(token_info("vek").issued_to(Some("vek")));
+    assert!(!token_info("other-app").issued_to(Some("vek")));
+    assert!(!token_info("").issued_to(Some("vek")));
+    assert!(!token_info("").issued_to(Some("")));
+    assert!(!token_info("vek").issued_to(None));
   }
 }
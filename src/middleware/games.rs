@@ -14,6 +14,14 @@ impl GamesMiddleware {
         }
     }
 
+    /// Без обращения к rawg.io, для тестов с заглушкой RAWG.
+    #[cfg(test)]
+    pub fn with_api_key(api_key: &str) -> Self {
+        GamesMiddleware {
+            api_key: Arc::new(Mutex::new(api_key.to_string())),
+        }
+    }
+
     pub async fn update_api_key() -> Result<String, Box<dyn std::error::Error>> {
         let url = "https://rawg.io/";
         let resp = reqwest::get(url).await?;
@@ -29,6 +37,10 @@ impl GamesMiddleware {
         Err("API Key not found".into())
     }
 
+    pub async fn current_api_key(&self) -> String {
+        self.api_key.lock().await.clone()
+    }
+
     pub async fn execute_with_retry<F, Fut, T>(&self, func: F) -> Result<T, reqwest::Error>
     where
         F: Fn(String) -> Fut,
@@ -39,7 +51,8 @@ impl GamesMiddleware {
             Ok(result) => Ok(result),
             Err(err) => {
                 if err.status() == Some(reqwest::StatusCode::UNAUTHORIZED) {
-                    if let Ok(new_api_key) = Self::update_api_key().await {
+                    let new_api_key = Self::update_api_key().await.ok();
+                    if let Some(new_api_key) = new_api_key {
                         let mut key = self.api_key.lock().await;
                         *key = new_api_key.clone();
                         api_key = new_api_key.clone();
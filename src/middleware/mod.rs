@@ -1,2 +1,4 @@
 pub(crate) mod auth;
 pub(crate) mod games;
+pub(crate) mod rbac;
+pub(crate) mod session;
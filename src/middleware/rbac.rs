@@ -0,0 +1,92 @@
+use crate::middleware::auth::AuthenticatedUser;
+use crate::model::dto::rbac::Permission;
+use crate::model::dto::token::TokenScope;
+use crate::model::error::{ErrorCode, ErrorResponse};
+use crate::prisma::PrismaClient;
+use crate::service::rbac::RbacService;
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, Error, HttpMessage};
+use futures::future::LocalBoxFuture;
+use log::warn;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+async fn check_permission(client: &PrismaClient, user: &AuthenticatedUser, permission: Permission) -> Result<(), ErrorCode> {
+  // Токену доступа для администрирования нужен scope admin, права берутся из ролей владельца.
+  if !user.allows(TokenScope::Admin) {
+    return Err(ErrorCode::AUTH004);
+  }
+
+  if RbacService::user_permissions(client, &user.id).await?.contains(&permission) {
+    Ok(())
+  } else {
+    Err(ErrorCode::AUTH005)
+  }
+}
+
+async fn authorize(req: &ServiceRequest, permission: Permission) -> Result<(), ErrorCode> {
+  let user = req
+    .extensions()
+    .get::<AuthenticatedUser>()
+    .cloned()
+    .ok_or(ErrorCode::AUTH003)?;
+  let client = req.app_data::<web::Data<PrismaClient>>().ok_or(ErrorCode::INTERNAL001)?;
+  let result = check_permission(client, &user, permission).await;
+  if let Err(ErrorCode::AUTH005) = result {
+    warn!("Пользователю {} не хватает права {}", user.id, permission.as_str());
+  }
+  result
+}
+
+#[doc = "Same check as `RequirePermission` for handlers where the permission only widens what the user may do, e.g. moderators deleting other users' reviews"]
+pub async fn has_permission(client: &PrismaClient, user: &AuthenticatedUser, permission: Permission) -> bool {
+  check_permission(client, user, permission).await.is_ok()
+}
+
+#[doc = "Rejects requests whose user has no role granting the permission, must run inside `AuthGuard`"]
+pub struct RequirePermission(pub Permission);
+
+impl<S, B> Transform<S, ServiceRequest> for RequirePermission
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Transform = RequirePermissionMiddleware<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(RequirePermissionMiddleware { service: Rc::new(service), permission: self.0 }))
+  }
+}
+
+pub struct RequirePermissionMiddleware<S> {
+  service: Rc<S>,
+  permission: Permission,
+}
+
+impl<S, B> Service<ServiceRequest> for RequirePermissionMiddleware<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<EitherBody<B>>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let service = self.service.clone();
+    let permission = self.permission;
+    Box::pin(async move {
+      match authorize(&req, permission).await {
+        Ok(()) => service.call(req).await.map(ServiceResponse::map_into_left_body),
+        Err(code) => Ok(req.into_response(ErrorResponse::build(code)).map_into_right_body()),
+      }
+    })
+  }
+}
@@ -0,0 +1,168 @@
+use crate::modules::helpers::random_token;
+use crate::service::session::{SESSION_DEVICE_KEY, SESSION_ID_KEY, SESSION_IP_KEY, SESSION_USER_AGENT_KEY};
+use actix_session::{Session, SessionExt};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::USER_AGENT;
+use actix_web::Error;
+use futures::future::LocalBoxFuture;
+use lazy_static::lazy_static;
+use log::warn;
+use std::env;
+use std::future::{ready, Ready};
+use std::net::IpAddr;
+use std::rc::Rc;
+
+#[doc = "Header the launcher uses to name the device in the session list"]
+const DEVICE_HEADER: &str = "X-Device-Name";
+
+lazy_static! {
+  #[doc = "Proxies from `VEK_TRUSTED_PROXIES` (comma-separated IPs) whose `Forwarded`/`X-Forwarded-For` is believed"]
+  static ref TRUSTED_PROXIES: Vec<IpAddr> = env::var("VEK_TRUSTED_PROXIES")
+    .map(|value| parse_trusted_proxies(&value))
+    .unwrap_or_default();
+}
+
+fn parse_trusted_proxies(value: &str) -> Vec<IpAddr> {
+  value
+    .split(',')
+    .map(str::trim)
+    .filter(|entry| !entry.is_empty())
+    .filter_map(|entry| match entry.parse() {
+      Ok(ip) => Some(ip),
+      Err(_) => {
+        warn!("VEK_TRUSTED_PROXIES: пропущен некорректный адрес {}", entry);
+        None
+      }
+    })
+    .collect()
+}
+
+#[doc = "Address of the connected peer, or the forwarded client address if the peer is a trusted proxy"]
+fn client_ip(peer: Option<IpAddr>, forwarded: Option<&str>, trusted: &[IpAddr]) -> Option<String> {
+  let peer = peer?;
+  if trusted.contains(&peer) {
+    if let Some(forwarded) = forwarded {
+      return Some(forwarded.to_string());
+    }
+  }
+  Some(peer.to_string())
+}
+
+fn device_from_user_agent(user_agent: &str) -> Option<&'static str> {
+  [
+    ("Windows", "Windows"),
+    ("Android", "Android"),
+    ("iPhone", "iOS"),
+    ("iPad", "iOS"),
+    ("Mac OS X", "macOS"),
+    ("Linux", "Linux"),
+  ]
+  .iter()
+  .find(|(marker, _)| user_agent.contains(marker))
+  .map(|(_, device)| *device)
+}
+
+fn record(session: &Session, key: &str, value: Option<String>) {
+  let value = match value {
+    Some(value) => value,
+    None => return,
+  };
+  if session.get::<String>(key).ok().flatten().as_ref() != Some(&value) {
+    if let Err(e) = session.insert(key, value) {
+      warn!("Не удалось записать {} в сессию: {:?}", key, e);
+    }
+  }
+}
+
+#[doc = "Records device, IP and user agent in non-empty sessions for `SqliteSessionStore`"]
+#[doc = "Must be wrapped inside `SessionMiddleware`"]
+pub struct SessionMetadata;
+
+impl<S, B> Transform<S, ServiceRequest> for SessionMetadata
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Transform = SessionMetadataMiddleware<S>;
+  type InitError = ();
+  type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+  fn new_transform(&self, service: S) -> Self::Future {
+    ready(Ok(SessionMetadataMiddleware { service: Rc::new(service) }))
+  }
+}
+
+pub struct SessionMetadataMiddleware<S> {
+  service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for SessionMetadataMiddleware<S>
+where
+  S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+  B: 'static,
+{
+  type Response = ServiceResponse<B>;
+  type Error = Error;
+  type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  forward_ready!(service);
+
+  fn call(&self, req: ServiceRequest) -> Self::Future {
+    let ip = client_ip(
+      req.peer_addr().map(|addr| addr.ip()),
+      req.connection_info().realip_remote_addr(),
+      &TRUSTED_PROXIES,
+    );
+    let user_agent = req
+      .headers()
+      .get(USER_AGENT)
+      .and_then(|value| value.to_str().ok())
+      .map(str::to_string);
+    let device = req
+      .headers()
+      .get(DEVICE_HEADER)
+      .and_then(|value| value.to_str().ok())
+      .map(str::to_string)
+      .or_else(|| user_agent.as_deref().and_then(device_from_user_agent).map(str::to_string));
+
+    let service = self.service.clone();
+    Box::pin(async move {
+      let res = service.call(req).await?;
+
+      // Анонимные запросы не создают сессий, поэтому метаданные пишутся только в непустые.
+      let session = res.request().get_session();
+      let has_state = !session.entries().is_empty();
+      if has_state {
+        if session.get::<String>(SESSION_ID_KEY).ok().flatten().is_none() {
+          record(&session, SESSION_ID_KEY, random_token(16).ok());
+        }
+        record(&session, SESSION_IP_KEY, ip);
+        record(&session, SESSION_USER_AGENT_KEY, user_agent);
+        record(&session, SESSION_DEVICE_KEY, device);
+      }
+
+      Ok(res)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn forwarded_addresses_are_used_only_behind_trusted_proxies() {
+    let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+    let client: IpAddr = "203.0.113.7".parse().unwrap();
+    let trusted = parse_trusted_proxies("10.0.0.2, not-an-ip,");
+    assert_eq!(trusted, vec![proxy]);
+
+    assert_eq!(client_ip(Some(proxy), Some("198.51.100.1"), &trusted).as_deref(), Some("198.51.100.1"));
+    assert_eq!(client_ip(Some(client), Some("198.51.100.1"), &trusted).as_deref(), Some("203.0.113.7"));
+    assert_eq!(client_ip(Some(client), Some("198.51.100.1"), &[]).as_deref(), Some("203.0.113.7"));
+    assert_eq!(client_ip(Some(proxy), None, &trusted).as_deref(), Some("10.0.0.2"));
+    assert_eq!(client_ip(None, Some("198.51.100.1"), &trusted), None);
+  }
+}
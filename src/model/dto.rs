@@ -10,6 +10,25 @@ pub mod auth {
     pub expires_in: i32,
     pub scope: String,
   }
+
+  #[doc = "Yandex OAuth token endpoint response"]
+  #[derive(Serialize, Debug, Deserialize)]
+  pub struct YandexTokenResponse {
+    pub token_type: String,
+    pub access_token: String,
+    pub expires_in: i32,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+  }
+
+  #[doc = "External account linked to the user, without stored tokens"]
+  #[derive(Serialize, Debug, Deserialize)]
+  pub struct IdentityInfo {
+    pub provider: String,
+    pub subject: String,
+    pub login: Option<String>,
+    pub linked_at: String,
+  }
 }
 
 pub mod games {
@@ -20,6 +39,27 @@ pub mod games {
   pub struct GameListRequest {
     pub page: Option<usize>,
     pub next: Option<String>,
+    #[doc = "Comma separated genre ids or slugs"]
+    pub genres: Option<String>,
+    #[doc = "Comma separated tag ids or slugs"]
+    pub tags: Option<String>,
+    #[doc = "Comma separated platform ids"]
+    pub platforms: Option<String>,
+    #[doc = "Comma separated developer ids or slugs"]
+    pub developers: Option<String>,
+    #[doc = "Comma separated publisher ids or slugs"]
+    pub publishers: Option<String>,
+    #[doc = "Release date lower bound, YYYY-MM-DD"]
+    pub released_from: Option<String>,
+    #[doc = "Release date upper bound, YYYY-MM-DD"]
+    pub released_to: Option<String>,
+    pub metacritic_min: Option<u8>,
+    pub metacritic_max: Option<u8>,
+    #[doc = "RAWG ordering field, prefixed with '-' for descending order"]
+    pub ordering: Option<String>,
+    pub page_size: Option<usize>,
+    #[doc = "Keep only games with a release in the local torrent index; pages may hold fewer than `page_size` games, follow `next` until it is null"]
+    pub has_torrent: Option<bool>,
   }
 
   #[derive(Clone)] 
@@ -29,6 +69,13 @@ pub mod games {
     pub next: Option<String>,
   }
 
+  #[derive(Clone)] 
+  #[derive(Serialize, Deserialize)]
+  pub struct GameSearchPageRequest {
+    #[doc = "Cursor or `next` link from a previous search response"]
+    pub next: String,
+  }
+
   #[derive(Clone)] 
   #[derive(Serialize, Deserialize)]
   pub struct GameDetailsRequest {
@@ -48,6 +95,813 @@ pub mod games {
     pub page: Option<usize>,
     pub next: Option<String>,
   }
+
+  #[doc = "Section of an aggregated response, failed sections carry an error instead of data"]
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct GameSection<T> {
+    pub data: Option<T>,
+    pub error: Option<String>,
+  }
+
+  #[doc = "Game page: details, media and matching releases in one payload"]
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct GameFullResponse {
+    pub details: GameSection<serde_json::Value>,
+    pub screenshots: GameSection<serde_json::Value>,
+    pub movies: GameSection<serde_json::Value>,
+    pub torrents: GameSection<Vec<super::torrent::TorrentRelease>>,
+  }
+
+  #[doc = "Paging for DLCs, series, parent games, achievements and store links"]
+  #[derive(Clone)] 
+  #[derive(Serialize, Deserialize)]
+  pub struct GameRelationRequest {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    pub next: Option<String>,
+  }
+}
+
+pub mod catalog {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct CatalogListRequest {
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+    pub next: Option<String>,
+  }
+
+  #[doc = "Genre, platform, tag, developer, publisher or store"]
+  #[derive(Clone, Debug, Serialize, Deserialize)]
+  pub struct CatalogEntry {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub games_count: Option<i64>,
+    pub image_background: Option<String>,
+    pub image_background_proxy: Option<String>,
+  }
+
+  #[derive(Clone, Debug, Serialize, Deserialize)]
+  pub struct CatalogDetails {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub games_count: Option<i64>,
+    pub image_background: Option<String>,
+    pub image_background_proxy: Option<String>,
+    pub image: Option<String>,
+    pub image_proxy: Option<String>,
+    pub domain: Option<String>,
+    pub description: Option<String>,
+  }
+
+  #[doc = "Page of RAWG results with pagination links rewritten to API cursors"]
+  #[derive(Clone, Debug, Serialize, Deserialize)]
+  pub struct PaginatedResponse<T> {
+    pub count: i64,
+    pub next: Option<String>,
+    pub previous: Option<String>,
+    pub results: Vec<T>,
+  }
+}
+
+pub mod images {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct ImageRequest {
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+    #[doc = "Output format: webp, jpeg or png"]
+    pub fmt: Option<String>,
+  }
+}
+
+pub mod requirements {
+  use serde::{Deserialize, Serialize};
+
+  #[doc = "Structured PC system requirements parsed from the RAWG free-form text"]
+  #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+  pub struct SystemRequirements {
+    pub os: Option<String>,
+    pub cpu: Option<String>,
+    pub ram_gb: Option<f32>,
+    pub gpu: Option<String>,
+    pub directx: Option<u32>,
+    pub storage_gb: Option<f32>,
+  }
+
+  #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+  pub struct PcRequirements {
+    pub minimum: Option<SystemRequirements>,
+    pub recommended: Option<SystemRequirements>,
+  }
+
+  #[doc = "Hardware profile submitted by the launcher"]
+  #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+  pub struct HardwareProfile {
+    pub os: Option<String>,
+    pub cpu: Option<String>,
+    pub ram_gb: Option<f32>,
+    pub gpu: Option<String>,
+    pub directx: Option<u32>,
+    #[doc = "Free disk space"]
+    pub storage_gb: Option<f32>,
+  }
+
+  #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+  #[serde(rename_all = "lowercase")]
+  pub enum Verdict {
+    Unknown,
+    Pass,
+    Warn,
+    Fail,
+  }
+
+  #[derive(Clone, Debug, Serialize, Deserialize)]
+  pub struct ComponentVerdict {
+    pub component: String,
+    pub verdict: Verdict,
+    pub minimum: Option<String>,
+    pub recommended: Option<String>,
+    pub provided: Option<String>,
+    pub note: Option<String>,
+  }
+
+  #[derive(Clone, Debug, Serialize, Deserialize)]
+  pub struct CompatibilityResponse {
+    pub game_id: i32,
+    pub overall: Verdict,
+    pub components: Vec<ComponentVerdict>,
+  }
+}
+
+pub mod profile {
+  use serde::{Deserialize, Serialize};
+
+  #[doc = "Profile update, omitted fields are left as is"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct UpdateProfileRequest {
+    #[doc = "Empty string clears the display name"]
+    pub display_name: Option<String>,
+    #[doc = "Language tag, e.g. `ru` or `en-US`"]
+    pub locale: Option<String>,
+    #[doc = "IANA time zone, e.g. `Europe/Moscow`"]
+    pub timezone: Option<String>,
+  }
+}
+
+pub mod session {
+  use serde::{Deserialize, Serialize};
+
+  #[doc = "Active login session of the current user"]
+  #[derive(Serialize, Debug, Deserialize)]
+  pub struct SessionInfo {
+    pub id: String,
+    pub device: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+    pub last_seen: String,
+    pub expires_at: String,
+    #[doc = "Session the request was made with"]
+    pub current: bool,
+  }
+}
+
+pub mod token {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+  #[serde(rename_all = "lowercase")]
+  pub enum TokenScope {
+    #[doc = "Read-only access to the account"]
+    Read,
+    #[doc = "Read access plus changes to the game library"]
+    Library,
+    #[doc = "Everything, including admin endpoints for admins"]
+    Admin,
+  }
+
+  impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        TokenScope::Read => "read",
+        TokenScope::Library => "library",
+        TokenScope::Admin => "admin",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<TokenScope> {
+      match value {
+        "read" => Some(TokenScope::Read),
+        "library" => Some(TokenScope::Library),
+        "admin" => Some(TokenScope::Admin),
+        _ => None,
+      }
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct CreateTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+  }
+
+  #[doc = "Personal access token without its secret"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct AccessTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<TokenScope>,
+    pub created_at: String,
+    pub last_used: Option<String>,
+  }
+
+  #[doc = "Returned once on creation, the token itself cannot be read again"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct CreatedTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: AccessTokenInfo,
+  }
+}
+
+pub mod library {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+  #[serde(rename_all = "lowercase")]
+  pub enum LibraryStatus {
+    Wishlist,
+    Installed,
+    Playing,
+    Completed,
+  }
+
+  impl LibraryStatus {
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        LibraryStatus::Wishlist => "wishlist",
+        LibraryStatus::Installed => "installed",
+        LibraryStatus::Playing => "playing",
+        LibraryStatus::Completed => "completed",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<LibraryStatus> {
+      match value {
+        "wishlist" => Some(LibraryStatus::Wishlist),
+        "installed" => Some(LibraryStatus::Installed),
+        "playing" => Some(LibraryStatus::Playing),
+        "completed" => Some(LibraryStatus::Completed),
+        _ => None,
+      }
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct LibraryQuery {
+    pub status: Option<LibraryStatus>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct CreateLibraryEntryRequest {
+    #[doc = "RAWG game id"]
+    pub game_id: i32,
+    pub status: LibraryStatus,
+    pub installed_version: Option<String>,
+    #[doc = "Hash of the install path computed by the launcher, the path itself is never sent"]
+    pub install_path_hash: Option<String>,
+  }
+
+  #[doc = "Omitted fields are left as is, an empty string clears the field"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct UpdateLibraryEntryRequest {
+    pub status: Option<LibraryStatus>,
+    pub installed_version: Option<String>,
+    pub install_path_hash: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct LibraryEntryInfo {
+    pub id: String,
+    pub game_id: i32,
+    pub status: LibraryStatus,
+    pub installed_version: Option<String>,
+    pub install_path_hash: Option<String>,
+    pub added_at: String,
+    pub updated_at: String,
+    pub last_played: Option<String>,
+    #[doc = "Total playtime in seconds"]
+    pub playtime: i32,
+    #[doc = "Playtime over the last two weeks in seconds"]
+    pub recent_playtime: i32,
+  }
+
+  #[doc = "Wishlisted game with what the notifier last saw for it"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct WishlistItem {
+    pub game_id: i32,
+    pub added_at: String,
+    pub name: Option<String>,
+    pub released: Option<String>,
+    pub price: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct PlaySessionInfo {
+    pub id: String,
+    pub game_id: i32,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    #[doc = "Seconds, set when the session is stopped"]
+    pub duration: Option<i32>,
+  }
+}
+
+pub mod notification {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+  #[serde(rename_all = "lowercase")]
+  pub enum NotificationKind {
+    #[doc = "A wishlisted game was released"]
+    Release,
+    #[doc = "A new repack of a wishlisted game appeared in the torrent index"]
+    Repack,
+    #[doc = "A wishlisted game got cheaper"]
+    Price,
+  }
+
+  impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        NotificationKind::Release => "release",
+        NotificationKind::Repack => "repack",
+        NotificationKind::Price => "price",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<NotificationKind> {
+      match value {
+        "release" => Some(NotificationKind::Release),
+        "repack" => Some(NotificationKind::Repack),
+        "price" => Some(NotificationKind::Price),
+        _ => None,
+      }
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct NotificationQuery {
+    #[doc = "Only unread notifications"]
+    pub unread: Option<bool>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct NotificationInfo {
+    pub id: String,
+    pub kind: NotificationKind,
+    pub game_id: i32,
+    pub title: String,
+    pub body: Option<String>,
+    pub read: bool,
+    pub created_at: String,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct NotificationList {
+    #[doc = "Unread count regardless of the filter"]
+    pub unread: i64,
+    pub notifications: Vec<NotificationInfo>,
+  }
+}
+
+pub mod webhook {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+  pub enum WebhookEvent {
+    #[doc = "A release appeared in the torrent index"]
+    #[serde(rename = "torrent.added")]
+    TorrentAdded,
+    #[doc = "A new release was matched to a catalog game"]
+    #[serde(rename = "game.linked")]
+    GameLinked,
+    #[doc = "A torrent provider failed during an index update"]
+    #[serde(rename = "scrape.failed")]
+    ScrapeFailed,
+  }
+
+  impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 3] = [WebhookEvent::TorrentAdded, WebhookEvent::GameLinked, WebhookEvent::ScrapeFailed];
+
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        WebhookEvent::TorrentAdded => "torrent.added",
+        WebhookEvent::GameLinked => "game.linked",
+        WebhookEvent::ScrapeFailed => "scrape.failed",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<WebhookEvent> {
+      WebhookEvent::ALL.into_iter().find(|event| event.as_str() == value)
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+  }
+
+  #[doc = "Omitted fields are left as is"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub active: Option<bool>,
+  }
+
+  #[doc = "Webhook without its signing secret"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct WebhookInfo {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub active: bool,
+    pub created_at: String,
+  }
+
+  #[doc = "Returned once on creation, the secret cannot be read again"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct CreatedWebhookResponse {
+    pub secret: String,
+    #[serde(flatten)]
+    pub info: WebhookInfo,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct WebhookDeliveryInfo {
+    pub id: String,
+    pub event: String,
+    pub attempts: i32,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub delivered_at: Option<String>,
+    #[doc = "When the next retry is due, empty once delivered or given up"]
+    pub next_attempt: Option<String>,
+    pub created_at: String,
+  }
+}
+
+pub mod saves {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct UploadSaveQuery {
+    #[doc = "Device name shown in the version history"]
+    pub device: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct SaveInfo {
+    pub id: String,
+    pub game_id: i32,
+    pub version: i32,
+    #[doc = "SHA-256 of the archive, hex encoded"]
+    pub hash: String,
+    pub size: i32,
+    pub device: Option<String>,
+    pub created_at: String,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct SaveGameSummary {
+    pub game_id: i32,
+    pub latest: SaveInfo,
+    pub versions: usize,
+    #[doc = "Total size of all stored versions"]
+    pub size: i64,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct SaveOverview {
+    pub used: i64,
+    pub quota: i64,
+    pub games: Vec<SaveGameSummary>,
+  }
+}
+
+pub mod reviews {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+  #[serde(rename_all = "lowercase")]
+  pub enum ReviewSort {
+    #[default]
+    Newest,
+    #[doc = "Most helpful votes first, newest among equals"]
+    Helpful,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReviewQuery {
+    pub sort: Option<ReviewSort>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct CreateReviewRequest {
+    #[doc = "1 to 5"]
+    pub rating: i32,
+    pub body: Option<String>,
+  }
+
+  #[doc = "Omitted fields are left as is, an empty body removes the text"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct UpdateReviewRequest {
+    pub rating: Option<i32>,
+    pub body: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReviewInfo {
+    pub id: String,
+    pub game_id: i32,
+    pub user_id: String,
+    #[doc = "Display name, or login when it is not set"]
+    pub author: String,
+    pub rating: i32,
+    pub body: Option<String>,
+    pub helpful: i32,
+    pub created_at: String,
+    pub updated_at: String,
+  }
+
+  #[doc = "Aggregated user ratings on the same 1 to 5 scale as RAWG's `rating`"]
+  #[derive(Serialize, Deserialize, Debug, Default)]
+  pub struct CommunityScore {
+    pub average: Option<f64>,
+    pub count: i64,
+    #[doc = "Number of ratings per star, from 1 to 5"]
+    pub distribution: [i64; 5],
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReviewPage {
+    pub score: CommunityScore,
+    pub page: usize,
+    pub page_size: usize,
+    pub results: Vec<ReviewInfo>,
+  }
+}
+
+pub mod linux {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+  #[serde(rename_all = "lowercase")]
+  pub enum LinuxRunner {
+    Proton,
+    Wine,
+  }
+
+  impl LinuxRunner {
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        LinuxRunner::Proton => "proton",
+        LinuxRunner::Wine => "wine",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<LinuxRunner> {
+      match value {
+        "proton" => Some(LinuxRunner::Proton),
+        "wine" => Some(LinuxRunner::Wine),
+        _ => None,
+      }
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+  #[serde(rename_all = "lowercase")]
+  pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+    Other,
+  }
+
+  impl GpuVendor {
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        GpuVendor::Nvidia => "nvidia",
+        GpuVendor::Amd => "amd",
+        GpuVendor::Intel => "intel",
+        GpuVendor::Other => "other",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<GpuVendor> {
+      match value {
+        "nvidia" => Some(GpuVendor::Nvidia),
+        "amd" => Some(GpuVendor::Amd),
+        "intel" => Some(GpuVendor::Intel),
+        "other" => Some(GpuVendor::Other),
+        _ => None,
+      }
+    }
+  }
+
+  #[doc = "ProtonDB tiers, ordered from worst to best"]
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+  #[serde(rename_all = "lowercase")]
+  pub enum LinuxRating {
+    #[doc = "Does not start or is unplayable"]
+    Borked,
+    Bronze,
+    Silver,
+    Gold,
+    #[doc = "Works perfectly out of the box"]
+    Platinum,
+  }
+
+  impl LinuxRating {
+    pub const ALL: [LinuxRating; 5] = [
+      LinuxRating::Borked,
+      LinuxRating::Bronze,
+      LinuxRating::Silver,
+      LinuxRating::Gold,
+      LinuxRating::Platinum,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        LinuxRating::Borked => "borked",
+        LinuxRating::Bronze => "bronze",
+        LinuxRating::Silver => "silver",
+        LinuxRating::Gold => "gold",
+        LinuxRating::Platinum => "platinum",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<LinuxRating> {
+      LinuxRating::ALL.into_iter().find(|rating| rating.as_str() == value)
+    }
+
+    #[doc = "0 for borked up to 4 for platinum"]
+    pub fn score(&self) -> usize {
+      LinuxRating::ALL.iter().position(|rating| rating == self).unwrap_or(0)
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct CreateLinuxReportRequest {
+    #[doc = "Torrent link of the release that was tested, as returned by `/api/torrent`"]
+    pub torrent: Option<String>,
+    pub runner: LinuxRunner,
+    #[doc = "For example `GE-Proton9-20` or `wine-9.0`"]
+    pub runner_version: String,
+    pub distro: String,
+    pub gpu_vendor: GpuVendor,
+    pub rating: LinuxRating,
+    pub notes: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct LinuxReportQuery {
+    pub runner: Option<LinuxRunner>,
+    pub gpu_vendor: Option<GpuVendor>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct LinuxReportInfo {
+    pub id: String,
+    pub game_id: i32,
+    pub user_id: String,
+    pub torrent: Option<String>,
+    pub runner: LinuxRunner,
+    pub runner_version: String,
+    pub distro: String,
+    pub gpu_vendor: GpuVendor,
+    pub rating: LinuxRating,
+    pub notes: Option<String>,
+    pub created_at: String,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Default)]
+  pub struct LinuxRatingCounts {
+    pub platinum: i64,
+    pub gold: i64,
+    pub silver: i64,
+    pub bronze: i64,
+    pub borked: i64,
+  }
+
+  #[doc = "ProtonDB-style summary over all reports for a game"]
+  #[derive(Serialize, Deserialize, Debug, Default)]
+  pub struct LinuxSummary {
+    pub count: i64,
+    #[doc = "Average tier over all reports"]
+    pub tier: Option<LinuxRating>,
+    #[doc = "Average tier over the most recent reports"]
+    pub trending: Option<LinuxRating>,
+    #[doc = "Best tier anyone reported"]
+    pub best: Option<LinuxRating>,
+    #[doc = "`low`, `moderate`, `good` or `strong`, depending on the number of reports"]
+    pub confidence: Option<String>,
+    pub distribution: LinuxRatingCounts,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct LinuxReportPage {
+    pub summary: LinuxSummary,
+    pub page: usize,
+    pub page_size: usize,
+    pub results: Vec<LinuxReportInfo>,
+  }
+}
+
+pub mod rbac {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  pub enum Permission {
+    #[doc = "Grant and revoke roles"]
+    #[serde(rename = "roles:manage")]
+    ManageRoles,
+    #[doc = "Read other users' accounts"]
+    #[serde(rename = "users:read")]
+    ReadUsers,
+    #[doc = "Run catalog and torrent index maintenance"]
+    #[serde(rename = "catalog:manage")]
+    ManageCatalog,
+    #[doc = "Hide or remove user generated content"]
+    #[serde(rename = "content:moderate")]
+    ModerateContent,
+  }
+
+  impl Permission {
+    pub const ALL: [Permission; 4] = [
+      Permission::ManageRoles,
+      Permission::ReadUsers,
+      Permission::ManageCatalog,
+      Permission::ModerateContent,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        Permission::ManageRoles => "roles:manage",
+        Permission::ReadUsers => "users:read",
+        Permission::ManageCatalog => "catalog:manage",
+        Permission::ModerateContent => "content:moderate",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<Permission> {
+      Permission::ALL.into_iter().find(|permission| permission.as_str() == value)
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct RoleInfo {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+  }
+}
+
+pub mod search {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct SuggestRequest {
+    pub q: String,
+    pub limit: Option<usize>,
+  }
+
+  #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+  #[serde(rename_all = "lowercase")]
+  pub enum SuggestionKind {
+    Game,
+    Release,
+  }
+
+  #[doc = "Type-ahead suggestion: a cached game or an indexed release"]
+  #[derive(Clone, Debug, Serialize, Deserialize)]
+  pub struct Suggestion {
+    pub kind: SuggestionKind,
+    pub name: String,
+    pub rawg_id: Option<i32>,
+    pub cover: Option<String>,
+  }
 }
 
 pub mod torrent {
@@ -56,6 +910,8 @@ pub mod torrent {
   #[derive(Clone, Serialize, Deserialize)]
   pub struct TorrentInfoRequest {
     pub name: String,
+    #[doc = "Return `TorrentRelease` objects with report stats instead of `[repacker, torrent]` pairs"]
+    pub with_reports: Option<bool>,
   }
 
   #[derive(Clone, Serialize, Deserialize)]
@@ -63,5 +919,119 @@ pub mod torrent {
     pub updated: String,
     pub magnet: String,
   }
+
+  #[derive(Clone, Serialize, Deserialize)]
+  pub struct TorrentRelease {
+    pub repacker: String,
+    pub torrent: String,
+    #[serde(default)]
+    pub reports: ReleaseReportStats,
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+  #[serde(rename_all = "snake_case")]
+  pub enum ReportKind {
+    Working,
+    Broken,
+    WrongGame,
+    #[doc = "Looks like malware or a fake release"]
+    Suspicious,
+  }
+
+  impl ReportKind {
+    pub fn as_str(&self) -> &'static str {
+      match self {
+        ReportKind::Working => "working",
+        ReportKind::Broken => "broken",
+        ReportKind::WrongGame => "wrong_game",
+        ReportKind::Suspicious => "suspicious",
+      }
+    }
+
+    pub fn parse(value: &str) -> Option<ReportKind> {
+      match value {
+        "working" => Some(ReportKind::Working),
+        "broken" => Some(ReportKind::Broken),
+        "wrong_game" => Some(ReportKind::WrongGame),
+        "suspicious" => Some(ReportKind::Suspicious),
+        _ => None,
+      }
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+  pub struct ReleaseReportStats {
+    pub working: i64,
+    pub broken: i64,
+    pub wrong_game: i64,
+    pub suspicious: i64,
+  }
+
+  impl ReleaseReportStats {
+    pub fn add(&mut self, kind: ReportKind) {
+      match kind {
+        ReportKind::Working => self.working += 1,
+        ReportKind::Broken => self.broken += 1,
+        ReportKind::WrongGame => self.wrong_game += 1,
+        ReportKind::Suspicious => self.suspicious += 1,
+      }
+    }
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReleaseQuery {
+    #[doc = "Torrent link as returned by `/api/torrent`"]
+    pub torrent: String,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReportReleaseRequest {
+    pub torrent: String,
+    pub kind: ReportKind,
+    pub notes: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReportReviewQuery {
+    #[doc = "Only releases with at least one report of this kind"]
+    pub kind: Option<ReportKind>,
+    pub page: Option<usize>,
+    pub page_size: Option<usize>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReportNote {
+    pub user_id: String,
+    pub kind: ReportKind,
+    pub notes: Option<String>,
+    pub updated_at: String,
+  }
+
+  #[doc = "Release in the moderation list, most reported problems first"]
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ReportedRelease {
+    pub torrent: String,
+    #[doc = "Empty when the release is no longer in the index"]
+    pub name: Option<String>,
+    pub repacker: Option<String>,
+    pub stats: ReleaseReportStats,
+    pub hidden: bool,
+    pub last_reported: String,
+    pub reports: Vec<ReportNote>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct HideReleaseRequest {
+    pub torrent: String,
+    pub reason: Option<String>,
+  }
+
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct HiddenReleaseInfo {
+    pub torrent: String,
+    pub reason: Option<String>,
+    pub hidden_by: String,
+    pub hidden_at: String,
+  }
 }
 
@@ -7,7 +7,31 @@ use serde_json::json;
 pub enum ErrorCode {
   #[doc = "User not found"]
   AUTH001,
+
+  #[doc = "Token expired or revoked"]
+  AUTH002,
+
+  #[doc = "Authentication required"]
+  AUTH003,
+
+  #[doc = "Access token scope does not allow this request"]
+  AUTH004,
+
+  #[doc = "Permission denied"]
+  AUTH005,
+
+  #[doc = "Identity provider is unavailable, retry later"]
+  AUTH006,
  
+  #[doc = "Game not found"]
+  GAME001,
+
+  #[doc = "Game metadata is unavailable, retry later"]
+  GAME002,
+
+  #[doc = "Metadata provider rejected the request"]
+  GAME003,
+
   #[doc = "Internal server error"]
   INTERNAL001,
 
@@ -20,6 +44,18 @@ pub enum ErrorCode {
   #[doc = "Bad request"]
   BADREQUEST(String),
 
+  #[doc = "Resource was changed by another client, reload and retry"]
+  PRECONDITION001,
+
+  #[doc = "If-Match header is required"]
+  PRECONDITION002,
+
+  #[doc = "Storage quota exceeded"]
+  QUOTA001,
+
+  #[doc = "Uploaded file is too large"]
+  PAYLOAD001,
+
   #[doc = "Unknown error"]
   UNKNOWN,
  }
@@ -42,10 +78,22 @@ impl ErrorResponse {
   pub fn build(code: ErrorCode) -> HttpResponse {
     match code {
       ErrorCode::AUTH001 => HttpResponse::NotFound(),
+      ErrorCode::AUTH002 => HttpResponse::Unauthorized(),
+      ErrorCode::AUTH003 => HttpResponse::Unauthorized(),
+      ErrorCode::AUTH004 => HttpResponse::Forbidden(),
+      ErrorCode::AUTH005 => HttpResponse::Forbidden(),
+      ErrorCode::AUTH006 => HttpResponse::ServiceUnavailable(),
+      ErrorCode::GAME001 => HttpResponse::NotFound(),
+      ErrorCode::GAME002 => HttpResponse::ServiceUnavailable(),
+      ErrorCode::GAME003 => HttpResponse::BadGateway(),
       ErrorCode::INTERNAL001 => HttpResponse::InternalServerError(),
       ErrorCode::DATABASE001(_) => HttpResponse::InternalServerError(),
       ErrorCode::DATABASE002 => HttpResponse::NotFound(),
       ErrorCode::BADREQUEST(_) => HttpResponse::BadRequest(),
+      ErrorCode::PRECONDITION001 => HttpResponse::PreconditionFailed(),
+      ErrorCode::PRECONDITION002 => HttpResponse::PreconditionRequired(),
+      ErrorCode::QUOTA001 => HttpResponse::InsufficientStorage(),
+      ErrorCode::PAYLOAD001 => HttpResponse::PayloadTooLarge(),
       ErrorCode::UNKNOWN => HttpResponse::ImATeapot(),
     }
     .json(json!(ErrorResponse::new(code)))
@@ -0,0 +1,64 @@
+use crate::model::error::ErrorCode;
+use crate::modules::auth::{provider_env, subject, OAuth2Client};
+use crate::service::auth::{AuthProvider, ProviderIdentity};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+const NAME: &str = "discord";
+
+pub struct DiscordOAuth {
+    oauth: OAuth2Client,
+    api_url: String,
+}
+
+impl DiscordOAuth {
+    /// API переопределяется через `VEK_DISCORD_API_URL`.
+    pub fn new() -> Self {
+        DiscordOAuth {
+            oauth: OAuth2Client::from_env(
+                NAME,
+                "https://discord.com/oauth2/authorize",
+                "https://discord.com/api/oauth2/token",
+                "identify",
+            ),
+            api_url: provider_env(NAME, "API_URL").unwrap_or_else(|| "https://discord.com/api".to_string()),
+        }
+    }
+}
+
+impl Default for DiscordOAuth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for DiscordOAuth {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn authorize_url(&self, state: &str, redirect_uri: &str) -> Option<String> {
+        self.oauth.authorize_url(state, redirect_uri)
+    }
+
+    async fn authenticate(
+        &self,
+        params: &HashMap<String, String>,
+        redirect_uri: &str,
+    ) -> Result<ProviderIdentity, ErrorCode> {
+        let tokens = self.oauth.exchange_code(params, redirect_uri).await?;
+        let profile = self
+            .oauth
+            .get_json(&format!("{}/users/@me", self.api_url.trim_end_matches('/')), &tokens.access_token)
+            .await?;
+
+        Ok(ProviderIdentity {
+            subject: subject(&profile["id"]).ok_or(ErrorCode::AUTH002)?,
+            login: profile["username"].as_str().map(str::to_string),
+            tokens: Some(tokens),
+            display_name: None,
+            avatar_url: None,
+        })
+    }
+}
@@ -0,0 +1,64 @@
+use crate::model::error::ErrorCode;
+use crate::modules::auth::{provider_env, subject, OAuth2Client};
+use crate::service::auth::{AuthProvider, ProviderIdentity};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+const NAME: &str = "github";
+
+pub struct GitHubOAuth {
+    oauth: OAuth2Client,
+    api_url: String,
+}
+
+impl GitHubOAuth {
+    /// API переопределяется через `VEK_GITHUB_API_URL`.
+    pub fn new() -> Self {
+        GitHubOAuth {
+            oauth: OAuth2Client::from_env(
+                NAME,
+                "https://github.com/login/oauth/authorize",
+                "https://github.com/login/oauth/access_token",
+                "read:user",
+            ),
+            api_url: provider_env(NAME, "API_URL").unwrap_or_else(|| "https://api.github.com".to_string()),
+        }
+    }
+}
+
+impl Default for GitHubOAuth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for GitHubOAuth {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn authorize_url(&self, state: &str, redirect_uri: &str) -> Option<String> {
+        self.oauth.authorize_url(state, redirect_uri)
+    }
+
+    async fn authenticate(
+        &self,
+        params: &HashMap<String, String>,
+        redirect_uri: &str,
+    ) -> Result<ProviderIdentity, ErrorCode> {
+        let tokens = self.oauth.exchange_code(params, redirect_uri).await?;
+        let profile = self
+            .oauth
+            .get_json(&format!("{}/user", self.api_url.trim_end_matches('/')), &tokens.access_token)
+            .await?;
+
+        Ok(ProviderIdentity {
+            subject: subject(&profile["id"]).ok_or(ErrorCode::AUTH002)?,
+            login: profile["login"].as_str().map(str::to_string),
+            tokens: Some(tokens),
+            display_name: None,
+            avatar_url: None,
+        })
+    }
+}
@@ -0,0 +1,112 @@
+use crate::model::error::ErrorCode;
+use crate::modules::auth::{provider_env, subject, OAuth2Client};
+use crate::service::auth::{AuthProvider, ProviderIdentity};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+const NAME: &str = "google";
+
+pub struct GoogleOAuth {
+    oauth: OAuth2Client,
+    userinfo_url: String,
+}
+
+impl GoogleOAuth {
+    /// Адрес профиля переопределяется через `VEK_GOOGLE_USERINFO_URL`.
+    pub fn new() -> Self {
+        GoogleOAuth {
+            oauth: OAuth2Client::from_env(
+                NAME,
+                "https://accounts.google.com/o/oauth2/v2/auth",
+                "https://oauth2.googleapis.com/token",
+                "openid email profile",
+            ),
+            userinfo_url: provider_env(NAME, "USERINFO_URL")
+                .unwrap_or_else(|| "https://openidconnect.googleapis.com/v1/userinfo".to_string()),
+        }
+    }
+}
+
+impl Default for GoogleOAuth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for GoogleOAuth {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn authorize_url(&self, state: &str, redirect_uri: &str) -> Option<String> {
+        self.oauth.authorize_url(state, redirect_uri)
+    }
+
+    async fn authenticate(
+        &self,
+        params: &HashMap<String, String>,
+        redirect_uri: &str,
+    ) -> Result<ProviderIdentity, ErrorCode> {
+        let tokens = self.oauth.exchange_code(params, redirect_uri).await?;
+        let profile = self.oauth.get_json(&self.userinfo_url, &tokens.access_token).await?;
+
+        // Логином становится локальная часть почты, сама почта наружу не отдаётся.
+        let login = profile["email"]
+            .as_str()
+            .and_then(|email| email.split('@').next())
+            .map(str::to_string);
+
+        Ok(ProviderIdentity {
+            subject: subject(&profile["sub"]).ok_or(ErrorCode::AUTH002)?,
+            login,
+            tokens: Some(tokens),
+            display_name: None,
+            avatar_url: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::stub;
+
+    fn provider() -> GoogleOAuth {
+        let base_url = stub::auth();
+        std::env::set_var("VEK_GOOGLE_CLIENT_ID", "vek-test.apps.googleusercontent.com");
+        std::env::set_var("VEK_GOOGLE_CLIENT_SECRET", "vek-test-secret");
+        std::env::set_var("VEK_GOOGLE_TOKEN_URL", format!("{}/oauth/token", base_url));
+        std::env::set_var("VEK_GOOGLE_USERINFO_URL", format!("{}/oauth/userinfo", base_url));
+        GoogleOAuth::new()
+    }
+
+    fn callback(code: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("code".to_string(), code.to_string()),
+            ("state".to_string(), "state".to_string()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn exchanges_the_code_and_reads_the_profile() {
+        let identity = provider()
+            .authenticate(&callback(stub::OAUTH_CODE), "http://127.0.0.1:8004/api/auth/google/callback")
+            .await
+            .expect("stub accepts the code");
+
+        assert_eq!(identity.subject, "109876543210987654321");
+        assert_eq!(identity.login.as_deref(), Some("alice"));
+        let tokens = identity.tokens.expect("tokens are kept");
+        assert_eq!(tokens.access_token, stub::OAUTH_ACCESS_TOKEN);
+        assert_eq!(tokens.expires_in, Some(3599));
+    }
+
+    #[tokio::test]
+    async fn rejected_codes_are_auth_errors() {
+        let result = provider()
+            .authenticate(&callback("reused-code"), "http://127.0.0.1:8004/api/auth/google/callback")
+            .await;
+        assert!(matches!(result, Err(ErrorCode::AUTH002)));
+    }
+}
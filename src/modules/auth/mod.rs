@@ -0,0 +1,159 @@
+use crate::model::error::ErrorCode;
+use crate::service::auth::ProviderTokens;
+use log::warn;
+use reqwest::header::{ACCEPT, USER_AGENT};
+use reqwest::{Client, StatusCode, Url};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+
+pub(crate) mod discord;
+pub(crate) mod github;
+pub(crate) mod google;
+pub(crate) mod steam;
+pub(crate) mod yandex;
+
+/// Значение `VEK_<PROVIDER>_<NAME>`, чтобы любой адрес провайдера можно было направить на заглушку.
+pub fn provider_env(provider: &str, name: &str) -> Option<String> {
+    env::var(format!("VEK_{}_{}", provider.to_uppercase(), name)).ok()
+}
+
+/// Общая часть OAuth2 authorization code flow.
+pub struct OAuth2Client {
+    provider: &'static str,
+    client: Client,
+    authorize_url: String,
+    token_url: String,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    scope: String,
+}
+
+impl OAuth2Client {
+    /// Адреса и scope переопределяются через `VEK_<PROVIDER>_AUTHORIZE_URL`, `_TOKEN_URL`, `_SCOPE`,
+    /// учётные данные приложения задаются через `_CLIENT_ID` и `_CLIENT_SECRET`.
+    pub fn from_env(provider: &'static str, authorize_url: &str, token_url: &str, scope: &str) -> Self {
+        OAuth2Client {
+            provider,
+            client: Client::new(),
+            authorize_url: provider_env(provider, "AUTHORIZE_URL").unwrap_or_else(|| authorize_url.to_string()),
+            token_url: provider_env(provider, "TOKEN_URL").unwrap_or_else(|| token_url.to_string()),
+            client_id: provider_env(provider, "CLIENT_ID"),
+            client_secret: provider_env(provider, "CLIENT_SECRET"),
+            scope: provider_env(provider, "SCOPE").unwrap_or_else(|| scope.to_string()),
+        }
+    }
+
+    pub fn authorize_url(&self, state: &str, redirect_uri: &str) -> Option<String> {
+        let client_id = self.client_id.as_deref()?;
+        let mut params = vec![
+            ("response_type", "code"),
+            ("client_id", client_id),
+            ("redirect_uri", redirect_uri),
+            ("state", state),
+        ];
+        if !self.scope.is_empty() {
+            params.push(("scope", self.scope.as_str()));
+        }
+
+        Url::parse_with_params(&self.authorize_url, &params)
+            .map(String::from)
+            .map_err(|e| warn!("Некорректный адрес авторизации {}: {:?}", self.provider, e))
+            .ok()
+    }
+
+    /// Обменивает `code` из колбэка на токены.
+    pub async fn exchange_code(
+        &self,
+        params: &HashMap<String, String>,
+        redirect_uri: &str,
+    ) -> Result<ProviderTokens, ErrorCode> {
+        let code = params
+            .get("code")
+            .ok_or_else(|| ErrorCode::BADREQUEST("code is required".to_string()))?;
+        let (client_id, client_secret) = match (&self.client_id, &self.client_secret) {
+            (Some(client_id), Some(client_secret)) => (client_id, client_secret),
+            _ => {
+                warn!("Провайдер {} не настроен: нет client id или client secret", self.provider);
+                return Err(ErrorCode::INTERNAL001);
+            }
+        };
+
+        let response = self
+            .client
+            .post(&self.token_url)
+            .header(ACCEPT, "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code.as_str()),
+                ("redirect_uri", redirect_uri),
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| {
+                warn!("Не удалось обменять код {}: {:?}", self.provider, e);
+                ErrorCode::INTERNAL001
+            })?;
+
+        let json = Self::json(self.provider, response).await?;
+        // GitHub сообщает об ошибке обмена кодом статусом 200 и полем `error`.
+        if json["error"].is_string() {
+            return Err(ErrorCode::AUTH002);
+        }
+
+        Ok(ProviderTokens {
+            token_type: json["token_type"].as_str().unwrap_or("bearer").to_string(),
+            access_token: json["access_token"].as_str().ok_or(ErrorCode::AUTH002)?.to_string(),
+            refresh_token: json["refresh_token"].as_str().map(str::to_string),
+            expires_in: json["expires_in"].as_i64().map(|expires_in| expires_in as i32),
+            scope: json["scope"].as_str().map(str::to_string),
+        })
+    }
+
+    /// GET-запрос к API провайдера с токеном пользователя.
+    pub async fn get_json(&self, url: &str, access_token: &str) -> Result<Value, ErrorCode> {
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(access_token)
+            .header(ACCEPT, "application/json")
+            .header(USER_AGENT, "vek-api")
+            .send()
+            .await
+            .map_err(|e| {
+                warn!("Не удалось получить профиль {}: {:?}", self.provider, e);
+                ErrorCode::INTERNAL001
+            })?;
+
+        Self::json(self.provider, response).await
+    }
+
+    /// Отказ провайдера (4xx) — недействительный код или токен, остальное — внутренняя ошибка.
+    pub async fn json(provider: &str, response: reqwest::Response) -> Result<Value, ErrorCode> {
+        let status = response.status();
+        if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+            warn!("Провайдер {} отклонил запрос. Статус: {}", provider, status);
+            return Err(ErrorCode::AUTH002);
+        }
+        if !status.is_success() {
+            warn!("Провайдер {} вернул статус {}", provider, status);
+            return Err(ErrorCode::INTERNAL001);
+        }
+
+        response.json().await.map_err(|e| {
+            warn!("Не удалось разобрать ответ {}: {:?}", provider, e);
+            ErrorCode::INTERNAL001
+        })
+    }
+}
+
+/// Идентификатор из JSON, который у разных провайдеров бывает строкой или числом.
+pub fn subject(value: &Value) -> Option<String> {
+    match value {
+        Value::String(subject) if !subject.is_empty() => Some(subject.clone()),
+        Value::Number(subject) => Some(subject.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,252 @@
+use crate::model::error::ErrorCode;
+use crate::modules::auth::provider_env;
+use crate::service::auth::{AuthProvider, ProviderIdentity, PUBLIC_URL};
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use log::warn;
+use regex::Regex;
+use reqwest::{Client, Url};
+use std::collections::HashMap;
+
+const NAME: &str = "steam";
+const OPENID_NS: &str = "http://specs.openid.net/auth/2.0";
+const IDENTIFIER_SELECT: &str = "http://specs.openid.net/auth/2.0/identifier_select";
+
+lazy_static! {
+    static ref CLAIMED_ID_REGEX: Regex = Regex::new(r"^https://steamcommunity\.com/openid/id/([0-9]{17})$").unwrap();
+}
+
+/// Вход через Steam OpenID 2.0: токенов нет, аккаунт подтверждается
+/// обратным запросом `check_authentication` к Steam.
+pub struct SteamOpenID {
+    client: Client,
+    openid_url: String,
+}
+
+impl SteamOpenID {
+    /// Адрес OpenID переопределяется через `VEK_STEAM_OPENID_URL`.
+    pub fn new() -> Self {
+        SteamOpenID {
+            client: Client::new(),
+            openid_url: provider_env(NAME, "OPENID_URL")
+                .unwrap_or_else(|| "https://steamcommunity.com/openid/login".to_string()),
+        }
+    }
+
+    fn return_to(state: &str, redirect_uri: &str) -> Option<String> {
+        Url::parse_with_params(redirect_uri, &[("state", state)])
+            .map(String::from)
+            .ok()
+    }
+
+    /// `return_to` должен вести ровно на наш колбэк и нести тот же `state`, что и сам колбэк,
+    /// иначе подтверждение Steam могло быть выписано для другого сайта или другого входа.
+    fn is_own_return_to(return_to: &str, redirect_uri: &str, state: Option<&String>) -> bool {
+        let (Ok(return_to), Ok(redirect_uri)) = (Url::parse(return_to), Url::parse(redirect_uri)) else {
+            return false;
+        };
+        let return_state = return_to
+            .query_pairs()
+            .find(|(name, _)| name == "state")
+            .map(|(_, value)| value.into_owned());
+
+        return_to.scheme() == redirect_uri.scheme()
+            && return_to.host_str() == redirect_uri.host_str()
+            && return_to.port_or_known_default() == redirect_uri.port_or_known_default()
+            && return_to.path() == redirect_uri.path()
+            && return_state.is_some()
+            && return_state.as_ref() == state
+    }
+}
+
+impl Default for SteamOpenID {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for SteamOpenID {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn authorize_url(&self, state: &str, redirect_uri: &str) -> Option<String> {
+        let return_to = Self::return_to(state, redirect_uri)?;
+        Url::parse_with_params(
+            &self.openid_url,
+            &[
+                ("openid.ns", OPENID_NS),
+                ("openid.mode", "checkid_setup"),
+                ("openid.return_to", return_to.as_str()),
+                ("openid.realm", PUBLIC_URL.as_str()),
+                ("openid.identity", IDENTIFIER_SELECT),
+                ("openid.claimed_id", IDENTIFIER_SELECT),
+            ],
+        )
+        .map(String::from)
+        .ok()
+    }
+
+    async fn authenticate(
+        &self,
+        params: &HashMap<String, String>,
+        redirect_uri: &str,
+    ) -> Result<ProviderIdentity, ErrorCode> {
+        if params.get("openid.mode").map(String::as_str) != Some("id_res") {
+            return Err(ErrorCode::AUTH002);
+        }
+        let return_to = params.get("openid.return_to").ok_or(ErrorCode::AUTH002)?;
+        if !Self::is_own_return_to(return_to, redirect_uri, params.get("state")) {
+            warn!("Steam OpenID вернул чужой return_to: {}", return_to);
+            return Err(ErrorCode::AUTH002);
+        }
+        // Подтверждение должен был выдать тот же сервер, которому отправлен `check_authentication`.
+        if params.get("openid.op_endpoint") != Some(&self.openid_url) {
+            warn!("Steam OpenID вернул чужой op_endpoint: {:?}", params.get("openid.op_endpoint"));
+            return Err(ErrorCode::AUTH002);
+        }
+        let claimed_id = params.get("openid.claimed_id").ok_or(ErrorCode::AUTH002)?;
+        let steam_id = CLAIMED_ID_REGEX
+            .captures(claimed_id)
+            .map(|caps| caps[1].to_string())
+            .ok_or(ErrorCode::AUTH002)?;
+
+        let mut verification: Vec<(&str, &str)> = params
+            .iter()
+            .filter(|(key, _)| key.starts_with("openid.") && key.as_str() != "openid.mode")
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        verification.push(("openid.mode", "check_authentication"));
+
+        let response = self
+            .client
+            .post(&self.openid_url)
+            .form(&verification)
+            .send()
+            .await
+            .map_err(|e| {
+                warn!("Не удалось проверить ответ Steam OpenID: {:?}", e);
+                ErrorCode::INTERNAL001
+            })?;
+        let body = response.text().await.map_err(|_| ErrorCode::INTERNAL001)?;
+        if !body.lines().any(|line| line.trim() == "is_valid:true") {
+            warn!("Steam OpenID не подтвердил вход для {}", steam_id);
+            return Err(ErrorCode::AUTH002);
+        }
+
+        Ok(ProviderIdentity {
+            subject: steam_id,
+            login: None,
+            tokens: None,
+            display_name: None,
+            avatar_url: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::stub;
+
+    const REDIRECT_URI: &str = "http://127.0.0.1:8004/api/auth/steam/callback";
+    const STATE: &str = "Vh3kQe0p1sYtJm7c";
+
+    fn provider() -> SteamOpenID {
+        std::env::set_var("VEK_STEAM_OPENID_URL", format!("{}/openid/login", stub::auth()));
+        SteamOpenID::new()
+    }
+
+    const CLAIMED_ID: &str = "https://steamcommunity.com/openid/id/76561197960287930";
+
+    /// Параметры колбэка в том виде, в каком их присылает Steam.
+    fn callback(return_to: &str, signature: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("state".to_string(), STATE.to_string()),
+            ("openid.ns".to_string(), OPENID_NS.to_string()),
+            ("openid.mode".to_string(), "id_res".to_string()),
+            ("openid.op_endpoint".to_string(), provider().openid_url),
+            ("openid.claimed_id".to_string(), CLAIMED_ID.to_string()),
+            ("openid.identity".to_string(), CLAIMED_ID.to_string()),
+            ("openid.return_to".to_string(), return_to.to_string()),
+            ("openid.response_nonce".to_string(), "2024-05-01T10:00:00ZQ2nVbBsrU0xtR5Gk0y9wqGz2YQ0=".to_string()),
+            ("openid.assoc_handle".to_string(), "1234567890".to_string()),
+            ("openid.signed".to_string(), "signed,op_endpoint,claimed_id,identity,return_to,response_nonce,assoc_handle".to_string()),
+            ("openid.sig".to_string(), signature.to_string()),
+        ])
+    }
+
+    fn own_return_to() -> String {
+        SteamOpenID::return_to(STATE, REDIRECT_URI).unwrap()
+    }
+
+    #[tokio::test]
+    async fn confirmed_logins_resolve_the_steam_id() {
+        let identity = provider()
+            .authenticate(&callback(&own_return_to(), stub::OPENID_SIGNATURE), REDIRECT_URI)
+            .await
+            .expect("stub confirms the signature");
+        assert_eq!(identity.subject, "76561197960287930");
+        assert!(identity.tokens.is_none());
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_signatures_are_rejected() {
+        let result = provider()
+            .authenticate(&callback(&own_return_to(), "forged"), REDIRECT_URI)
+            .await;
+        assert!(matches!(result, Err(ErrorCode::AUTH002)));
+    }
+
+    #[tokio::test]
+    async fn foreign_return_to_is_rejected() {
+        let foreign = [
+            format!("https://evil.example/api/auth/steam/callback?state={}", STATE),
+            format!("{}.evil.example/?state={}", REDIRECT_URI.trim_end_matches("/api/auth/steam/callback"), STATE),
+            format!("{}-other?state={}", REDIRECT_URI, STATE),
+            format!("{}?state=another-login", REDIRECT_URI),
+            REDIRECT_URI.to_string(),
+        ];
+
+        for return_to in foreign {
+            let result = provider()
+                .authenticate(&callback(&return_to, stub::OPENID_SIGNATURE), REDIRECT_URI)
+                .await;
+            assert!(matches!(result, Err(ErrorCode::AUTH002)), "{} was accepted", return_to);
+        }
+    }
+
+    #[tokio::test]
+    async fn foreign_claimed_ids_and_endpoints_are_rejected() {
+        let claimed_ids = [
+            "https://evil.example/openid/id/76561197960287930",
+            "http://steamcommunity.com/openid/id/76561197960287930",
+            "https://steamcommunity.com.evil.example/openid/id/76561197960287930",
+            "https://steamcommunity.com/openid/id/7656119796028793",
+        ];
+        for claimed_id in claimed_ids {
+            let mut params = callback(&own_return_to(), stub::OPENID_SIGNATURE);
+            params.insert("openid.claimed_id".to_string(), claimed_id.to_string());
+            params.insert("openid.identity".to_string(), claimed_id.to_string());
+            let result = provider().authenticate(&params, REDIRECT_URI).await;
+            assert!(matches!(result, Err(ErrorCode::AUTH002)), "{} was accepted", claimed_id);
+        }
+
+        let mut params = callback(&own_return_to(), stub::OPENID_SIGNATURE);
+        params.insert("openid.op_endpoint".to_string(), "https://evil.example/openid/login".to_string());
+        let result = provider().authenticate(&params, REDIRECT_URI).await;
+        assert!(matches!(result, Err(ErrorCode::AUTH002)));
+    }
+
+    #[test]
+    fn authorize_url_returns_to_the_callback_with_state() {
+        let url = Url::parse(&provider().authorize_url(STATE, REDIRECT_URI).unwrap()).unwrap();
+        let return_to = url
+            .query_pairs()
+            .find(|(name, _)| name == "openid.return_to")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        assert!(SteamOpenID::is_own_return_to(&return_to, REDIRECT_URI, Some(&STATE.to_string())));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::model::error::ErrorCode;
+use crate::modules::auth::OAuth2Client;
+use crate::service::auth::{AuthProvider, ProviderIdentity, ProviderTokens, YANDEX_LOGIN_URL, YANDEX_OAUTH_URL};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::collections::HashMap;
+
+const NAME: &str = "yandex";
+/// Аватар по `default_avatar_id` из ответа `/info`.
+const AVATAR_URL: &str = "https://avatars.yandex.net/get-yapic";
+
+pub struct YandexOAuth {
+    oauth: OAuth2Client,
+    client: Client,
+}
+
+impl YandexOAuth {
+    /// Адреса берутся из `VEK_YANDEX_OAUTH_URL` и `VEK_YANDEX_LOGIN_URL`.
+    pub fn new() -> Self {
+        YandexOAuth {
+            oauth: OAuth2Client::from_env(
+                NAME,
+                &format!("{}/authorize", *YANDEX_OAUTH_URL),
+                &format!("{}/token", *YANDEX_OAUTH_URL),
+                "",
+            ),
+            client: Client::new(),
+        }
+    }
+
+    /// Лаунчер получает токены сам и передаёт их в `POST /api/auth`.
+    fn tokens_from_params(params: &HashMap<String, String>) -> Option<ProviderTokens> {
+        Some(ProviderTokens {
+            token_type: params.get("token_type").cloned().unwrap_or_else(|| "bearer".to_string()),
+            access_token: params.get("access_token")?.clone(),
+            refresh_token: params.get("refresh_token").cloned().filter(|token| !token.is_empty()),
+            expires_in: params.get("expires_in").and_then(|expires_in| expires_in.parse().ok()),
+            scope: params.get("scope").cloned(),
+        })
+    }
+}
+
+impl Default for YandexOAuth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for YandexOAuth {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn authorize_url(&self, state: &str, redirect_uri: &str) -> Option<String> {
+        self.oauth.authorize_url(state, redirect_uri)
+    }
+
+    async fn authenticate(
+        &self,
+        params: &HashMap<String, String>,
+        redirect_uri: &str,
+    ) -> Result<ProviderIdentity, ErrorCode> {
+        let tokens = match Self::tokens_from_params(params) {
+            Some(tokens) => tokens,
+            None => self.oauth.exchange_code(params, redirect_uri).await?,
+        };
+
+        let response = self
+            .client
+            .get(format!("{}/info?format=json&oauth_token={}", *YANDEX_LOGIN_URL, tokens.access_token))
+            .send()
+            .await
+            .map_err(|_| ErrorCode::INTERNAL001)?;
+        let profile = OAuth2Client::json(NAME, response).await?;
+
+        // Аккаунты Yandex исторически идентифицируются по логину.
+        let login = profile["login"].as_str().ok_or(ErrorCode::AUTH002)?.to_string();
+        let avatar_url = profile["default_avatar_id"]
+            .as_str()
+            .filter(|_| !profile["is_avatar_empty"].as_bool().unwrap_or(true))
+            .map(|avatar_id| format!("{}/{}/islands-200", AVATAR_URL, avatar_id));
+        Ok(ProviderIdentity {
+            subject: login.clone(),
+            login: Some(login),
+            tokens: Some(tokens),
+            display_name: profile["display_name"].as_str().map(str::to_string),
+            avatar_url,
+        })
+    }
+}
@@ -0,0 +1,175 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::modules::constants::API_SECRET;
+use crate::modules::metadata::rawg::BASE_URL;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const CURSOR_PARAM: &str = "next";
+
+/// Параметры страницы RAWG, которые можно передать клиенту в курсоре.
+const ALLOWED_PARAMS: [&str; 15] = [
+    "page",
+    "page_size",
+    "search",
+    "search_precise",
+    "search_exact",
+    "ordering",
+    "play_on_desktop",
+    "parent_platforms",
+    "platforms",
+    "genres",
+    "tags",
+    "developers",
+    "publishers",
+    "dates",
+    "metacritic",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageCursor {
+    /// Маршрут нашего API, для которого выдан курсор, без строки запроса.
+    pub endpoint: String,
+    pub path: String,
+    pub params: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CursorError {
+    RawUrl,
+    Malformed,
+    BadSignature,
+    WrongEndpoint,
+}
+
+impl std::fmt::Display for CursorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CursorError::RawUrl => write!(f, "Raw URLs are not accepted as cursor"),
+            CursorError::Malformed => write!(f, "Malformed cursor"),
+            CursorError::BadSignature => write!(f, "Invalid cursor signature"),
+            CursorError::WrongEndpoint => write!(f, "Cursor does not belong to this endpoint"),
+        }
+    }
+}
+
+impl PageCursor {
+    /// Разбирает ссылку пагинации RAWG. Ссылки на другие хосты отбрасываются.
+    pub fn from_rawg_url(raw: &str, endpoint: &str) -> Option<Self> {
+        let url = Url::parse(raw).ok()?;
+        let base = Url::parse(BASE_URL.as_str()).ok()?;
+        if url.scheme() != base.scheme() || url.host_str() != base.host_str() || url.port() != base.port() {
+            return None;
+        }
+
+        let path = url.path().strip_prefix(base.path())?.to_string();
+        let params = url
+            .query_pairs()
+            .filter(|(name, _)| ALLOWED_PARAMS.contains(&name.as_ref()))
+            .map(|(name, value)| (name.into_owned(), value.into_owned()))
+            .collect();
+
+        Some(PageCursor { endpoint: endpoint.to_string(), path, params })
+    }
+
+    pub fn encode(&self) -> String {
+        let payload = serde_json::to_vec(self).unwrap_or_default();
+        let payload = URL_SAFE_NO_PAD.encode(payload);
+        format!("{}.{}", payload, sign(payload.as_bytes()))
+    }
+
+    /// Декодирует курсор, выданный API. Принимает как сам курсор, так и
+    /// нашу ссылку вида `/api/games?next=<cursor>` из поля `next` ответа.
+    pub fn decode(value: &str) -> Result<Self, CursorError> {
+        let value = value.trim();
+        if value.contains("://") || value.starts_with("//") {
+            return Err(CursorError::RawUrl);
+        }
+
+        let token = if value.starts_with('/') {
+            let url = Url::parse("http://localhost")
+                .and_then(|base| base.join(value))
+                .map_err(|_| CursorError::Malformed)?;
+            url.query_pairs()
+                .find(|(name, _)| name == CURSOR_PARAM)
+                .map(|(_, cursor)| cursor.into_owned())
+                .ok_or(CursorError::Malformed)?
+        } else {
+            value.to_string()
+        };
+
+        let (payload, signature) = token.split_once('.').ok_or(CursorError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| CursorError::Malformed)?;
+
+        let mut mac = HmacSha256::new_from_slice(API_SECRET.as_bytes()).map_err(|_| CursorError::Malformed)?;
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature).map_err(|_| CursorError::BadSignature)?;
+
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| CursorError::Malformed)?;
+        let cursor: PageCursor = serde_json::from_slice(&payload).map_err(|_| CursorError::Malformed)?;
+
+        if cursor.path.contains("..")
+            || cursor.params.iter().any(|(name, _)| !ALLOWED_PARAMS.contains(&name.as_str()))
+        {
+            return Err(CursorError::Malformed);
+        }
+
+        Ok(cursor)
+    }
+
+    /// Значение параметра RAWG, сохранённого в курсоре.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Декодирует курсор и проверяет, что он выдан нашим маршрутом `endpoint`
+    /// для указанного пути RAWG, чтобы курсоры нельзя было переносить между эндпоинтами.
+    pub fn decode_for(value: &str, endpoint: &str, path: &str) -> Result<Self, CursorError> {
+        let cursor = Self::decode(value)?;
+        if cursor.endpoint != endpoint || cursor.path != path {
+            return Err(CursorError::WrongEndpoint);
+        }
+        Ok(cursor)
+    }
+}
+
+fn sign(payload: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(API_SECRET.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+}
+
+/// Заменяет ссылки `next`/`previous` из ответа RAWG на ссылки нашего API
+/// с подписанным курсором. Ссылки, которые не удалось разобрать, обнуляются.
+pub fn rewrite_pagination(json: &mut Value, route: &str) {
+    let endpoint = route.split('?').next().unwrap_or(route);
+    for field in ["next", "previous"] {
+        if let Some(link) = json.get_mut(field) {
+            let rewritten = link
+                .as_str()
+                .and_then(|raw| PageCursor::from_rawg_url(raw, endpoint))
+                .map(|cursor| {
+                    let separator = if route.contains('?') { '&' } else { '?' };
+                    format!("{}{}{}={}", route, separator, CURSOR_PARAM, cursor.encode())
+                });
+
+            *link = match rewritten {
+                Some(url) => Value::String(url),
+                None => Value::Null,
+            };
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use std::fs::{self};
 use std::path::PathBuf;
 use std::env;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use lazy_static::lazy_static;
+use regex::Regex;
 
 use crate::modules::formatters::{
     remove_duplicate_spaces,
@@ -10,7 +14,14 @@ use crate::modules::formatters::{
     remove_trash,
 };
 
-pub fn get_database_path() -> Result<PathBuf, std::io::Error> {
+lazy_static! {
+    /// Хвост названия раздачи, который не делает её другой игрой: версия, сборка, издание, состав репака.
+    static ref RELEASE_TAIL_REGEX: Regex = Regex::new(
+        r"^(v ?[0-9]|build|update|repack|multi|dlcs?\b|incl|goty|complete|deluxe|ultimate|definitive|gold|premium|enhanced|digital|edition|[0-9]+ dlcs?\b)"
+    ).unwrap();
+}
+
+pub fn get_data_dir() -> Result<PathBuf, std::io::Error> {
     let mut path = dirs::data_local_dir().ok_or_else(|| {
         std::io::Error::new(
             std::io::ErrorKind::NotFound,
@@ -23,6 +34,18 @@ pub fn get_database_path() -> Result<PathBuf, std::io::Error> {
         fs::create_dir_all(&path)?;
     }
 
+    Ok(path)
+}
+
+/// Случайная строка в base64url, например для `state` OAuth.
+pub fn random_token(bytes: usize) -> Result<String, getrandom::Error> {
+    let mut buffer = vec![0u8; bytes];
+    getrandom::getrandom(&mut buffer)?;
+    Ok(URL_SAFE_NO_PAD.encode(buffer))
+}
+
+pub fn get_database_path() -> Result<PathBuf, std::io::Error> {
+    let mut path = get_data_dir()?;
     path.push("veklauncher.db");
     Ok(path)
 }
@@ -54,3 +77,76 @@ pub fn format_name(name: String) -> String {
     ];
     pipe(name, functions).trim().to_string()
 }
+
+/// Название игры или раздачи в виде для сравнения.
+pub fn normalize_name(name: &str) -> String {
+    format_name(name.to_string()).to_lowercase()
+}
+
+/// Раздача относится к игре, если нормализованные названия совпадают целиком или
+/// раздача отличается только хвостом вроде версии и издания. Поэтому "Doom" не
+/// совпадает с "DOOM Eternal", а "Portal" — с "Portal 2".
+pub fn release_matches(release: &str, game: &str) -> bool {
+    if game.is_empty() {
+        return false;
+    }
+    match release.strip_prefix(game) {
+        Some("") => true,
+        Some(tail) => tail.starts_with(' ') && RELEASE_TAIL_REGEX.is_match(tail.trim_start()),
+        None => false,
+    }
+}
+
+/// Игра, к которой относится раздача. `release` и названия игр уже нормализованы
+/// через `normalize_name`; из подходящих берётся самое длинное название.
+pub fn link_release<'a, T>(release: &str, games: &'a [(String, T)]) -> Option<&'a T> {
+    games
+        .iter()
+        .filter(|(game, _)| release_matches(release, game))
+        .max_by_key(|(game, _)| game.len())
+        .map(|(_, linked)| linked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(release: &str, game: &str) -> bool {
+        release_matches(&normalize_name(release), &normalize_name(game))
+    }
+
+    #[test]
+    fn matches_the_same_game_with_release_details() {
+        assert!(matches("DOOM", "Doom"));
+        assert!(matches("The Witcher 3 Wild Hunt", "The Witcher 3: Wild Hunt"));
+        assert!(matches("Cyberpunk 2077 v2.12 + Phantom Liberty DLC", "Cyberpunk 2077"));
+        assert!(matches("Hades Build 1234567", "Hades"));
+        assert!(matches("Sekiro Shadows Die Twice GOTY Edition", "Sekiro: Shadows Die Twice"));
+        assert!(matches("Stardew Valley MULTi10", "Stardew Valley"));
+    }
+
+    #[test]
+    fn does_not_match_other_games_with_a_common_prefix() {
+        assert!(!matches("DOOM Eternal", "Doom"));
+        assert!(!matches("Portal 2", "Portal"));
+        assert!(!matches("Hades II", "Hades"));
+        assert!(!matches("Doom", "Doom Eternal"));
+        assert!(!matches("Doomsday Paradise", "Doom"));
+        assert!(!matches("Anything", ""));
+    }
+
+    #[test]
+    fn links_releases_to_the_longest_matching_game() {
+        let games: Vec<(String, i32)> = ["Portal", "Portal 2", "DOOM", "DOOM Eternal"]
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (normalize_name(name), id as i32))
+            .collect();
+        let link = |release: &str| link_release(&normalize_name(release), &games).copied();
+
+        assert_eq!(link("Portal 2 Build 8491853"), Some(1));
+        assert_eq!(link("Portal [FitGirl Repack]"), Some(0));
+        assert_eq!(link("DOOM Eternal Deluxe Edition v6.66 + 2 DLCs"), Some(3));
+        assert_eq!(link("Portal Stories Mel"), None);
+    }
+}
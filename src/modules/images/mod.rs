@@ -0,0 +1,200 @@
+use lazy_static::lazy_static;
+use reqwest::Url;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::modules::helpers::get_data_dir;
+
+const DEFAULT_ALLOWED_HOSTS: &str = "media.rawg.io,cdn.akamai.steamstatic.com,shared.akamai.steamstatic.com,cdn.cloudflare.steamstatic.com,steamcdn-a.akamaihd.net";
+pub const SOURCE_FILE: &str = "source";
+/// Сколько ссылок держать в памяти. Ссылка, которую так и не запросили, забывается
+/// через `REGISTRY_TTL` или раньше, если реестр переполнен.
+const REGISTRY_CAPACITY: usize = 50_000;
+const REGISTRY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Поля ответов RAWG и Steam, в которых лежат ссылки на изображения.
+const IMAGE_FIELDS: [&str; 4] = ["background_image", "background_image_additional", "image", "image_background"];
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::new(REGISTRY_CAPACITY, REGISTRY_TTL));
+    /// Хосты, с которых прокси может загружать изображения (`VEK_IMAGE_HOSTS`).
+    static ref ALLOWED_HOSTS: Vec<String> = env::var("VEK_IMAGE_HOSTS")
+        .unwrap_or_else(|_| DEFAULT_ALLOWED_HOSTS.to_string())
+        .split(',')
+        .map(|host| host.trim().to_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect();
+}
+
+/// Соответствие хэшей и ссылок с ограниченным размером и временем жизни записей.
+struct Registry {
+    urls: HashMap<String, (String, Instant)>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl Registry {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Registry { urls: HashMap::new(), capacity, ttl }
+    }
+
+    fn insert(&mut self, hash: String, url: String) {
+        if !self.urls.contains_key(&hash) && self.urls.len() >= self.capacity {
+            self.evict();
+        }
+        self.urls.insert(hash, (url, Instant::now()));
+    }
+
+    fn get(&self, hash: &str) -> Option<String> {
+        self.urls
+            .get(hash)
+            .filter(|(_, seen)| seen.elapsed() < self.ttl)
+            .map(|(url, _)| url.clone())
+    }
+
+    /// Удаляет устаревшие записи, а если их не нашлось — самую давнюю четверть.
+    fn evict(&mut self) {
+        let ttl = self.ttl;
+        self.urls.retain(|_, (_, seen)| seen.elapsed() < ttl);
+        if self.urls.len() < self.capacity {
+            return;
+        }
+
+        let mut seen: Vec<Instant> = self.urls.values().map(|(_, seen)| *seen).collect();
+        seen.sort_unstable();
+        let cutoff = seen[seen.len() / 4];
+        self.urls.retain(|_, (_, seen)| *seen > cutoff);
+    }
+}
+
+pub fn is_allowed_host(host: Option<&str>) -> bool {
+    host.map(|host| ALLOWED_HOSTS.iter().any(|allowed| allowed == &host.to_lowercase()))
+        .unwrap_or(false)
+}
+
+pub fn is_allowed_url(url: &str) -> bool {
+    Url::parse(url)
+        .map(|url| matches!(url.scheme(), "http" | "https") && is_allowed_host(url.host_str()))
+        .unwrap_or(false)
+}
+
+pub fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 32 && hash.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+}
+
+pub fn image_hash(url: &str) -> String {
+    content_hash(url.as_bytes())
+}
+
+/// Хэш загруженного файла, в том же формате, что и у ссылок.
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .take(16)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn cache_dir() -> Result<PathBuf, std::io::Error> {
+    let mut path = get_data_dir()?;
+    path.push("images");
+    Ok(path)
+}
+
+/// Регистрирует ссылку на изображение и возвращает её хэш. На диск ссылка
+/// попадает только при первой загрузке изображения, см. `ImageService`.
+pub fn register(url: &str) -> Option<String> {
+    if !is_allowed_url(url) {
+        return None;
+    }
+
+    let hash = image_hash(url);
+    if let Ok(mut registry) = REGISTRY.lock() {
+        registry.insert(hash.clone(), url.to_string());
+    }
+    Some(hash)
+}
+
+/// Находит исходную ссылку по хэшу: сначала в памяти, затем на диске.
+pub async fn resolve(hash: &str) -> Option<String> {
+    if !is_valid_hash(hash) {
+        return None;
+    }
+
+    if let Some(url) = REGISTRY.lock().ok().and_then(|registry| registry.get(hash)) {
+        return Some(url);
+    }
+
+    let path = cache_dir().ok()?.join(hash).join(SOURCE_FILE);
+    let url = tokio::fs::read_to_string(path).await.ok()?;
+    let url = url.trim().to_string();
+    if image_hash(&url) != hash || !is_allowed_url(&url) {
+        return None;
+    }
+    Some(url)
+}
+
+/// Добавляет рядом с каждым полем изображения поле `<name>_proxy`
+/// со ссылкой на `/api/images/{hash}`.
+pub fn attach_proxy_links(json: &mut Value) {
+    match json {
+        Value::Array(items) => {
+            for item in items {
+                attach_proxy_links(item);
+            }
+        }
+        Value::Object(map) => {
+            let proxies: Vec<(String, String)> = IMAGE_FIELDS
+                .iter()
+                .filter_map(|field| {
+                    let url = map.get(*field)?.as_str()?;
+                    let hash = register(url)?;
+                    Some((format!("{}_proxy", field), format!("/api/images/{}", hash)))
+                })
+                .collect();
+
+            for (_, item) in map.iter_mut() {
+                attach_proxy_links(item);
+            }
+            for (field, link) in proxies {
+                map.insert(field, Value::String(link));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_stays_within_capacity() {
+        let mut registry = Registry::new(8, REGISTRY_TTL);
+        for i in 0..100 {
+            registry.insert(format!("hash{}", i), format!("https://media.rawg.io/{}.jpg", i));
+            assert!(registry.urls.len() <= 8);
+        }
+        assert_eq!(registry.get("hash99").as_deref(), Some("https://media.rawg.io/99.jpg"));
+        assert_eq!(registry.get("hash0"), None);
+    }
+
+    #[test]
+    fn registry_forgets_expired_links() {
+        let mut registry = Registry::new(8, Duration::ZERO);
+        registry.insert("hash".to_string(), "https://media.rawg.io/a.jpg".to_string());
+        assert_eq!(registry.get("hash"), None);
+    }
+
+    #[test]
+    fn registers_only_allowed_hosts() {
+        assert!(register("https://media.rawg.io/media/games/20a/20aa03a10cda45239fe22d035c0ebe64.jpg").is_some());
+        assert!(register("https://example.com/image.jpg").is_none());
+        assert!(register("file:///etc/passwd").is_none());
+    }
+}
@@ -0,0 +1,2 @@
+pub(crate) mod rawg;
+pub(crate) mod steam;
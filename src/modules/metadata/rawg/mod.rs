@@ -0,0 +1,141 @@
+use crate::middleware::games::GamesMiddleware;
+use crate::model::dto::games::GameDetailsRequest;
+use crate::service::games::GamesService;
+use crate::service::metadata::{GameMetadata, GameMetadataProvider, GameReference, MetadataError};
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+use std::env;
+use std::sync::Arc;
+
+const DEFAULT_BASE_URL: &str = "https://api.rawg.io/api/";
+
+lazy_static! {
+    /// Базовый адрес RAWG, переопределяется через `VEK_RAWG_BASE_URL` (например, для локальной заглушки).
+    pub(crate) static ref BASE_URL: String = {
+        // В тестах RAWG всегда подменяется локальной заглушкой с записанными ответами.
+        #[cfg(test)]
+        crate::modules::stub::rawg();
+        env::var("VEK_RAWG_BASE_URL")
+            .map(|url| if url.ends_with('/') { url } else { format!("{}/", url) })
+            .unwrap_or_else(|_| DEFAULT_BASE_URL.to_string())
+    };
+    static ref STEAM_APP_REGEX: Regex = Regex::new(r"store\.steampowered\.com/app/([0-9]+)").unwrap();
+}
+
+pub struct ProviderRAWG {
+    middleware: Arc<GamesMiddleware>,
+}
+
+impl ProviderRAWG {
+    pub fn new(middleware: Arc<GamesMiddleware>) -> Self {
+        ProviderRAWG { middleware }
+    }
+
+    async fn find_steam_app_id(&self, id: i32) -> Option<u32> {
+        let stores = self
+            .middleware
+            .execute_with_retry(move |api_key| async move {
+                GamesService::get_game_relation(api_key, id, "stores", vec![]).await
+            })
+            .await
+            .ok()?;
+
+        stores["results"]
+            .as_array()?
+            .iter()
+            .filter_map(|store| store["url"].as_str())
+            .find_map(|url| STEAM_APP_REGEX.captures(url))
+            .and_then(|caps| caps.get(1))
+            .and_then(|app_id| app_id.as_str().parse().ok())
+    }
+
+    fn names(value: &Value) -> Vec<String> {
+        value
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item["name"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl GameMetadataProvider for ProviderRAWG {
+    fn name(&self) -> &'static str {
+        "rawg"
+    }
+
+    async fn fetch_metadata(&self, game: &GameReference) -> Result<Option<GameMetadata>, MetadataError> {
+        let id = game.rawg_id;
+        let details = self.middleware.execute_with_retry(move |api_key| async move {
+            GamesService::get_game_details(api_key, GameDetailsRequest { id }).await
+        });
+        let steam_app_id = async {
+            match game.steam_app_id {
+                Some(app_id) => Some(app_id),
+                None => self.find_steam_app_id(id).await,
+            }
+        };
+
+        let (details, steam_app_id) = tokio::join!(details, steam_app_id);
+        let details = details.map_err(|e| MetadataError::from_http("RAWG", e))?;
+
+        Ok(Some(GameMetadata {
+            name: details["name"].as_str().map(str::to_string),
+            description: details["description_raw"].as_str().map(str::to_string),
+            released: details["released"].as_str().map(str::to_string),
+            background_image: details["background_image"].as_str().map(str::to_string),
+            website: details["website"].as_str().filter(|url| !url.is_empty()).map(str::to_string),
+            metacritic: details["metacritic"].as_i64(),
+            developers: Self::names(&details["developers"]),
+            publishers: Self::names(&details["publishers"]),
+            genres: Self::names(&details["genres"]),
+            price: None,
+            steam_app_id,
+            raw: Some(details),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::stub;
+
+    fn provider() -> ProviderRAWG {
+        ProviderRAWG::new(Arc::new(GamesMiddleware::with_api_key(stub::RAWG_API_KEY)))
+    }
+
+    #[tokio::test]
+    async fn reads_details_and_steam_app_id_from_recorded_responses() {
+        let game = GameReference { rawg_id: 3498, ..Default::default() };
+        let metadata = provider().fetch_metadata(&game).await.unwrap().unwrap();
+
+        assert_eq!(metadata.name.as_deref(), Some("Grand Theft Auto V"));
+        assert_eq!(metadata.released.as_deref(), Some("2013-09-17"));
+        assert_eq!(metadata.metacritic, Some(92));
+        assert_eq!(metadata.developers, vec!["Rockstar North"]);
+        assert_eq!(metadata.genres, vec!["Action", "Adventure"]);
+        assert_eq!(metadata.steam_app_id, Some(271590));
+        assert_eq!(metadata.raw.unwrap()["id"], 3498);
+    }
+
+    #[tokio::test]
+    async fn keeps_a_known_steam_app_id() {
+        let game = GameReference { rawg_id: 3498, steam_app_id: Some(3240220), ..Default::default() };
+        let metadata = provider().fetch_metadata(&game).await.unwrap().unwrap();
+
+        assert_eq!(metadata.steam_app_id, Some(3240220));
+    }
+
+    #[tokio::test]
+    async fn unknown_games_are_not_found() {
+        let game = GameReference { rawg_id: 404404, ..Default::default() };
+        assert_eq!(provider().fetch_metadata(&game).await.unwrap_err(), MetadataError::NotFound);
+    }
+}
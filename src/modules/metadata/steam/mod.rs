@@ -0,0 +1,154 @@
+use crate::service::metadata::{GameMetadata, GameMetadataProvider, GameReference, MetadataError};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::Client;
+use serde_json::Value;
+use std::env;
+
+const DEFAULT_BASE_URL: &str = "https://store.steampowered.com/api/";
+
+pub struct ProviderSteam {
+    client: Client,
+    base_url: String,
+}
+
+impl ProviderSteam {
+    /// Адрес Steam Store API переопределяется через `VEK_STEAM_STORE_URL`.
+    pub fn new() -> Self {
+        Self::with_base_url(&env::var("VEK_STEAM_STORE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()))
+    }
+
+    pub fn with_base_url(base_url: &str) -> Self {
+        let base_url = if base_url.ends_with('/') { base_url.to_string() } else { format!("{}/", base_url) };
+
+        ProviderSteam {
+            client: Client::new(),
+            base_url,
+        }
+    }
+
+    fn strings(value: &Value, field: Option<&str>) -> Vec<String> {
+        value
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| match field {
+                        Some(field) => item[field].as_str(),
+                        None => item.as_str(),
+                    })
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Steam отдаёт даты в виде "12 Mar, 2020" или "Mar 12, 2020".
+    fn release_date(value: &Value) -> Option<String> {
+        let date = value["date"].as_str()?;
+        ["%d %b, %Y", "%b %d, %Y", "%d %B, %Y", "%B %d, %Y"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+            .map(|date| date.format("%Y-%m-%d").to_string())
+    }
+}
+
+impl Default for ProviderSteam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl GameMetadataProvider for ProviderSteam {
+    fn name(&self) -> &'static str {
+        "steam"
+    }
+
+    fn ready(&self, game: &GameReference) -> bool {
+        game.steam_app_id.is_some()
+    }
+
+    async fn fetch_metadata(&self, game: &GameReference) -> Result<Option<GameMetadata>, MetadataError> {
+        let app_id = match game.steam_app_id {
+            Some(app_id) => app_id,
+            None => return Ok(None),
+        };
+
+        let json = self
+            .client
+            .get(format!("{}appdetails", self.base_url))
+            .query(&[("appids", app_id.to_string()), ("l", "english".to_string())])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| MetadataError::from_http("Steam", e))?
+            .json::<Value>()
+            .await
+            .map_err(|e| MetadataError::Unavailable(format!("Ошибка чтения ответа Steam: {}", e.without_url())))?;
+
+        let entry = &json[app_id.to_string()];
+        if !entry["success"].as_bool().unwrap_or(false) {
+            return Ok(None);
+        }
+        let data = &entry["data"];
+
+        Ok(Some(GameMetadata {
+            name: data["name"].as_str().map(str::to_string),
+            description: data["short_description"].as_str().map(str::to_string),
+            released: Self::release_date(&data["release_date"]),
+            background_image: data["header_image"].as_str().map(str::to_string),
+            website: data["website"].as_str().filter(|url| !url.is_empty()).map(str::to_string),
+            metacritic: data["metacritic"]["score"].as_i64(),
+            developers: Self::strings(&data["developers"], None),
+            publishers: Self::strings(&data["publishers"], None),
+            genres: Self::strings(&data["genres"], Some("description")),
+            price: data["price_overview"]["final_formatted"].as_str().map(str::to_string),
+            steam_app_id: Some(app_id),
+            raw: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::stub;
+
+    fn game(steam_app_id: Option<u32>) -> GameReference {
+        GameReference { rawg_id: 3498, steam_app_id, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn reads_store_details_from_recorded_responses() {
+        let provider = ProviderSteam::with_base_url(stub::steam());
+        let metadata = provider.fetch_metadata(&game(Some(271590))).await.unwrap().unwrap();
+
+        assert_eq!(metadata.name.as_deref(), Some("Grand Theft Auto V Legacy"));
+        assert_eq!(metadata.released.as_deref(), Some("2015-04-13"));
+        assert_eq!(metadata.price.as_deref(), Some("$14.99"));
+        assert_eq!(metadata.metacritic, Some(96));
+        assert_eq!(metadata.publishers, vec!["Rockstar Games"]);
+        assert_eq!(metadata.genres, vec!["Action", "Adventure"]);
+    }
+
+    #[tokio::test]
+    async fn unknown_apps_have_no_metadata() {
+        let provider = ProviderSteam::with_base_url(stub::steam());
+        assert!(provider.fetch_metadata(&game(Some(1))).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn waits_for_the_steam_app_id() {
+        let provider = ProviderSteam::with_base_url(stub::steam());
+        assert!(!provider.ready(&game(None)));
+        assert!(provider.ready(&game(Some(271590))));
+    }
+
+    #[test]
+    fn parses_both_store_date_formats() {
+        assert_eq!(ProviderSteam::release_date(&serde_json::json!({ "date": "13 Apr, 2015" })).as_deref(), Some("2015-04-13"));
+        assert_eq!(ProviderSteam::release_date(&serde_json::json!({ "date": "Apr 13, 2015" })).as_deref(), Some("2015-04-13"));
+        assert_eq!(ProviderSteam::release_date(&serde_json::json!({ "date": "Coming soon" })), None);
+    }
+}
@@ -1,4 +1,14 @@
+pub(crate) mod auth;
 pub(crate) mod constants;
+pub(crate) mod cursor;
 pub(crate) mod helpers;
+pub(crate) mod images;
+pub(crate) mod metadata;
 pub(crate) mod providers;
+pub(crate) mod requirements;
+pub(crate) mod sanitizer;
+pub(crate) mod storage;
+#[cfg(test)]
+pub(crate) mod stub;
+pub(crate) mod webhook;
 pub(crate) mod formatters;
@@ -0,0 +1,471 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::model::dto::requirements::*;
+
+lazy_static! {
+    static ref BREAK_TAG_REGEX: Regex = Regex::new(r"(?i)<br\s*/?>|</?(p|li|ul)[^>]*>").unwrap();
+    static ref TAG_REGEX: Regex = Regex::new(r"<[^>]+>").unwrap();
+    static ref FIELD_REGEX: Regex = Regex::new(r"(?i)(OS|Operating System|Processor|CPU|Memory|RAM|Graphics|Video Card|Video|GPU|DirectX|Storage|Hard Drive|Hard Disk Space|Disk Space|HDD|Sound Card|Network|Additional Notes)\s*\*?\s*:").unwrap();
+    static ref SIZE_REGEX: Regex = Regex::new(r"(?i)([0-9]+(?:[.,][0-9]+)?)\s*(TB|GB|MB)").unwrap();
+    static ref NUMBER_REGEX: Regex = Regex::new(r"([0-9]{1,2})").unwrap();
+    static ref INTEL_REGEX: Regex = Regex::new(r"(?i)\bi([3579])[- ]?([0-9]{3,5})").unwrap();
+    static ref RYZEN_REGEX: Regex = Regex::new(r"(?i)ryzen\s*([3579])\s*(?:pro\s*)?([0-9]{4})").unwrap();
+    static ref NVIDIA_REGEX: Regex = Regex::new(r"(?i)\b(?:gtx|rtx)\s*([0-9]{3,4})").unwrap();
+    static ref RADEON_REGEX: Regex = Regex::new(r"(?i)\brx\s*([0-9]{3,4})").unwrap();
+    static ref WINDOWS_REGEX: Regex = Regex::new(r"(?i)windows\s*(7|8\.1|8|10|11)").unwrap();
+}
+
+fn size_in_gb(value: &str) -> Option<f32> {
+    let caps = SIZE_REGEX.captures(value)?;
+    let amount: f32 = caps[1].replace(',', ".").parse().ok()?;
+    match caps[2].to_uppercase().as_str() {
+        "TB" => Some(amount * 1024.0),
+        "MB" => Some(amount / 1024.0),
+        _ => Some(amount),
+    }
+}
+
+/// Разбирает текст требований RAWG ("OS: Windows 10 ... Storage: 50 GB") в структуру.
+pub fn parse_requirements(text: &str) -> Option<SystemRequirements> {
+    let text = BREAK_TAG_REGEX.replace_all(text, "\n");
+    let text = TAG_REGEX.replace_all(&text, "");
+    let text = FIELD_REGEX.replace_all(&text, "\n$0");
+
+    let mut requirements = SystemRequirements::default();
+    for line in text.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim().trim_end_matches('*').trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+        if value.is_empty() {
+            continue;
+        }
+
+        match key.as_str() {
+            "os" | "operating system" => requirements.os = Some(value.to_string()),
+            "processor" | "cpu" => requirements.cpu = Some(value.to_string()),
+            "memory" | "ram" => requirements.ram_gb = size_in_gb(value),
+            "graphics" | "video card" | "video" | "gpu" => requirements.gpu = Some(value.to_string()),
+            "directx" => {
+                requirements.directx = NUMBER_REGEX
+                    .captures(value)
+                    .and_then(|caps| caps[1].parse().ok())
+            }
+            "storage" | "hard drive" | "hard disk space" | "disk space" | "hdd" => {
+                requirements.storage_gb = size_in_gb(value)
+            }
+            _ => {}
+        }
+    }
+
+    if requirements == SystemRequirements::default() {
+        None
+    } else {
+        Some(requirements)
+    }
+}
+
+/// Достаёт требования для PC из массива `platforms` ответа RAWG.
+pub fn pc_requirements(details: &Value) -> Option<PcRequirements> {
+    let pc = details["platforms"]
+        .as_array()?
+        .iter()
+        .find(|platform| platform["platform"]["slug"].as_str() == Some("pc"))?;
+
+    let requirements = if pc["requirements"].is_object() {
+        &pc["requirements"]
+    } else {
+        &pc["requirements_en"]
+    };
+
+    let minimum = requirements["minimum"].as_str().and_then(parse_requirements);
+    let recommended = requirements["recommended"].as_str().and_then(parse_requirements);
+    if minimum.is_none() && recommended.is_none() {
+        return None;
+    }
+
+    Some(PcRequirements { minimum, recommended })
+}
+
+/// Добавляет в ответ RAWG поле `pc_requirements` со структурированными требованиями.
+pub fn attach_pc_requirements(details: &mut Value) {
+    if let Some(requirements) = pc_requirements(details) {
+        if let (Some(map), Ok(value)) = (details.as_object_mut(), serde_json::to_value(requirements)) {
+            map.insert("pc_requirements".to_string(), value);
+        }
+    }
+}
+
+/// Прибавка к оценке за класс модели (i3/i5/i7/i9, Ryzen 3/5/7/9) в годах:
+/// старший класс прошлых поколений обычно быстрее младшего класса новых.
+fn tier_bonus(tier: u32) -> Option<f32> {
+    match tier {
+        3 => Some(0.0),
+        5 => Some(3.0),
+        7 => Some(5.0),
+        9 => Some(6.0),
+        _ => None,
+    }
+}
+
+/// Грубая оценка производительности процессора: год выхода поколения плюс
+/// прибавка за класс, в одной шкале для Intel и AMD. В требованиях часто указаны
+/// альтернативы ("i5-2500K / Ryzen 3 1200"), поэтому берётся самая слабая.
+fn cpu_score(text: &str) -> Option<f32> {
+    let intel = INTEL_REGEX.captures_iter(text).filter_map(|caps| {
+        let tier: u32 = caps[1].parse().ok()?;
+        let model = &caps[2];
+        let generation: u32 = match model.len() {
+            3 => 1,
+            4 => model[..1].parse().ok()?,
+            _ => model[..2].parse().ok()?,
+        };
+        let year = match generation {
+            1 => 2009.5,
+            2 => 2011.0,
+            3 => 2012.0,
+            4 => 2013.0,
+            5 | 6 => 2015.0,
+            7 => 2017.0,
+            8 => 2017.5,
+            9 => 2018.5,
+            10 => 2020.0,
+            11 => 2021.0,
+            12 => 2021.5,
+            13 => 2022.5,
+            14 => 2023.5,
+            _ => return None,
+        };
+        Some(year + tier_bonus(tier)?)
+    });
+    let ryzen = RYZEN_REGEX.captures_iter(text).filter_map(|caps| {
+        let tier: u32 = caps[1].parse().ok()?;
+        let year = match &caps[2][..1] {
+            "1" => 2017.0,
+            "2" => 2018.0,
+            "3" => 2019.0,
+            "4" => 2019.5,
+            "5" => 2020.5,
+            "6" => 2021.5,
+            "7" | "8" => 2022.5,
+            "9" => 2024.0,
+            _ => return None,
+        };
+        Some(year + tier_bonus(tier)?)
+    });
+
+    intel.chain(ryzen).reduce(f32::min)
+}
+
+/// Грубая оценка производительности видеокарты в единой шкале для NVIDIA и AMD:
+/// номер поколения плюс класс модели, так что GTX 980 и GTX 1060 оказываются рядом.
+fn gpu_score(text: &str) -> Option<f32> {
+    let nvidia = NVIDIA_REGEX.captures_iter(text).filter_map(|caps| {
+        let model: u32 = caps[1].parse().ok()?;
+        let generation = match model / 100 {
+            4 => 1.0,
+            5 => 2.0,
+            6 => 3.0,
+            7 => 4.0,
+            9 => 5.0,
+            10 => 6.0,
+            16 => 6.5,
+            20 => 7.0,
+            30 => 8.0,
+            40 => 9.0,
+            50 => 10.0,
+            _ => return None,
+        };
+        Some(generation + ((model % 100) / 10) as f32)
+    });
+    let radeon = RADEON_REGEX.captures_iter(text).filter_map(|caps| {
+        let model: u32 = caps[1].parse().ok()?;
+        let (generation, tier) = match model / 100 {
+            4 => (4.0, (model % 100) / 10),
+            5 => (4.5, (model % 100) / 10),
+            53..=59 => (7.0, (model % 1000) / 100),
+            60..=69 => (8.0, (model % 1000) / 100),
+            70..=79 => (9.0, (model % 1000) / 100),
+            90..=99 => (10.0, (model % 100) / 10),
+            _ => return None,
+        };
+        Some(generation + tier as f32)
+    });
+
+    nvidia.chain(radeon).reduce(f32::min)
+}
+
+/// Сравнивает значение с требованиями. Если известны только рекомендуемые,
+/// то отставание от них не означает, что игра не запустится, и даёт `Warn`.
+fn graded(provided: Option<f32>, minimum: Option<f32>, recommended: Option<f32>) -> Verdict {
+    let provided = match provided {
+        Some(provided) => provided,
+        None => return Verdict::Unknown,
+    };
+
+    match (minimum, recommended) {
+        (Some(minimum), _) if provided < minimum => Verdict::Fail,
+        (_, Some(recommended)) if provided < recommended => Verdict::Warn,
+        (None, None) => Verdict::Unknown,
+        _ => Verdict::Pass,
+    }
+}
+
+fn os_verdict(provided: Option<&str>, required: Option<&str>) -> (Verdict, Option<String>) {
+    let (provided, required) = match (provided, required) {
+        (Some(provided), Some(required)) => (provided.to_lowercase(), required.to_lowercase()),
+        _ => return (Verdict::Unknown, None),
+    };
+
+    if required.contains("windows") && !provided.contains("windows") {
+        return (Verdict::Warn, Some("Windows build, may run through Proton or Wine".to_string()));
+    }
+    if required.contains("64") && provided.contains("32") {
+        return (Verdict::Fail, Some("64-bit OS is required".to_string()));
+    }
+
+    let version = |text: &str| {
+        WINDOWS_REGEX
+            .captures(text)
+            .and_then(|caps| caps[1].parse::<f32>().ok())
+    };
+    match (version(&provided), version(&required)) {
+        (Some(provided), Some(required)) if provided < required => (Verdict::Fail, None),
+        _ => (Verdict::Pass, None),
+    }
+}
+
+/// Сравнивает профиль железа с требованиями игры по каждому компоненту.
+pub fn check_compatibility(requirements: &PcRequirements, profile: &HardwareProfile) -> Vec<ComponentVerdict> {
+    let minimum = requirements.minimum.clone().unwrap_or_default();
+    let recommended = requirements.recommended.clone().unwrap_or_default();
+    let gb = |value: Option<f32>| value.map(|value| format!("{} GB", value));
+    let text_verdict = |score: fn(&str) -> Option<f32>, provided: &Option<String>, min: &Option<String>, rec: &Option<String>| {
+        let provided_score = provided.as_deref().and_then(score);
+        let min_score = min.as_deref().and_then(score);
+        let rec_score = rec.as_deref().and_then(score);
+        match graded(provided_score, min_score, rec_score) {
+            Verdict::Unknown if provided.is_some() && (min.is_some() || rec.is_some()) => {
+                (Verdict::Warn, Some("Could not compare models automatically".to_string()))
+            }
+            verdict => (verdict, None),
+        }
+    };
+
+    let (os, os_note) = os_verdict(profile.os.as_deref(), minimum.os.as_deref().or(recommended.os.as_deref()));
+    let (cpu, cpu_note) = text_verdict(cpu_score, &profile.cpu, &minimum.cpu, &recommended.cpu);
+    let (gpu, gpu_note) = text_verdict(gpu_score, &profile.gpu, &minimum.gpu, &recommended.gpu);
+
+    vec![
+        ComponentVerdict {
+            component: "os".to_string(),
+            verdict: os,
+            minimum: minimum.os.clone(),
+            recommended: recommended.os.clone(),
+            provided: profile.os.clone(),
+            note: os_note,
+        },
+        ComponentVerdict {
+            component: "cpu".to_string(),
+            verdict: cpu,
+            minimum: minimum.cpu.clone(),
+            recommended: recommended.cpu.clone(),
+            provided: profile.cpu.clone(),
+            note: cpu_note,
+        },
+        ComponentVerdict {
+            component: "ram".to_string(),
+            verdict: graded(profile.ram_gb, minimum.ram_gb, recommended.ram_gb),
+            minimum: gb(minimum.ram_gb),
+            recommended: gb(recommended.ram_gb),
+            provided: gb(profile.ram_gb),
+            note: None,
+        },
+        ComponentVerdict {
+            component: "gpu".to_string(),
+            verdict: gpu,
+            minimum: minimum.gpu.clone(),
+            recommended: recommended.gpu.clone(),
+            provided: profile.gpu.clone(),
+            note: gpu_note,
+        },
+        ComponentVerdict {
+            component: "directx".to_string(),
+            verdict: graded(
+                profile.directx.map(|version| version as f32),
+                minimum.directx.map(|version| version as f32),
+                recommended.directx.map(|version| version as f32),
+            ),
+            minimum: minimum.directx.map(|version| version.to_string()),
+            recommended: recommended.directx.map(|version| version.to_string()),
+            provided: profile.directx.map(|version| version.to_string()),
+            note: None,
+        },
+        ComponentVerdict {
+            component: "storage".to_string(),
+            verdict: graded(profile.storage_gb, minimum.storage_gb.or(recommended.storage_gb), None),
+            minimum: gb(minimum.storage_gb),
+            recommended: gb(recommended.storage_gb),
+            provided: gb(profile.storage_gb),
+            note: None,
+        },
+    ]
+}
+
+/// Итоговый вердикт: худший из известных, либо `Unknown`, если сравнить не удалось.
+pub fn overall_verdict(components: &[ComponentVerdict]) -> Verdict {
+    components
+        .iter()
+        .map(|component| component.verdict)
+        .max()
+        .unwrap_or(Verdict::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requirement_texts() {
+        let cases = [
+            (
+                "<strong>Minimum:</strong><br><ul><li><strong>OS:</strong> Windows 10 64-bit</li><li><strong>Processor:</strong> Intel Core i5-4460</li><li><strong>Memory:</strong> 8 GB RAM</li><li><strong>Graphics:</strong> GTX 970</li><li><strong>DirectX:</strong> Version 12</li><li><strong>Storage:</strong> 50 GB available space</li></ul>",
+                SystemRequirements {
+                    os: Some("Windows 10 64-bit".to_string()),
+                    cpu: Some("Intel Core i5-4460".to_string()),
+                    ram_gb: Some(8.0),
+                    gpu: Some("GTX 970".to_string()),
+                    directx: Some(12),
+                    storage_gb: Some(50.0),
+                },
+            ),
+            (
+                "Minimum: OS: Windows 7 Processor: Ryzen 5 1600 Memory: 4096 MB RAM Hard Drive: 1,5 TB",
+                SystemRequirements {
+                    os: Some("Windows 7".to_string()),
+                    cpu: Some("Ryzen 5 1600".to_string()),
+                    ram_gb: Some(4.0),
+                    storage_gb: Some(1536.0),
+                    ..Default::default()
+                },
+            ),
+            (
+                "Video Card: RX 580\nDisk Space: 512 MB",
+                SystemRequirements {
+                    gpu: Some("RX 580".to_string()),
+                    storage_gb: Some(0.5),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(parse_requirements(text), Some(expected), "{}", text);
+        }
+        assert_eq!(parse_requirements("Requires a 64-bit processor"), None);
+    }
+
+    #[test]
+    fn ranks_processors_by_performance() {
+        let ordered = [
+            "Intel Core i3-2100",
+            "Intel Core i5-2500K",
+            "AMD Ryzen 3 1200",
+            "AMD Ryzen 5 1600",
+            "Intel Core i5-9400F",
+            "AMD Ryzen 5 3600",
+            "Intel Core i5-12400",
+            "AMD Ryzen 7 7700X",
+        ];
+        for pair in ordered.windows(2) {
+            let (weaker, stronger) = (cpu_score(pair[0]).unwrap(), cpu_score(pair[1]).unwrap());
+            assert!(weaker < stronger, "{} ({}) should rank below {} ({})", pair[0], weaker, pair[1], stronger);
+        }
+        assert!(cpu_score("Intel Core i3-9100") < cpu_score("Intel Core i7-8700K"));
+        assert_eq!(cpu_score("Intel Core i5-2500K / AMD Ryzen 3 1200"), cpu_score("i5-2500K"));
+        assert_eq!(cpu_score("Dual core 2.4 GHz"), None);
+    }
+
+    #[test]
+    fn ranks_graphics_cards_by_performance() {
+        let ordered = [
+            "GeForce GTX 660",
+            "GeForce GTX 1050",
+            "GeForce GTX 1060",
+            "GeForce GTX 1660",
+            "Radeon RX 6600",
+            "GeForce RTX 3070",
+            "Radeon RX 9070",
+        ];
+        for pair in ordered.windows(2) {
+            let (weaker, stronger) = (gpu_score(pair[0]).unwrap(), gpu_score(pair[1]).unwrap());
+            assert!(weaker < stronger, "{} ({}) should rank below {} ({})", pair[0], weaker, pair[1], stronger);
+        }
+        assert!(gpu_score("RX 580") > gpu_score("GTX 1050"));
+        assert_eq!(gpu_score("GTX 980"), gpu_score("GTX 1070"));
+        assert_eq!(gpu_score("RX 5700"), gpu_score("RTX 2070"));
+        assert_eq!(gpu_score("Intel HD Graphics 4000"), None);
+    }
+
+    #[test]
+    fn grades_against_minimum_and_recommended() {
+        let cases = [
+            (Some(8.0), Some(8.0), Some(16.0), Verdict::Warn),
+            (Some(16.0), Some(8.0), Some(16.0), Verdict::Pass),
+            (Some(4.0), Some(8.0), Some(16.0), Verdict::Fail),
+            (Some(8.0), Some(8.0), None, Verdict::Pass),
+            (Some(4.0), Some(8.0), None, Verdict::Fail),
+            (Some(4.0), None, Some(16.0), Verdict::Warn),
+            (Some(16.0), None, Some(16.0), Verdict::Pass),
+            (Some(4.0), None, None, Verdict::Unknown),
+            (None, Some(8.0), Some(16.0), Verdict::Unknown),
+        ];
+
+        for (provided, minimum, recommended, expected) in cases {
+            assert_eq!(graded(provided, minimum, recommended), expected, "{:?} {:?} {:?}", provided, minimum, recommended);
+        }
+    }
+
+    #[test]
+    fn checks_profiles_against_requirements() {
+        let requirements = PcRequirements {
+            minimum: parse_requirements("OS: Windows 10 64-bit Processor: Intel Core i5-4460 Memory: 8 GB Graphics: GTX 970 Storage: 50 GB"),
+            recommended: parse_requirements("Processor: Intel Core i7-8700K Memory: 16 GB Graphics: RTX 2070"),
+        };
+        let verdict = |profile: HardwareProfile| {
+            check_compatibility(&requirements, &profile)
+                .into_iter()
+                .map(|component| (component.component, component.verdict))
+                .collect::<Vec<_>>()
+        };
+
+        let components = verdict(HardwareProfile {
+            os: Some("Windows 11 64-bit".to_string()),
+            cpu: Some("Intel Core i5-12400".to_string()),
+            ram_gb: Some(16.0),
+            gpu: Some("GeForce GTX 1060".to_string()),
+            directx: None,
+            storage_gb: Some(40.0),
+        });
+        assert_eq!(
+            components,
+            vec![
+                ("os".to_string(), Verdict::Pass),
+                ("cpu".to_string(), Verdict::Pass),
+                ("ram".to_string(), Verdict::Pass),
+                ("gpu".to_string(), Verdict::Warn),
+                ("directx".to_string(), Verdict::Unknown),
+                ("storage".to_string(), Verdict::Fail),
+            ]
+        );
+
+        let only_recommended = PcRequirements { minimum: None, recommended: requirements.recommended.clone() };
+        let components = check_compatibility(
+            &only_recommended,
+            &HardwareProfile { ram_gb: Some(8.0), cpu: Some("Intel Core i3-8100".to_string()), ..Default::default() },
+        );
+        assert_eq!(overall_verdict(&components), Verdict::Warn);
+    }
+}
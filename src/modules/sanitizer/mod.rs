@@ -0,0 +1,146 @@
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::modules::cursor::rewrite_pagination;
+
+/// Параметры запроса, которые считаются учётными данными апстрима.
+const CREDENTIAL_PARAMS: [&str; 6] = [
+    "key",
+    "api_key",
+    "apikey",
+    "token",
+    "access_token",
+    "client_secret",
+];
+
+/// Приводит ответ апстрима к виду, безопасному для отдачи клиенту:
+/// ссылки пагинации заменяются курсорами, из остальных ссылок удаляются
+/// учётные данные, а активный ключ вырезается из любых строк.
+pub fn sanitize_response(json: &mut Value, route: &str, api_key: &str) {
+    rewrite_pagination(json, route);
+    sanitize_value(json, api_key);
+}
+
+fn sanitize_value(value: &mut Value, api_key: &str) {
+    match value {
+        Value::String(text) => {
+            if let Some(clean) = strip_credentials(text) {
+                *text = clean;
+            }
+            if !api_key.is_empty() && text.contains(api_key) {
+                *text = text.replace(api_key, "");
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                sanitize_value(item, api_key);
+            }
+        }
+        Value::Object(map) => {
+            for (_, item) in map.iter_mut() {
+                sanitize_value(item, api_key);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Возвращает ссылку без параметров с учётными данными, если они в ней были.
+fn strip_credentials(text: &str) -> Option<String> {
+    if !text.starts_with("http://") && !text.starts_with("https://") {
+        return None;
+    }
+
+    let mut url = Url::parse(text).ok()?;
+    let is_credential = |name: &str| CREDENTIAL_PARAMS.contains(&name.to_ascii_lowercase().as_str());
+
+    if !url.query_pairs().any(|(name, _)| is_credential(&name)) && url.password().is_none() {
+        return None;
+    }
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !is_credential(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+
+    Some(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::stub;
+    use serde_json::json;
+
+    const KEY: &str = stub::RAWG_API_KEY;
+
+    fn rawg_url(path_and_query: &str) -> String {
+        format!("{}{}", stub::rawg(), path_and_query)
+    }
+
+    #[test]
+    fn removes_the_key_from_pagination_and_nested_urls() {
+        let mut json = json!({
+            "count": 40,
+            "next": rawg_url(&format!("games?key={}&page=3&search=portal", KEY)),
+            "previous": rawg_url(&format!("games?key={}&search=portal", KEY)),
+            "results": [
+                {
+                    "name": "Portal",
+                    "clip": {
+                        "clip": format!("https://media.rawg.io/media/stories/a1b.mp4?key={}", KEY),
+                        "video": format!("https://www.youtube.com/watch?v=TluRVBhmf8w&api_key={}", KEY),
+                    },
+                    "stores": [
+                        { "url": format!("https://user:{}@store.steampowered.com/app/400/", KEY) }
+                    ],
+                    "description": format!("Mirror of https://example.com/?token={} in text", KEY),
+                }
+            ],
+        });
+
+        sanitize_response(&mut json, "/api/games/search", KEY);
+        let body = json.to_string();
+
+        assert!(!body.contains(KEY), "response still contains the key: {}", body);
+        assert!(json["next"].as_str().unwrap().starts_with("/api/games/search?next="));
+        assert!(json["previous"].as_str().unwrap().starts_with("/api/games/search?next="));
+        assert_eq!(
+            json["results"][0]["clip"]["video"],
+            "https://www.youtube.com/watch?v=TluRVBhmf8w"
+        );
+        assert_eq!(json["results"][0]["stores"][0]["url"], "https://store.steampowered.com/app/400/");
+    }
+
+    #[test]
+    fn drops_pagination_links_to_other_hosts() {
+        let mut json = json!({
+            "next": format!("https://evil.example.com/api/games?key={}&page=2", KEY),
+            "previous": null,
+        });
+
+        sanitize_response(&mut json, "/api/games", KEY);
+
+        assert_eq!(json["next"], Value::Null);
+        assert_eq!(json["previous"], Value::Null);
+    }
+
+    #[test]
+    fn keeps_urls_without_credentials_untouched() {
+        let url = "https://media.rawg.io/media/games/20a/20aa03a10cda45239fe22d035c0ebe64.jpg";
+        let mut json = json!({ "background_image": url });
+
+        sanitize_response(&mut json, "/api/games/3498", KEY);
+
+        assert_eq!(json["background_image"], url);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::modules::helpers::{get_data_dir, random_token};
+use crate::service::saves::{SaveStorage, SaveStream, SaveUpload};
+use async_trait::async_trait;
+use std::env;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio_util::io::ReaderStream;
+
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    /// Архивы лежат в `saves` внутри каталога данных, путь переопределяется через `VEK_SAVES_DIR`.
+    pub fn new() -> Result<Self, std::io::Error> {
+        let root = match env::var("VEK_SAVES_DIR") {
+            Ok(dir) => PathBuf::from(dir),
+            Err(_) => get_data_dir()?.join("saves"),
+        };
+        Ok(LocalStorage { root })
+    }
+
+    /// Ключ состоит из сегментов `[A-Za-z0-9_-]`, разделённых `/`, поэтому не может выйти за корень.
+    fn path(&self, key: &str) -> Result<PathBuf, String> {
+        let valid = !key.is_empty()
+            && key.split('/').all(|segment| {
+                !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            });
+        if !valid {
+            return Err(format!("Invalid storage key: {}", key));
+        }
+        Ok(key.split('/').fold(self.root.clone(), |path, segment| path.join(segment)))
+    }
+}
+
+pub struct LocalUpload {
+    file: BufWriter<File>,
+    temp: PathBuf,
+    path: PathBuf,
+    finished: bool,
+}
+
+#[async_trait]
+impl SaveUpload for LocalUpload {
+    async fn write(&mut self, chunk: &[u8]) -> Result<(), String> {
+        self.file.write_all(chunk).await.map_err(|e| e.to_string())
+    }
+
+    async fn commit(mut self: Box<Self>) -> Result<(), String> {
+        self.file.flush().await.map_err(|e| e.to_string())?;
+        self.file.get_ref().sync_all().await.map_err(|e| e.to_string())?;
+        tokio::fs::rename(&self.temp, &self.path).await.map_err(|e| e.to_string())?;
+        self.finished = true;
+        Ok(())
+    }
+
+    async fn abort(mut self: Box<Self>) {
+        let _ = tokio::fs::remove_file(&self.temp).await;
+        self.finished = true;
+    }
+}
+
+/// Загрузка, прерванная вместе с запросом, не должна оставлять временный файл.
+impl Drop for LocalUpload {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = std::fs::remove_file(&self.temp);
+        }
+    }
+}
+
+#[async_trait]
+impl SaveStorage for LocalStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn begin_put(&self, key: &str) -> Result<Box<dyn SaveUpload>, String> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(|e| e.to_string())?;
+        }
+
+        // Запись через временный файл, чтобы оборванная загрузка не оставила половину архива.
+        let suffix = random_token(8).map_err(|e| e.to_string())?;
+        let temp = path.with_extension(format!("{}.tmp", suffix));
+        let file = File::create(&temp).await.map_err(|e| e.to_string())?;
+        Ok(Box::new(LocalUpload {
+            file: BufWriter::new(file),
+            temp,
+            path,
+            finished: false,
+        }))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<SaveStream>, String> {
+        match File::open(self.path(key)?).await {
+            Ok(file) => Ok(Some(Box::pin(ReaderStream::new(file)))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+
+    fn storage(name: &str) -> LocalStorage {
+        let root = std::env::temp_dir().join(format!("vek-saves-{}-{}", name, random_token(8).unwrap()));
+        LocalStorage { root }
+    }
+
+    fn files(storage: &LocalStorage) -> Vec<String> {
+        let dir = storage.root.join("user").join("3498");
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()).collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    async fn read(storage: &LocalStorage, key: &str) -> Option<Vec<u8>> {
+        let stream = storage.get(key).await.unwrap()?;
+        let chunks: Vec<_> = stream.try_collect().await.unwrap();
+        Some(chunks.concat())
+    }
+
+    #[tokio::test]
+    async fn committed_uploads_are_readable_by_key() {
+        let storage = storage("commit");
+        let mut upload = storage.begin_put("user/3498/a1").await.unwrap();
+        upload.write(b"first ").await.unwrap();
+        upload.write(b"second").await.unwrap();
+        assert!(read(&storage, "user/3498/a1").await.is_none());
+
+        upload.commit().await.unwrap();
+        assert_eq!(read(&storage, "user/3498/a1").await, Some(b"first second".to_vec()));
+        assert_eq!(files(&storage), vec!["a1".to_string()]);
+        let _ = std::fs::remove_dir_all(&storage.root);
+    }
+
+    #[tokio::test]
+    async fn aborted_and_dropped_uploads_leave_no_files() {
+        let storage = storage("abort");
+        let mut upload = storage.begin_put("user/3498/a1").await.unwrap();
+        upload.write(b"partial").await.unwrap();
+        upload.abort().await;
+
+        let mut upload = storage.begin_put("user/3498/a2").await.unwrap();
+        upload.write(b"partial").await.unwrap();
+        drop(upload);
+
+        assert!(files(&storage).is_empty());
+        assert!(read(&storage, "user/3498/a1").await.is_none());
+        let _ = std::fs::remove_dir_all(&storage.root);
+    }
+
+    #[tokio::test]
+    async fn keys_cannot_leave_the_root() {
+        let storage = storage("keys");
+        for key in ["../etc/passwd", "user/../../x", "", "user//a", "user/a.b"] {
+            assert!(storage.begin_put(key).await.is_err(), "{} was accepted", key);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Ключ, который записан в фикстурах RAWG вместо настоящего.
+pub const RAWG_API_KEY: &str = "0f4e2c9a7b1d4e6f8a3c5b7d9e1f2a4c";
+
+/// Адрес, под которым ответы RAWG были записаны. Заглушка подменяет его своим.
+const RECORDED_RAWG_URL: &str = "https://api.rawg.io/api/";
+
+static RAWG: OnceLock<String> = OnceLock::new();
+static STEAM: OnceLock<String> = OnceLock::new();
+
+/// Запускает локальный сервер с маршрутами `routes` в отдельном потоке и возвращает его адрес.
+pub fn spawn(routes: fn(&mut web::ServiceConfig)) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+    let address = format!("http://{}", listener.local_addr().expect("Stub server has no address"));
+
+    std::thread::spawn(move || {
+        actix_web::rt::System::new().block_on(async move {
+            HttpServer::new(move || App::new().configure(routes))
+                .workers(1)
+                .listen(listener)
+                .expect("Failed to start stub server")
+                .run()
+                .await
+        })
+    });
+
+    address
+}
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+/// Записанный ответ из `tests/fixtures`, `None` если такого файла нет.
+pub fn fixture(name: &str) -> Option<String> {
+    std::fs::read_to_string(fixture_path(name)).ok()
+}
+
+/// Заглушка RAWG, общая для всех тестов процесса. Адрес RAWG в тестах
+/// всегда указывает на неё, см. `modules::metadata::rawg::BASE_URL`.
+pub fn rawg() -> &'static str {
+    RAWG.get_or_init(|| {
+        let base_url = format!("{}/api/", spawn(rawg_routes));
+        std::env::set_var("VEK_RAWG_BASE_URL", &base_url);
+        base_url
+    })
+}
+
+fn rawg_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/{path:.*}", web::get().to(rawg_fixture));
+}
+
+/// `GET /api/games/3498/movies?page=2` отдаёт `rawg/games_3498_movies_page2.json`.
+/// Игра `0` имитирует сбой RAWG, неизвестные пути отвечают 404, как настоящий API.
+async fn rawg_fixture(req: HttpRequest, path: web::Path<String>) -> HttpResponse {
+    let query = web::Query::<Vec<(String, String)>>::from_query(req.query_string())
+        .map(|query| query.into_inner())
+        .unwrap_or_default();
+    let param = |name: &str| query.iter().find(|(param, _)| param == name).map(|(_, value)| value.as_str());
+
+    if param("key") != Some(RAWG_API_KEY) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({ "error": "The key parameter is not provided" }));
+    }
+
+    let path = path.into_inner();
+    let base_url = RAWG.get().map(String::as_str).unwrap_or(RECORDED_RAWG_URL);
+    if path.starts_with("games/0") {
+        // Как и настоящий апстрим при сбое, заглушка повторяет запрос вместе с ключом.
+        return HttpResponse::BadGateway().body(format!("Upstream error for {}{}?{}", base_url, path, req.query_string()));
+    }
+
+    let mut name = path.trim_matches('/').replace('/', "_");
+    if let Some(page) = param("page").filter(|page| *page != "1") {
+        name = format!("{}_page{}", name, page);
+    }
+
+    match fixture(&format!("rawg/{}.json", name)) {
+        Some(body) => HttpResponse::Ok()
+            .content_type("application/json")
+            .body(body.replace(RECORDED_RAWG_URL, base_url)),
+        None => HttpResponse::NotFound().json(serde_json::json!({ "detail": "Not found." })),
+    }
+}
+
+/// Заглушка Steam Store API, адрес передаётся в `ProviderSteam::with_base_url`.
+pub fn steam() -> &'static str {
+    STEAM.get_or_init(|| format!("{}/api/", spawn(steam_routes)))
+}
+
+fn steam_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/appdetails", web::get().to(steam_app_details));
+}
+
+/// Для приложений без фикстуры Steam, как и настоящий, отвечает `success: false`.
+async fn steam_app_details(query: web::Query<Vec<(String, String)>>) -> HttpResponse {
+    let app_id = query
+        .iter()
+        .find(|(name, _)| name == "appids")
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default();
+
+    match fixture(&format!("steam/appdetails_{}.json", app_id)) {
+        Some(body) => HttpResponse::Ok().content_type("application/json").body(body),
+        None => HttpResponse::Ok().json(serde_json::json!({ app_id: { "success": false } })),
+    }
+}
+
+/// Код авторизации, который заглушка OAuth меняет на `OAUTH_ACCESS_TOKEN`.
+pub const OAUTH_CODE: &str = "4/0AQlEd8x-stub-code";
+pub const OAUTH_ACCESS_TOKEN: &str = "ya29.stub-access-token";
+/// Подпись, которую заглушка Steam OpenID признаёт в `check_authentication`.
+pub const OPENID_SIGNATURE: &str = "W0u9DeUgKMvhhyTRjEXWOS3wTW8=";
+
+static AUTH: OnceLock<String> = OnceLock::new();
+
+/// Заглушка провайдеров входа: OAuth2 (`/oauth/token`, `/oauth/userinfo`)
+/// и Steam OpenID (`/openid/login`).
+pub fn auth() -> &'static str {
+    AUTH.get_or_init(|| spawn(auth_routes))
+}
+
+fn auth_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/oauth/token", web::post().to(oauth_token))
+        .route("/oauth/userinfo", web::get().to(oauth_userinfo))
+        .route("/openid/login", web::post().to(openid_check));
+}
+
+/// Как и настоящие провайдеры, на неизвестный код отвечает 400 `invalid_grant`.
+async fn oauth_token(form: web::Form<Vec<(String, String)>>) -> HttpResponse {
+    let param = |name: &str| form.iter().find(|(param, _)| param == name).map(|(_, value)| value.as_str());
+    if param("grant_type") != Some("authorization_code") || param("code") != Some(OAUTH_CODE) {
+        return HttpResponse::BadRequest().json(serde_json::json!({ "error": "invalid_grant" }));
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "access_token": OAUTH_ACCESS_TOKEN,
+        "token_type": "Bearer",
+        "expires_in": 3599,
+        "scope": "openid email profile",
+    }))
+}
+
+async fn oauth_userinfo(req: HttpRequest) -> HttpResponse {
+    let authorization = req
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok());
+    if authorization != Some(format!("Bearer {}", OAUTH_ACCESS_TOKEN).as_str()) {
+        return HttpResponse::Unauthorized().json(serde_json::json!({ "error": "invalid_token" }));
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "sub": "109876543210987654321",
+        "email": "alice@example.com",
+        "email_verified": true,
+    }))
+}
+
+async fn openid_check(form: web::Form<Vec<(String, String)>>) -> HttpResponse {
+    let param = |name: &str| form.iter().find(|(param, _)| param == name).map(|(_, value)| value.as_str());
+    let valid = param("openid.mode") == Some("check_authentication") && param("openid.sig") == Some(OPENID_SIGNATURE);
+    HttpResponse::Ok()
+        .content_type("text/plain")
+        .body(format!("ns:http://specs.openid.net/auth/2.0\nis_valid:{}\n", valid))
+}
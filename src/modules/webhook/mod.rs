@@ -0,0 +1,296 @@
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use sha2::Sha256;
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-Vek-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Vek-Timestamp";
+pub const EVENT_HEADER: &str = "X-Vek-Event";
+pub const DELIVERY_HEADER: &str = "X-Vek-Delivery";
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `VEK_WEBHOOK_ALLOW_PRIVATE=1` разрешает адреса в локальной сети, например для тестового приёмника.
+pub fn allow_private() -> bool {
+    env::var("VEK_WEBHOOK_ALLOW_PRIVATE")
+        .map(|value| value == "true" || value == "1")
+        .unwrap_or(false)
+}
+
+/// Подпись `sha256=<hex>` от `<timestamp>.<body>`. Получатель проверяет её
+/// тем же секретом и отбрасывает запросы со старой меткой времени.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+fn is_private_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        // 100.64.0.0/10: CGNAT, адреса внутри сети провайдера.
+        || (a == 100 && (b & 0xc0) == 64)
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b & 0xfe) == 18)
+        || a >= 240
+}
+
+/// IPv4, встроенный в IPv6: `::ffff:a.b.c.d`, `::a.b.c.d` и NAT64 `64:ff9b::a.b.c.d`.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let tail = Ipv4Addr::new(
+        (segments[6] >> 8) as u8,
+        segments[6] as u8,
+        (segments[7] >> 8) as u8,
+        segments[7] as u8,
+    );
+    match segments[..6] {
+        [0, 0, 0, 0, 0, 0xffff] | [0, 0, 0, 0, 0, 0] => Some(tail),
+        [0x64, 0xff9b, 0, 0, 0, 0] => Some(tail),
+        _ => None,
+    }
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(ipv4) = embedded_ipv4(ip) {
+                return is_private_ipv4(ipv4);
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || (first == 0x2001 && ip.segments()[1] == 0x0db8)
+        }
+    }
+}
+
+fn is_local_name(host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_lowercase();
+    host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") || host.ends_with(".internal")
+}
+
+/// Проверяет адрес вебхука: только http(s) и, если не разрешено явно, не локальная сеть.
+/// Имена хостов дополнительно проверяются при доставке, см. `PublicResolver`.
+pub fn check_url(url: &str, allow_private: bool) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|_| "url must be an absolute http or https URL".to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("url must be an absolute http or https URL".to_string());
+    }
+
+    // Url уже привёл записи вроде `2130706433` и `0x7f.1` к обычному виду IPv4.
+    let host = parsed.host_str().ok_or_else(|| "url must have a host".to_string())?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let private = match host.parse::<IpAddr>() {
+        Ok(ip) => is_private_ip(ip),
+        Err(_) => is_local_name(host),
+    };
+    if private && !allow_private {
+        return Err("url must not point to a private network".to_string());
+    }
+
+    Ok(parsed)
+}
+
+pub fn validate_url(url: &str) -> Result<Url, String> {
+    check_url(url, allow_private())
+}
+
+/// Резолвер для доставки: адрес проверяется в момент соединения, поэтому имя,
+/// которое после регистрации стало указывать в локальную сеть, никуда не приведёт.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| !is_private_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// HTTP-клиент доставки вебхуков.
+pub struct WebhookClient {
+    client: Client,
+    allow_private: bool,
+}
+
+impl WebhookClient {
+    pub fn new(allow_private: bool) -> Self {
+        // Редиректы не выполняются, а прокси не используется: иначе адрес проверял бы не мы.
+        let mut builder = Client::builder()
+            .redirect(Policy::none())
+            .no_proxy()
+            .timeout(DELIVERY_TIMEOUT);
+        if !allow_private {
+            builder = builder.dns_resolver(Arc::new(PublicResolver));
+        }
+        WebhookClient {
+            client: builder.build().unwrap_or_default(),
+            allow_private,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(allow_private())
+    }
+
+    /// Отправляет подписанную доставку. Возвращает статус ответа или текст ошибки
+    /// со статусом, если получатель ответил не 2xx.
+    pub async fn post(
+        &self,
+        url: &str,
+        event: &str,
+        delivery_id: &str,
+        secret: &str,
+        payload: &str,
+    ) -> Result<u16, (Option<u16>, String)> {
+        // Адрес проверяется снова: IP-адреса в URL резолвер не видит.
+        let url = check_url(url, self.allow_private).map_err(|e| (None, e))?;
+        let timestamp = chrono::Utc::now().timestamp();
+        let response = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header(EVENT_HEADER, event)
+            .header(DELIVERY_HEADER, delivery_id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(secret, timestamp, payload))
+            .body(payload.to_string())
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(status.as_u16())
+        } else {
+            Err((Some(status.as_u16()), format!("Receiver responded with {}", status)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::stub;
+    use actix_web::{web, HttpRequest, HttpResponse};
+    use std::str::FromStr;
+
+    const SECRET: &str = "whsec_3f9a1c7e5b2d4f6a8c0e";
+
+    #[test]
+    fn private_addresses_are_detected() {
+        let private = [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1",
+            "100.127.255.254", "0.0.0.0", "255.255.255.255", "::1", "::", "fd00::1", "fe80::1",
+            "::ffff:127.0.0.1", "::ffff:169.254.169.254", "::ffff:10.0.0.1", "64:ff9b::10.0.0.1", "::127.0.0.1",
+        ];
+        for ip in private {
+            assert!(is_private_ip(ip.parse().unwrap()), "{} is private", ip);
+        }
+
+        let public = ["93.184.216.34", "100.128.0.1", "1.1.1.1", "2606:4700:4700::1111", "::ffff:93.184.216.34"];
+        for ip in public {
+            assert!(!is_private_ip(ip.parse().unwrap()), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn urls_to_private_networks_are_rejected() {
+        let rejected = [
+            "http://127.0.0.1:8080/hook",
+            "http://2130706433/hook",
+            "http://0x7f.1/hook",
+            "http://[::ffff:7f00:1]/hook",
+            "http://100.100.100.200/latest/meta-data",
+            "http://LOCALHOST./hook",
+            "http://metadata.google.internal/computeMetadata",
+            "ftp://example.com/hook",
+            "/relative",
+        ];
+        for url in rejected {
+            assert!(check_url(url, false).is_err(), "{} was accepted", url);
+        }
+
+        assert!(check_url("https://hooks.example.com/vek", false).is_ok());
+        assert!(check_url("http://127.0.0.1:8080/hook", true).is_ok());
+    }
+
+    #[tokio::test]
+    async fn resolver_drops_private_addresses() {
+        let result = PublicResolver.resolve(Name::from_str("localhost").unwrap()).await;
+        assert!(result.is_err());
+    }
+
+    /// Приёмник проверяет подпись так же, как должен делать настоящий.
+    fn receiver_routes(cfg: &mut web::ServiceConfig) {
+        cfg.route(
+            "/hook",
+            web::post().to(|req: HttpRequest, body: String| async move {
+                let header = |name: &str| req.headers().get(name).and_then(|value| value.to_str().ok()).unwrap_or_default();
+                let timestamp: i64 = header(TIMESTAMP_HEADER).parse().unwrap_or_default();
+                if header(SIGNATURE_HEADER) == sign(SECRET, timestamp, &body) && header(EVENT_HEADER) == "ping" {
+                    HttpResponse::NoContent().finish()
+                } else {
+                    HttpResponse::Unauthorized().finish()
+                }
+            }),
+        );
+    }
+
+    #[actix_web::test]
+    async fn delivers_to_a_local_receiver_only_when_allowed() {
+        let url = format!("{}/hook", stub::spawn(receiver_routes));
+        let payload = r#"{"event":"ping"}"#;
+
+        std::env::set_var("VEK_WEBHOOK_ALLOW_PRIVATE", "1");
+        let allowed = WebhookClient::from_env();
+        assert_eq!(allowed.post(&url, "ping", "delivery-1", SECRET, payload).await, Ok(204));
+        assert_eq!(
+            allowed.post(&url, "ping", "delivery-2", "another-secret", payload).await.map_err(|(status, _)| status),
+            Err(Some(401))
+        );
+
+        let denied = WebhookClient::new(false);
+        let (status, message) = denied.post(&url, "ping", "delivery-3", SECRET, payload).await.unwrap_err();
+        assert_eq!(status, None);
+        assert!(message.contains("private"), "{}", message);
+
+        let by_name = url.replace("127.0.0.1", "localhost");
+        assert!(denied.post(&by_name, "ping", "delivery-4", SECRET, payload).await.is_err());
+    }
+}
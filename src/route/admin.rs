@@ -0,0 +1,151 @@
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::rbac::RequirePermission;
+use crate::model::dto::rbac::Permission;
+use crate::model::dto::torrent::{HideReleaseRequest, ReleaseQuery, ReportReviewQuery};
+use crate::model::error::ErrorResponse;
+use crate::prisma::PrismaClient;
+use crate::service::rbac::RbacService;
+use crate::service::reports::ReportService;
+use crate::service::webhook::WebhookService;
+use actix_web::{delete, get, put, web, HttpResponse, Responder};
+use log::info;
+use std::sync::Arc;
+
+#[doc = "Mounted under `/admin` behind `AuthGuard` in `server::get_config`"]
+pub fn admin_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg
+    .service(get_roles)
+    .service(get_user_roles)
+    .service(grant_role)
+    .service(revoke_role)
+    .service(get_all_webhooks)
+    .service(get_webhook_deliveries)
+    .service(delete_any_webhook)
+    .service(get_release_reports)
+    .service(get_hidden_releases)
+    .service(hide_release)
+    .service(unhide_release);
+}
+
+#[get("/roles", wrap = "RequirePermission(Permission::ManageRoles)")]
+async fn get_roles(client: web::Data<PrismaClient>) -> impl Responder {
+  match RbacService::list_roles(&client).await {
+    Ok(roles) => HttpResponse::Ok().json(roles),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/users/{id}/roles", wrap = "RequirePermission(Permission::ManageRoles)")]
+async fn get_user_roles(
+  path: web::Path<String>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match RbacService::user_roles(&client, &path.into_inner()).await {
+    Ok(roles) => HttpResponse::Ok().json(roles),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[put("/users/{id}/roles/{role}", wrap = "RequirePermission(Permission::ManageRoles)")]
+async fn grant_role(
+  path: web::Path<(String, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (user_id, role) = path.into_inner();
+  match RbacService::grant_role(&client, &user_id, &role).await {
+    Ok(roles) => {
+      info!("Пользователю ID: {} выдана роль {}", user_id, role);
+      HttpResponse::Ok().json(roles)
+    }
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/users/{id}/roles/{role}", wrap = "RequirePermission(Permission::ManageRoles)")]
+async fn revoke_role(
+  path: web::Path<(String, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (user_id, role) = path.into_inner();
+  match RbacService::revoke_role(&client, &user_id, &role).await {
+    Ok(roles) => {
+      info!("У пользователя ID: {} снята роль {}", user_id, role);
+      HttpResponse::Ok().json(roles)
+    }
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/webhooks", wrap = "RequirePermission(Permission::ManageCatalog)")]
+async fn get_all_webhooks(webhook_service: web::Data<Arc<WebhookService>>) -> impl Responder {
+  match webhook_service.list_webhooks(None).await {
+    Ok(webhooks) => HttpResponse::Ok().json(webhooks),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/webhooks/{id}/deliveries", wrap = "RequirePermission(Permission::ManageCatalog)")]
+async fn get_webhook_deliveries(
+  path: web::Path<String>,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  match webhook_service.list_deliveries(None, &path.into_inner()).await {
+    Ok(deliveries) => HttpResponse::Ok().json(deliveries),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/webhooks/{id}", wrap = "RequirePermission(Permission::ManageCatalog)")]
+async fn delete_any_webhook(
+  path: web::Path<String>,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  match webhook_service.delete_webhook(None, &path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/releases/reports", wrap = "RequirePermission(Permission::ModerateContent)")]
+async fn get_release_reports(
+  query: web::Query<ReportReviewQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReportService::review_list(&client, query.into_inner()).await {
+    Ok(releases) => HttpResponse::Ok().json(releases),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/releases/hidden", wrap = "RequirePermission(Permission::ModerateContent)")]
+async fn get_hidden_releases(client: web::Data<PrismaClient>) -> impl Responder {
+  match ReportService::list_hidden(&client).await {
+    Ok(releases) => HttpResponse::Ok().json(releases),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[doc = "Hidden releases are filtered out of `/api/torrent` and survive index rebuilds"]
+#[put("/releases/hidden", wrap = "RequirePermission(Permission::ModerateContent)")]
+async fn hide_release(
+  user: AuthenticatedUser,
+  body: web::Json<HideReleaseRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReportService::hide(&client, &user.id, body.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/releases/hidden", wrap = "RequirePermission(Permission::ModerateContent)")]
+async fn unhide_release(
+  user: AuthenticatedUser,
+  query: web::Query<ReleaseQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReportService::unhide(&client, &user.id, &query.torrent).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
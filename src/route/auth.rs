@@ -1,11 +1,18 @@
 use crate::model::dto::auth::LoginRequest;
-use crate::model::error::ErrorResponse;
+use crate::model::error::{ErrorCode, ErrorResponse};
+use crate::modules::helpers::random_token;
 use crate::prisma::PrismaClient;
-use crate::service::auth::authenticate;
+use crate::service::auth::{
+  authenticate, link_identity, login_with_identity, redirect_uri, AuthFlow, AuthService, AUTH_FLOW_SESSION_KEY,
+};
 
 use actix_identity::Identity;
+use actix_session::Session;
+use actix_web::http::header::LOCATION;
 use actix_web::web::Json;
-use actix_web::{post, web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use std::collections::HashMap;
+use std::sync::Arc;
 
 #[allow(dead_code)]
 pub fn auth_controller_init(cfg: &mut web::ServiceConfig) {
@@ -13,16 +20,57 @@ pub fn auth_controller_init(cfg: &mut web::ServiceConfig) {
     web::scope("/auth")
       .service(login)
       .service(logout)
+      .service(provider_login)
+      .service(provider_callback)
   );
 }
 
+#[doc = "Store the flow in the session and redirect the browser to the provider"]
+pub(crate) fn begin_auth_flow(
+  session: &Session,
+  auth_service: &AuthService,
+  provider: &str,
+  link_user_id: Option<String>,
+) -> HttpResponse {
+  let provider = match auth_service.provider(provider) {
+    Some(provider) => provider,
+    None => return HttpResponse::NotFound().body("Unknown auth provider"),
+  };
+
+  let state = match random_token(24) {
+    Ok(state) => state,
+    Err(e) => {
+      log::error!("Не удалось сгенерировать state: {:?}", e);
+      return ErrorResponse::build(ErrorCode::INTERNAL001);
+    }
+  };
+
+  let url = match provider.authorize_url(&state, &redirect_uri(provider.name())) {
+    Some(url) => url,
+    None => return ErrorResponse::build(ErrorCode::BADREQUEST(format!("Provider {} is not configured", provider.name()))),
+  };
+
+  let flow = AuthFlow {
+    provider: provider.name().to_string(),
+    state,
+    link_user_id,
+  };
+  if let Err(e) = session.insert(AUTH_FLOW_SESSION_KEY, flow) {
+    log::error!("Не удалось сохранить состояние входа в сессии: {:?}", e);
+    return ErrorResponse::build(ErrorCode::INTERNAL001);
+  }
+
+  HttpResponse::Found().insert_header((LOCATION, url)).finish()
+}
+
 #[post("")]
 async fn login(
   body: Json<LoginRequest>,
   req: HttpRequest,
   data: web::Data<PrismaClient>,
+  auth_service: web::Data<Arc<AuthService>>,
 ) -> impl Responder {
-  let auth_result = authenticate(body.into_inner(), data).await;
+  let auth_result = authenticate(body.into_inner(), &data, &auth_service).await;
   match auth_result {
     Ok(user_data) => {
       Identity::login(&req.extensions_mut(), user_data.id.clone()).unwrap();
@@ -37,3 +85,167 @@ async fn logout(ident: Identity) -> impl Responder {
   ident.logout();
   HttpResponse::Ok().finish()
 }
+
+#[get("/{provider}")]
+async fn provider_login(
+  path: web::Path<String>,
+  session: Session,
+  auth_service: web::Data<Arc<AuthService>>,
+) -> impl Responder {
+  begin_auth_flow(&session, &auth_service, &path.into_inner(), None)
+}
+
+#[doc = "Take the pending flow out of the session if it was started for this provider with the same state"]
+fn take_auth_flow(session: &Session, provider: &str, params: &HashMap<String, String>) -> Option<AuthFlow> {
+  match session.remove_as::<AuthFlow>(AUTH_FLOW_SESSION_KEY) {
+    Some(Ok(flow)) if flow.provider == provider && params.get("state") == Some(&flow.state) => Some(flow),
+    _ => None,
+  }
+}
+
+#[get("/{provider}/callback")]
+async fn provider_callback(
+  path: web::Path<String>,
+  params: web::Query<HashMap<String, String>>,
+  req: HttpRequest,
+  session: Session,
+  data: web::Data<PrismaClient>,
+  auth_service: web::Data<Arc<AuthService>>,
+) -> impl Responder {
+  let provider_name = path.into_inner();
+  let params = params.into_inner();
+
+  let flow = match take_auth_flow(&session, &provider_name, &params) {
+    Some(flow) => flow,
+    None => {
+      log::warn!("Колбэк {} без действующего state", provider_name);
+      return ErrorResponse::build(ErrorCode::BADREQUEST("Invalid or expired state".to_string()));
+    }
+  };
+  let provider = match auth_service.provider(&provider_name) {
+    Some(provider) => provider,
+    None => return HttpResponse::NotFound().body("Unknown auth provider"),
+  };
+
+  let identity = match provider.authenticate(&params, &redirect_uri(provider.name())).await {
+    Ok(identity) => identity,
+    Err(e) => return ErrorResponse::build(e),
+  };
+
+  match flow.link_user_id {
+    Some(user_id) => match link_identity(&data, &user_id, provider.name(), identity).await {
+      Ok(identities) => HttpResponse::Ok().json(identities),
+      Err(e) => ErrorResponse::build(e),
+    },
+    None => match login_with_identity(&data, provider.name(), identity).await {
+      Ok(user_data) => {
+        Identity::login(&req.extensions_mut(), user_data.id.clone()).unwrap();
+        HttpResponse::Ok().json(user_data)
+      }
+      Err(e) => ErrorResponse::build(e),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::modules::auth::google::GoogleOAuth;
+  use crate::modules::stub;
+  use actix_http::Request;
+  use actix_session::storage::CookieSessionStore;
+  use actix_session::SessionMiddleware;
+  use actix_web::cookie::{Cookie, Key};
+  use actix_web::dev::{Service, ServiceResponse};
+  use actix_web::{test, App};
+
+  #[doc = "Callback that stops right after the state check, so no database is needed"]
+  async fn check_state(
+    path: web::Path<String>,
+    params: web::Query<HashMap<String, String>>,
+    session: Session,
+  ) -> HttpResponse {
+    match take_auth_flow(&session, &path.into_inner(), &params.into_inner()) {
+      Some(_) => HttpResponse::Ok().finish(),
+      None => ErrorResponse::build(ErrorCode::BADREQUEST("Invalid or expired state".to_string())),
+    }
+  }
+
+  async fn start(
+    path: web::Path<String>,
+    session: Session,
+    auth_service: web::Data<Arc<AuthService>>,
+  ) -> HttpResponse {
+    begin_auth_flow(&session, &auth_service, &path.into_inner(), None)
+  }
+
+  async fn auth_app() -> impl Service<Request, Response = ServiceResponse, Error = actix_web::Error> {
+    std::env::set_var("VEK_GOOGLE_CLIENT_ID", "vek-test.apps.googleusercontent.com");
+    std::env::set_var("VEK_GOOGLE_CLIENT_SECRET", "vek-test-secret");
+    std::env::set_var("VEK_GOOGLE_TOKEN_URL", format!("{}/oauth/token", stub::auth()));
+    let auth_service = Arc::new(AuthService::new(vec![Box::new(GoogleOAuth::new())]));
+
+    test::init_service(
+      App::new()
+        .wrap(SessionMiddleware::new(CookieSessionStore::default(), Key::from(&[7; 64])))
+        .app_data(web::Data::new(auth_service))
+        .route("/api/auth/{provider}", web::get().to(start))
+        .route("/api/auth/{provider}/callback", web::get().to(check_state)),
+    )
+    .await
+  }
+
+  #[doc = "Start a Google login and return the session cookie with the issued state"]
+  async fn begin(
+    app: &impl Service<Request, Response = ServiceResponse, Error = actix_web::Error>,
+  ) -> (Cookie<'static>, String) {
+    let response = test::call_service(app, test::TestRequest::get().uri("/api/auth/google").to_request()).await;
+    assert_eq!(response.status().as_u16(), 302);
+
+    let location = response.headers().get(LOCATION).unwrap().to_str().unwrap().to_string();
+    let state = reqwest::Url::parse(&location)
+      .unwrap()
+      .query_pairs()
+      .find(|(name, _)| name == "state")
+      .map(|(_, state)| state.into_owned())
+      .unwrap();
+    let cookie = response.response().cookies().next().unwrap().into_owned();
+    (cookie, state)
+  }
+
+  async fn callback(
+    app: &impl Service<Request, Response = ServiceResponse, Error = actix_web::Error>,
+    uri: &str,
+    cookie: Option<Cookie<'static>>,
+  ) -> u16 {
+    let mut request = test::TestRequest::get().uri(uri);
+    if let Some(cookie) = cookie {
+      request = request.cookie(cookie);
+    }
+    test::call_service(app, request.to_request()).await.status().as_u16()
+  }
+
+  #[actix_web::test]
+  async fn callback_accepts_the_issued_state() {
+    let app = auth_app().await;
+    let (cookie, state) = begin(&app).await;
+
+    let uri = format!("/api/auth/google/callback?code={}&state={}", stub::OAUTH_CODE, state);
+    assert_eq!(callback(&app, &uri, Some(cookie.clone())).await, 200);
+  }
+
+  #[actix_web::test]
+  async fn callback_rejects_a_mismatched_state() {
+    let app = auth_app().await;
+    let (cookie, state) = begin(&app).await;
+
+    let forged = format!("/api/auth/google/callback?code={}&state=forged-{}", stub::OAUTH_CODE, state);
+    assert_eq!(callback(&app, &forged, Some(cookie.clone())).await, 400);
+
+    let other_provider = format!("/api/auth/github/callback?code={}&state={}", stub::OAUTH_CODE, state);
+    assert_eq!(callback(&app, &other_provider, Some(cookie)).await, 400);
+
+    let without_session = format!("/api/auth/google/callback?code={}&state={}", stub::OAUTH_CODE, state);
+    assert_eq!(callback(&app, &without_session, None).await, 400);
+  }
+}
@@ -0,0 +1,94 @@
+use actix_web::{web, HttpResponse, Responder};
+use crate::model::dto::catalog::*;
+use crate::model::error::{ErrorCode, ErrorResponse};
+use crate::modules::cursor::PageCursor;
+use crate::modules::images::attach_proxy_links;
+use crate::modules::sanitizer::sanitize_response;
+use crate::service::games::GamesService;
+use crate::middleware::games::GamesMiddleware;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+const CATALOG_RESOURCES: [&str; 6] = ["genres", "platforms", "tags", "developers", "publishers", "stores"];
+
+#[derive(Clone, Copy)]
+struct CatalogResource(&'static str);
+
+#[allow(dead_code)]
+pub fn catalog_controller_init(cfg: &mut web::ServiceConfig) {
+    for resource in CATALOG_RESOURCES {
+        cfg.service(
+            web::scope(&format!("/{}", resource))
+                .app_data(web::Data::new(CatalogResource(resource)))
+                .route("", web::get().to(get_catalog_list))
+                .route("/{id}", web::get().to(get_catalog_details))
+        );
+    }
+}
+
+async fn catalog_response<T: DeserializeOwned + Serialize>(
+    middleware: &GamesMiddleware,
+    result: Result<Value, reqwest::Error>,
+    route: &str,
+) -> HttpResponse {
+    match result {
+        Ok(mut result) => {
+            sanitize_response(&mut result, route, &middleware.current_api_key().await);
+            attach_proxy_links(&mut result);
+            match serde_json::from_value::<T>(result) {
+                Ok(data) => HttpResponse::Ok().json(data),
+                Err(e) => {
+                    log::error!("Не удалось разобрать ответ RAWG для {}: {:?}", route, e);
+                    HttpResponse::InternalServerError().finish()
+                }
+            }
+        }
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+async fn get_catalog_list(
+    resource: web::Data<CatalogResource>,
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    data: web::Query<CatalogListRequest>
+) -> impl Responder {
+    let resource = resource.0;
+    let request = data.into_inner();
+    let params = match &request.next {
+        Some(next) => match PageCursor::decode_for(next, &format!("/api/{}", resource), resource) {
+            Ok(cursor) => cursor.params,
+            Err(e) => return ErrorResponse::build(ErrorCode::BADREQUEST(e.to_string())),
+        },
+        None => match GamesService::page_params(request.page, request.page_size) {
+            Ok(params) => params,
+            Err(e) => return ErrorResponse::build(ErrorCode::BADREQUEST(e)),
+        },
+    };
+
+    let result = middleware
+        .execute_with_retry(move |api_key| {
+            let params = params.clone();
+            async move { GamesService::get_catalog_list(api_key, resource, params).await }
+        })
+        .await;
+
+    catalog_response::<PaginatedResponse<CatalogEntry>>(&middleware, result, &format!("/api/{}", resource)).await
+}
+
+async fn get_catalog_details(
+    resource: web::Data<CatalogResource>,
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let resource = resource.0;
+    let request_id = path.into_inner();
+    let result = middleware
+        .execute_with_retry(move |api_key| {
+            async move { GamesService::get_catalog_details(api_key, resource, request_id).await }
+        })
+        .await;
+
+    catalog_response::<CatalogDetails>(&middleware, result, &format!("/api/{}/{}", resource, request_id)).await
+}
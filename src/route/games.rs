@@ -1,7 +1,25 @@
 use actix_web::{get, post, web, HttpResponse, Responder};
 use crate::model::dto::games::*;
-use crate::service::games::GamesService;
+use crate::model::dto::requirements::{CompatibilityResponse, HardwareProfile};
+use crate::model::error::{ErrorCode, ErrorResponse};
+use crate::modules::cursor::PageCursor;
+use crate::model::dto::torrent::TorrentRelease;
+use crate::modules::helpers::format_name;
+use crate::modules::images::attach_proxy_links;
+use crate::modules::requirements::{attach_pc_requirements, check_compatibility, overall_verdict, pc_requirements};
+use crate::modules::sanitizer::sanitize_response;
+use crate::prisma::PrismaClient;
+use crate::route::linux::linux_controller_init;
+use crate::route::reviews::reviews_controller_init;
+use crate::service::games::{GamesService, DEFAULT_PAGE_SIZE};
+use crate::service::linux::LinuxReportService;
+use crate::service::metadata::{MetadataError, MetadataService};
+use crate::service::mirror::MirrorService;
+use crate::service::reports::ReportService;
+use crate::service::reviews::ReviewService;
+use crate::service::torrent::TorrentService;
 use crate::middleware::games::GamesMiddleware;
+use serde_json::Value;
 use std::sync::Arc;
 
 #[allow(dead_code)]
@@ -10,82 +28,445 @@ pub fn games_controller_init(cfg: &mut web::ServiceConfig) {
         web::scope("/games")
             .service(get_game_list)
             .service(search_game)
+            .service(search_game_page)
             .service(get_game_details)
+            .service(get_game_full)
+            .service(check_game_compatibility)
             .service(get_game_screenshots)
             .service(get_game_movies)
+            .service(get_game_additions)
+            .service(get_game_series)
+            .service(get_parent_games)
+            .service(get_game_achievements)
+            .service(get_game_stores)
+            .configure(reviews_controller_init)
+            .configure(linux_controller_init)
     );
 }
 
+fn decode_cursor(next: &Option<String>, endpoint: &str, path: &str) -> Result<Option<PageCursor>, ErrorCode> {
+    match next {
+        Some(next) => PageCursor::decode_for(next, endpoint, path)
+            .map(Some)
+            .map_err(|e| ErrorCode::BADREQUEST(e.to_string())),
+        None => Ok(None),
+    }
+}
+
+async fn games_response(
+    middleware: &GamesMiddleware,
+    result: Result<Value, reqwest::Error>,
+    route: &str,
+) -> HttpResponse {
+    match result {
+        Ok(mut result) => {
+            sanitize_response(&mut result, route, &middleware.current_api_key().await);
+            attach_proxy_links(&mut result);
+            HttpResponse::Ok().json(result)
+        }
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+fn mirror_games(mirror: &Arc<MirrorService>, result: &Result<Value, reqwest::Error>) {
+    if let Ok(json) = result {
+        let mirror = mirror.clone();
+        let json = json.clone();
+        tokio::spawn(async move { mirror.store_games(&json).await });
+    }
+}
+
+/// Сколько страниц RAWG просматривается за один запрос с `has_torrent=true`.
+const HAS_TORRENT_MAX_PAGES: usize = 5;
+
+/// Список игр, у которых есть раздача в индексе. Отфильтрованная страница RAWG может
+/// оказаться короткой, поэтому следующие страницы дочитываются, пока не наберётся
+/// `page_size` игр или не кончится лимит. `next` указывает на первую непросмотренную
+/// страницу, так что ответ бывает короче `page_size` и даже пустым при ненулевом `next`.
+async fn list_games_with_torrent(
+    middleware: &GamesMiddleware,
+    torrent_service: &TorrentService,
+    mirror: &Arc<MirrorService>,
+    mut params: Vec<(String, String)>,
+) -> Result<Value, reqwest::Error> {
+    let page_size = params
+        .iter()
+        .find(|(name, _)| name == "page_size")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_PAGE_SIZE);
+
+    let mut combined: Option<Value> = None;
+    for _ in 0..HAS_TORRENT_MAX_PAGES {
+        let request = params.clone();
+        let result = middleware
+            .execute_with_retry(move |api_key| {
+                let params = request.clone();
+                async move { GamesService::get_game_list(api_key, params).await }
+            })
+            .await;
+        mirror_games(mirror, &result);
+        let mut page = result?;
+
+        let games: Vec<Value> = page["results"].as_array().cloned().unwrap_or_default();
+        let names: Vec<String> = games
+            .iter()
+            .filter_map(|game| game["name"].as_str().map(str::to_string))
+            .collect();
+        let available = torrent_service.games_with_torrent(&names).await.unwrap_or_else(|e| {
+            log::warn!("Не удалось проверить раздачи для списка игр: {}", e);
+            Default::default()
+        });
+        let games: Vec<Value> = games
+            .into_iter()
+            .filter(|game| game["name"].as_str().map(|name| available.contains(name)).unwrap_or(false))
+            .collect();
+
+        let next = page["next"]
+            .as_str()
+            .and_then(|next| PageCursor::from_rawg_url(next, "/api/games"))
+            .map(|cursor| cursor.params);
+        let combined = combined.get_or_insert_with(|| {
+            page["results"] = Value::Array(vec![]);
+            page.clone()
+        });
+        combined["next"] = page["next"].take();
+        if let Some(results) = combined["results"].as_array_mut() {
+            results.extend(games);
+        }
+
+        let filled = combined["results"].as_array().map(Vec::len).unwrap_or(0) >= page_size;
+        match next {
+            Some(next) if !filled => params = next,
+            _ => break,
+        }
+    }
+
+    Ok(combined.unwrap_or_default())
+}
+
 #[get("")]
 async fn get_game_list(
     middleware: web::Data<Arc<GamesMiddleware>>,
+    torrent_service: web::Data<Arc<TorrentService>>,
+    mirror: web::Data<Arc<MirrorService>>,
     data: web::Query<GameListRequest>
 ) -> impl Responder {
     let request = data.into_inner();
-    match middleware
+    let params = match decode_cursor(&request.next, "/api/games", &GamesService::game_list_path()) {
+        Ok(Some(cursor)) => cursor.params,
+        Ok(None) => match GamesService::game_list_params(&request) {
+            Ok(params) => params,
+            Err(e) => return ErrorResponse::build(ErrorCode::BADREQUEST(e)),
+        },
+        Err(code) => return ErrorResponse::build(code),
+    };
+
+    if request.has_torrent.unwrap_or(false) {
+        let result = list_games_with_torrent(&middleware, &torrent_service, &mirror, params).await;
+        return games_response(&middleware, result, "/api/games?has_torrent=true").await;
+    }
+
+    let result = middleware
+        .execute_with_retry(move |api_key| {
+            let params = params.clone();
+            async move { GamesService::get_game_list(api_key, params).await }
+        })
+        .await;
+    mirror_games(&mirror, &result);
+
+    games_response(&middleware, result, "/api/games").await
+}
+
+async fn search_response(
+    middleware: &GamesMiddleware,
+    mirror: &Arc<MirrorService>,
+    request: GameSearchRequest,
+    cursor: Option<PageCursor>,
+) -> HttpResponse {
+    let result = middleware
         .execute_with_retry(move |api_key| {
             let req = request.clone();
-            async move { GamesService::get_game_list(api_key, req).await }
+            let cursor = cursor.clone();
+            async move { GamesService::search_game(api_key, req, cursor).await }
         })
-        .await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(_) => HttpResponse::InternalServerError().finish(),
-    }
+        .await;
+    mirror_games(mirror, &result);
+
+    games_response(middleware, result, "/api/games/search").await
 }
 
 #[post("/search")]
 async fn search_game(
     middleware: web::Data<Arc<GamesMiddleware>>,
+    mirror: web::Data<Arc<MirrorService>>,
     data: web::Json<GameSearchRequest>
 ) -> impl Responder {
     let request = data.into_inner();
-    match middleware
-        .execute_with_retry(move |api_key| {
-            let req = request.clone();
-            async move { GamesService::search_game(api_key, req).await }
-        })
-        .await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(_) => HttpResponse::InternalServerError().finish(),
+    let cursor = match decode_cursor(&request.next, "/api/games/search", &GamesService::game_list_path()) {
+        Ok(cursor) => cursor,
+        Err(code) => return ErrorResponse::build(code),
+    };
+    // Курсор продолжает только тот поиск, для которого был выдан.
+    if let Some(cursor) = &cursor {
+        if cursor.param("search") != Some(request.query.as_str()) {
+            return ErrorResponse::build(ErrorCode::BADREQUEST("Cursor does not belong to this search query".to_string()));
+        }
+    }
+
+    search_response(&middleware, &mirror, request, cursor).await
+}
+
+#[doc = "Follows the `next`/`previous` links of search results, the query is taken from the cursor"]
+#[get("/search")]
+async fn search_game_page(
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    mirror: web::Data<Arc<MirrorService>>,
+    data: web::Query<GameSearchPageRequest>
+) -> impl Responder {
+    let cursor = match PageCursor::decode_for(&data.next, "/api/games/search", &GamesService::game_list_path()) {
+        Ok(cursor) => cursor,
+        Err(e) => return ErrorResponse::build(ErrorCode::BADREQUEST(e.to_string())),
+    };
+    let request = GameSearchRequest {
+        query: cursor.param("search").unwrap_or_default().to_string(),
+        next: Some(data.into_inner().next),
+    };
+
+    search_response(&middleware, &mirror, request, Some(cursor)).await
+}
+
+/// Данные игры от провайдеров метаданных, а если RAWG недоступен — из локального каталога.
+/// Ответ «игры нет» и прочие отказы RAWG в каталог не уводят.
+async fn load_game_details(
+    metadata_service: &MetadataService,
+    mirror: &Arc<MirrorService>,
+    id: i32,
+) -> Result<Value, MetadataError> {
+    match metadata_service.get_game_details(id).await {
+        Ok(result) => {
+            let mirror = mirror.clone();
+            let details = result.clone();
+            tokio::spawn(async move {
+                if let Err(e) = mirror.store_game(&details, true).await {
+                    log::warn!("Не удалось сохранить игру {} в локальный каталог: {}", id, e);
+                }
+            });
+            Ok(result)
+        }
+        Err(MetadataError::Unavailable(e)) => {
+            log::warn!("RAWG недоступен для игры {}, используется локальный каталог: {}", id, e);
+            match mirror.get_game_details(id).await {
+                Ok(Some(details)) => Ok(details),
+                Ok(None) => Err(MetadataError::Unavailable(e)),
+                Err(db) => {
+                    log::error!("Не удалось прочитать игру {} из локального каталога: {}", id, db);
+                    Err(MetadataError::Unavailable(e))
+                }
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn game_details_error(error: &MetadataError) -> HttpResponse {
+    ErrorResponse::build(match error {
+        MetadataError::NotFound => ErrorCode::GAME001,
+        MetadataError::Unavailable(_) => ErrorCode::GAME002,
+        MetadataError::Rejected(_) => ErrorCode::GAME003,
+    })
+}
+
+/// Оценка пользователей VEK рядом с `rating` от RAWG.
+async fn attach_community_score(client: &PrismaClient, id: i32, details: &mut Value) {
+    match ReviewService::community_score(client, id).await {
+        Ok(score) => details["community_score"] = serde_json::json!(score),
+        Err(e) => log::warn!("Не удалось посчитать оценку сообщества для игры {}: {:?}", id, e),
+    }
+}
+
+/// Сводка отчётов о запуске в Linux через Proton/Wine.
+async fn attach_linux_summary(client: &PrismaClient, id: i32, details: &mut Value) {
+    match LinuxReportService::summary(client, id).await {
+        Ok(summary) => details["linux_compatibility"] = serde_json::json!(summary),
+        Err(e) => log::warn!("Не удалось посчитать сводку Linux-отчётов для игры {}: {:?}", id, e),
     }
 }
 
 #[get("/{id}")]
 async fn get_game_details(
     middleware: web::Data<Arc<GamesMiddleware>>,
+    metadata_service: web::Data<Arc<MetadataService>>,
+    mirror: web::Data<Arc<MirrorService>>,
+    client: web::Data<PrismaClient>,
     path: web::Path<i32>,
 ) -> impl Responder {
     let request_id = path.into_inner();
-    match middleware
-        .execute_with_retry(move |api_key| {
-            async move { GamesService::get_game_details(api_key, GameDetailsRequest { id: request_id }).await }
-        })
-        .await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(_) => HttpResponse::InternalServerError().finish(),
+    match load_game_details(&metadata_service, &mirror, request_id).await {
+        Ok(mut result) => {
+            sanitize_response(&mut result, &format!("/api/games/{}", request_id), &middleware.current_api_key().await);
+            attach_proxy_links(&mut result);
+            attach_pc_requirements(&mut result);
+            attach_community_score(&client, request_id, &mut result).await;
+            attach_linux_summary(&client, request_id, &mut result).await;
+            HttpResponse::Ok().json(result)
+        }
+        Err(e) => game_details_error(&e),
     }
 }
 
+fn game_section(result: Result<Value, String>, route: &str, api_key: &str) -> GameSection<Value> {
+    match result {
+        Ok(mut data) => {
+            sanitize_response(&mut data, route, api_key);
+            attach_proxy_links(&mut data);
+            GameSection { data: Some(data), error: None }
+        }
+        Err(e) => GameSection { data: None, error: Some(e) },
+    }
+}
+
+/// Ошибка RAWG для раздела `/full`. Текст ошибки reqwest содержит ссылку с ключом,
+/// поэтому клиенту уходит только фиксированное сообщение.
+fn media_error(section: &str, id: i32, e: reqwest::Error) -> String {
+    log::warn!("Не удалось получить {} игры {}: {}", section, id, e.without_url());
+    format!("Game {} are unavailable", section)
+}
+
+#[get("/{id}/full")]
+async fn get_game_full(
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    metadata_service: web::Data<Arc<MetadataService>>,
+    mirror: web::Data<Arc<MirrorService>>,
+    torrent_service: web::Data<Arc<TorrentService>>,
+    client: web::Data<PrismaClient>,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let request_id = path.into_inner();
+
+    let details = async {
+        load_game_details(&metadata_service, &mirror, request_id)
+            .await
+            .map_err(|e| e.public_message().to_string())
+    };
+    let screenshots = middleware.execute_with_retry(move |api_key| async move {
+        let req = GameScreenshotsRequest { page: None, next: None };
+        GamesService::get_game_screenshots(api_key, request_id, req, None).await
+    });
+    let movies = middleware.execute_with_retry(move |api_key| async move {
+        let req = GameMoviesRequest { page: None, next: None };
+        GamesService::get_game_movies(api_key, request_id, req, None).await
+    });
+
+    let (mut details, screenshots, movies) = tokio::join!(details, screenshots, movies);
+    if let Ok(details) = details.as_mut() {
+        attach_pc_requirements(details);
+        attach_community_score(&client, request_id, details).await;
+        attach_linux_summary(&client, request_id, details).await;
+    }
+
+    let torrents = match details.as_ref().ok().and_then(|details| details["name"].as_str()) {
+        Some(name) => match torrent_service.search_torrent(&format_name(name.to_string())).await {
+            Ok(results) => {
+                let links: Vec<String> = results.iter().map(|(_, torrent)| torrent.clone()).collect();
+                let mut stats = ReportService::stats_for(&client, &links).await.unwrap_or_default();
+                GameSection {
+                    data: Some(
+                        results
+                            .into_iter()
+                            .map(|(repacker, torrent)| TorrentRelease {
+                                reports: stats.remove(&torrent).unwrap_or_default(),
+                                repacker,
+                                torrent,
+                            })
+                            .collect(),
+                    ),
+                    error: None,
+                }
+            }
+            Err(e) => GameSection { data: None, error: Some(e) },
+        },
+        None => GameSection { data: None, error: Some("Game name is unknown".to_string()) },
+    };
+
+    let api_key = middleware.current_api_key().await;
+    HttpResponse::Ok().json(GameFullResponse {
+        details: game_section(details, &format!("/api/games/{}", request_id), &api_key),
+        screenshots: game_section(
+            screenshots.map_err(|e| media_error("screenshots", request_id, e)),
+            &format!("/api/games/{}/screenshots", request_id),
+            &api_key,
+        ),
+        movies: game_section(
+            movies.map_err(|e| media_error("movies", request_id, e)),
+            &format!("/api/games/{}/movies", request_id),
+            &api_key,
+        ),
+        torrents,
+    })
+}
+
+#[post("/{id}/compatibility")]
+async fn check_game_compatibility(
+    metadata_service: web::Data<Arc<MetadataService>>,
+    mirror: web::Data<Arc<MirrorService>>,
+    path: web::Path<i32>,
+    data: web::Json<HardwareProfile>,
+) -> impl Responder {
+    let request_id = path.into_inner();
+    let details = match load_game_details(&metadata_service, &mirror, request_id).await {
+        Ok(details) => details,
+        Err(e) => return game_details_error(&e),
+    };
+
+    let requirements = match pc_requirements(&details) {
+        Some(requirements) => requirements,
+        None => return HttpResponse::NotFound().body("Game has no PC requirements"),
+    };
+
+    let components = check_compatibility(&requirements, &data.into_inner());
+    HttpResponse::Ok().json(CompatibilityResponse {
+        game_id: request_id,
+        overall: overall_verdict(&components),
+        components,
+    })
+}
+
 #[get("/{id}/screenshots")]
 async fn get_game_screenshots(
     middleware: web::Data<Arc<GamesMiddleware>>,
     path: web::Path<i32>,
+    mirror: web::Data<Arc<MirrorService>>,
     data: web::Query<GameScreenshotsRequest>
 ) -> impl Responder {
     let request_id = path.into_inner();
     let request = data.into_inner();
-    match middleware
+    let cursor = match decode_cursor(
+        &request.next,
+        &format!("/api/games/{}/screenshots", request_id),
+        &GamesService::game_screenshots_path(request_id),
+    ) {
+        Ok(cursor) => cursor,
+        Err(code) => return ErrorResponse::build(code),
+    };
+
+    let result = middleware
         .execute_with_retry(move |api_key| {
             let req = GameScreenshotsRequest {
                 page: request.page,
                 next: request.next.clone(),
             };
-            async move { GamesService::get_game_screenshots(api_key, request_id, req).await }
+            let cursor = cursor.clone();
+            async move { GamesService::get_game_screenshots(api_key, request_id, req, cursor).await }
         })
-        .await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(_) => HttpResponse::InternalServerError().finish(),
+        .await;
+
+    if let Ok(json) = &result {
+        let mirror = mirror.get_ref().clone();
+        let json = json.clone();
+        tokio::spawn(async move { mirror.store_screenshots(request_id, &json).await });
     }
+
+    games_response(&middleware, result, &format!("/api/games/{}/screenshots", request_id)).await
 }
 
 #[get("/{id}/movies")]
@@ -96,16 +477,222 @@ async fn get_game_movies(
 ) -> impl Responder {
     let request_id = path.into_inner();
     let request = data.into_inner();
-    match middleware
+    let cursor = match decode_cursor(
+        &request.next,
+        &format!("/api/games/{}/movies", request_id),
+        &GamesService::game_movies_path(request_id),
+    ) {
+        Ok(cursor) => cursor,
+        Err(code) => return ErrorResponse::build(code),
+    };
+
+    let result = middleware
         .execute_with_retry(move |api_key| {
             let req = GameMoviesRequest {
                 page: request.page,
                 next: request.next.clone(),
             };
-            async move { GamesService::get_game_movies(api_key, request_id, req).await }
+            let cursor = cursor.clone();
+            async move { GamesService::get_game_movies(api_key, request_id, req, cursor).await }
         })
-        .await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(_) => HttpResponse::InternalServerError().finish(),
+        .await;
+
+    games_response(&middleware, result, &format!("/api/games/{}/movies", request_id)).await
+}
+
+async fn game_relation_response(
+    middleware: &GamesMiddleware,
+    id: i32,
+    relation: &'static str,
+    request: GameRelationRequest,
+) -> HttpResponse {
+    let params = match decode_cursor(
+        &request.next,
+        &format!("/api/games/{}/{}", id, relation),
+        &GamesService::game_relation_path(id, relation),
+    ) {
+        Ok(Some(cursor)) => cursor.params,
+        Ok(None) => match GamesService::page_params(request.page, request.page_size) {
+            Ok(params) => params,
+            Err(e) => return ErrorResponse::build(ErrorCode::BADREQUEST(e)),
+        },
+        Err(code) => return ErrorResponse::build(code),
+    };
+
+    let result = middleware
+        .execute_with_retry(move |api_key| {
+            let params = params.clone();
+            async move { GamesService::get_game_relation(api_key, id, relation, params).await }
+        })
+        .await;
+
+    games_response(middleware, result, &format!("/api/games/{}/{}", id, relation)).await
+}
+
+#[get("/{id}/additions")]
+async fn get_game_additions(
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    path: web::Path<i32>,
+    data: web::Query<GameRelationRequest>
+) -> impl Responder {
+    game_relation_response(&middleware, path.into_inner(), "additions", data.into_inner()).await
+}
+
+#[get("/{id}/game-series")]
+async fn get_game_series(
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    path: web::Path<i32>,
+    data: web::Query<GameRelationRequest>
+) -> impl Responder {
+    game_relation_response(&middleware, path.into_inner(), "game-series", data.into_inner()).await
+}
+
+#[get("/{id}/parent-games")]
+async fn get_parent_games(
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    path: web::Path<i32>,
+    data: web::Query<GameRelationRequest>
+) -> impl Responder {
+    game_relation_response(&middleware, path.into_inner(), "parent-games", data.into_inner()).await
+}
+
+#[get("/{id}/achievements")]
+async fn get_game_achievements(
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    path: web::Path<i32>,
+    data: web::Query<GameRelationRequest>
+) -> impl Responder {
+    game_relation_response(&middleware, path.into_inner(), "achievements", data.into_inner()).await
+}
+
+#[get("/{id}/stores")]
+async fn get_game_stores(
+    middleware: web::Data<Arc<GamesMiddleware>>,
+    path: web::Path<i32>,
+    data: web::Query<GameRelationRequest>
+) -> impl Responder {
+    game_relation_response(&middleware, path.into_inner(), "stores", data.into_inner()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::stub;
+    use actix_http::Request;
+    use actix_web::dev::{Service, ServiceResponse};
+    use actix_web::{test, App};
+
+    async fn games_app() -> impl Service<Request, Response = ServiceResponse, Error = actix_web::Error> {
+        stub::rawg();
+        let middleware = Arc::new(GamesMiddleware::with_api_key(stub::RAWG_API_KEY));
+        test::init_service(
+            App::new().app_data(web::Data::new(middleware)).service(
+                web::scope("/api/games")
+                    .service(get_game_movies)
+                    .service(get_game_additions)
+                    .service(get_game_stores),
+            ),
+        )
+        .await
+    }
+
+    async fn get_body(
+        app: &impl Service<Request, Response = ServiceResponse, Error = actix_web::Error>,
+        uri: &str,
+    ) -> (u16, String) {
+        let response = test::call_service(app, test::TestRequest::get().uri(uri).to_request()).await;
+        let status = response.status().as_u16();
+        let body = test::read_body(response).await;
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    #[actix_web::test]
+    async fn paginated_responses_do_not_contain_the_api_key() {
+        let app = games_app().await;
+
+        let (status, body) = get_body(&app, "/api/games/3498/additions?page_size=2").await;
+        assert_eq!(status, 200);
+        assert!(!body.contains(stub::RAWG_API_KEY), "response contains the key: {}", body);
+
+        let json: Value = serde_json::from_str(&body).unwrap();
+        let next = json["next"].as_str().expect("next link is rewritten");
+        assert!(next.starts_with("/api/games/3498/additions?next="));
+
+        let (status, body) = get_body(&app, next).await;
+        assert_eq!(status, 200);
+        assert!(!body.contains(stub::RAWG_API_KEY), "response contains the key: {}", body);
+        let json: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["results"][0]["id"], 416837);
+        assert!(json["previous"].as_str().unwrap().starts_with("/api/games/3498/additions?next="));
+    }
+
+    #[actix_web::test]
+    async fn cursors_are_rejected_by_other_endpoints() {
+        let app = games_app().await;
+
+        let (_, body) = get_body(&app, "/api/games/3498/additions?page_size=2").await;
+        let json: Value = serde_json::from_str(&body).unwrap();
+        let cursor = json["next"].as_str().unwrap().split("next=").nth(1).unwrap().to_string();
+
+        let (status, _) = get_body(&app, &format!("/api/games/3498/stores?next={}", cursor)).await;
+        assert_eq!(status, 400);
+    }
+
+    #[actix_web::test]
+    async fn upstream_failures_do_not_contain_the_api_key() {
+        let app = games_app().await;
+
+        let (status, body) = get_body(&app, "/api/games/0/movies").await;
+        assert_eq!(status, 500);
+        assert!(!body.contains(stub::RAWG_API_KEY), "response contains the key: {}", body);
+    }
+
+    #[actix_web::test]
+    async fn full_page_media_errors_do_not_contain_the_api_key() {
+        stub::rawg();
+        let req = GameMoviesRequest { page: None, next: None };
+        let e = GamesService::get_game_movies(stub::RAWG_API_KEY.to_string(), 0, req, None)
+            .await
+            .expect_err("stub fails for game 0");
+        assert!(e.to_string().contains(stub::RAWG_API_KEY));
+
+        let section = game_section(Err(media_error("movies", 0, e)), "/api/games/0/movies", stub::RAWG_API_KEY);
+        assert_eq!(section.error.as_deref(), Some("Game movies are unavailable"));
+    }
+
+    #[actix_web::test]
+    async fn game_details_errors_use_the_error_envelope() {
+        let cases = [
+            (MetadataError::NotFound, 404, "GAME001"),
+            (MetadataError::Unavailable("RAWG request failed".to_string()), 503, "GAME002"),
+            (MetadataError::Rejected("RAWG responded with 401".to_string()), 502, "GAME003"),
+        ];
+        for (error, status, code) in cases {
+            let response = game_details_error(&error);
+            assert_eq!(response.status().as_u16(), status);
+            let body = actix_web::body::to_bytes(response.into_body()).await.unwrap();
+            let json: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["code"], code);
+        }
+    }
+
+    #[actix_web::test]
+    async fn raw_rawg_urls_are_not_accepted_as_cursor() {
+        let app = games_app().await;
+
+        let raw = format!("{}games/3498/movies?key={}&page=2", stub::rawg(), stub::RAWG_API_KEY);
+        let uri = format!("/api/games/3498/movies?next={}", urlencode(&raw));
+        let (status, body) = get_body(&app, &uri).await;
+        assert_eq!(status, 400);
+        assert!(!body.contains(stub::RAWG_API_KEY));
+    }
+
+    fn urlencode(value: &str) -> String {
+        reqwest::Url::parse_with_params("http://localhost", &[("v", value)])
+            .unwrap()
+            .query()
+            .unwrap()
+            .trim_start_matches("v=")
+            .to_string()
     }
 }
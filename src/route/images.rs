@@ -0,0 +1,56 @@
+use actix_web::http::header::{CacheControl, CacheDirective};
+use actix_web::{get, web, HttpResponse, Responder};
+use crate::model::dto::images::ImageRequest;
+use crate::model::error::{ErrorCode, ErrorResponse};
+use crate::service::images::{ImageError, ImageService, ImageVariant, OutputFormat};
+use std::sync::Arc;
+
+pub fn images_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg.service(
+    web::scope("/images")
+      .service(get_image),
+  );
+}
+
+#[get("/{hash}")]
+async fn get_image(
+  service: web::Data<Arc<ImageService>>,
+  path: web::Path<String>,
+  data: web::Query<ImageRequest>,
+) -> impl Responder {
+  let hash = path.into_inner();
+  let request = data.into_inner();
+
+  let format = match request.fmt.as_deref().map(OutputFormat::parse) {
+    Some(None) => return ErrorResponse::build(ErrorCode::BADREQUEST("fmt must be one of: webp, jpeg, png".to_string())),
+    Some(format) => format,
+    None => None,
+  };
+
+  let variant = ImageVariant {
+    width: request.w,
+    height: request.h,
+    format,
+  };
+
+  match service.get_image(&hash, variant).await {
+    Ok((bytes, content_type)) => HttpResponse::Ok()
+      .content_type(content_type)
+      .insert_header(CacheControl(vec![
+        CacheDirective::Public,
+        CacheDirective::MaxAge(31_536_000),
+        CacheDirective::Extension("immutable".to_owned(), None),
+      ]))
+      .body(bytes),
+    Err(ImageError::NotFound) => HttpResponse::NotFound().body("Image not found"),
+    Err(ImageError::BadRequest(message)) => ErrorResponse::build(ErrorCode::BADREQUEST(message)),
+    Err(ImageError::Upstream(message)) => {
+      log::warn!("Не удалось загрузить изображение {}: {}", hash, message);
+      HttpResponse::BadGateway().finish()
+    }
+    Err(ImageError::Internal(message)) => {
+      log::error!("Ошибка обработки изображения {}: {}", hash, message);
+      HttpResponse::InternalServerError().finish()
+    }
+  }
+}
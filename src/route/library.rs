@@ -0,0 +1,166 @@
+use crate::middleware::auth::AuthenticatedUser;
+use crate::model::dto::library::{CreateLibraryEntryRequest, LibraryQuery, LibraryStatus, UpdateLibraryEntryRequest};
+use crate::model::error::ErrorResponse;
+use crate::prisma::PrismaClient;
+use crate::service::library::LibraryService;
+use actix_web::{delete, get, patch, post, put, web, HttpResponse, Responder};
+
+#[doc = "Mounted under `/user` behind `AuthGuard`, before `user_controller_init` so `/library` is not taken for a user id"]
+pub fn library_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg.service(
+    web::scope("/library")
+      .service(get_library)
+      .service(add_to_library)
+      .service(get_library_entry)
+      .service(update_library_entry)
+      .service(remove_from_library)
+      .service(get_play_sessions)
+      .service(start_play_session)
+      .service(stop_play_session),
+  );
+  cfg.service(
+    web::scope("/wishlist")
+      .service(get_wishlist)
+      .service(add_to_wishlist)
+      .service(remove_from_wishlist),
+  );
+}
+
+#[get("")]
+async fn get_library(
+  user: AuthenticatedUser,
+  query: web::Query<LibraryQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::list_entries(&client, &user.id, query.into_inner().status).await {
+    Ok(entries) => HttpResponse::Ok().json(entries),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("")]
+async fn add_to_library(
+  user: AuthenticatedUser,
+  body: web::Json<CreateLibraryEntryRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::create_entry(&client, &user.id, body.into_inner()).await {
+    Ok(entry) => HttpResponse::Created().json(entry),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/{game_id}")]
+async fn get_library_entry(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::get_entry(&client, &user.id, path.into_inner()).await {
+    Ok(entry) => HttpResponse::Ok().json(entry),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[patch("/{game_id}")]
+async fn update_library_entry(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  body: web::Json<UpdateLibraryEntryRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::update_entry(&client, &user.id, path.into_inner(), body.into_inner()).await {
+    Ok(entry) => HttpResponse::Ok().json(entry),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{game_id}")]
+async fn remove_from_library(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::delete_entry(&client, &user.id, path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/{game_id}/sessions")]
+async fn get_play_sessions(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::list_sessions(&client, &user.id, path.into_inner()).await {
+    Ok(sessions) => HttpResponse::Ok().json(sessions),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("/{game_id}/sessions")]
+async fn start_play_session(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::start_session(&client, &user.id, path.into_inner()).await {
+    Ok(session) => HttpResponse::Created().json(session),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("/{game_id}/sessions/{id}/stop")]
+async fn stop_play_session(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (game_id, session_id) = path.into_inner();
+  match LibraryService::stop_session(&client, &user.id, game_id, &session_id).await {
+    Ok(session) => HttpResponse::Ok().json(session),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("")]
+async fn get_wishlist(
+  user: AuthenticatedUser,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::list_wishlist(&client, &user.id).await {
+    Ok(wishlist) => HttpResponse::Ok().json(wishlist),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[put("/{game_id}")]
+async fn add_to_wishlist(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let request = CreateLibraryEntryRequest {
+    game_id: path.into_inner(),
+    status: LibraryStatus::Wishlist,
+    installed_version: None,
+    install_path_hash: None,
+  };
+  match LibraryService::create_entry(&client, &user.id, request).await {
+    Ok(entry) => HttpResponse::Created().json(entry),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{game_id}")]
+async fn remove_from_wishlist(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LibraryService::remove_from_wishlist(&client, &user.id, path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
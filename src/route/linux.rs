@@ -0,0 +1,55 @@
+use crate::middleware::auth::{AuthGuard, AuthenticatedUser};
+use crate::middleware::rbac::has_permission;
+use crate::model::dto::linux::{CreateLinuxReportRequest, LinuxReportQuery};
+use crate::model::dto::rbac::Permission;
+use crate::model::error::ErrorResponse;
+use crate::prisma::PrismaClient;
+use crate::service::linux::LinuxReportService;
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+
+#[doc = "Configured inside the `/games` scope; reading is public, writes go through `AuthGuard`"]
+pub fn linux_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg
+    .service(get_linux_reports)
+    .service(create_linux_report)
+    .service(delete_linux_report);
+}
+
+#[get("/{id}/linux-reports")]
+async fn get_linux_reports(
+  path: web::Path<i32>,
+  query: web::Query<LinuxReportQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LinuxReportService::list_reports(&client, path.into_inner(), query.into_inner()).await {
+    Ok(page) => HttpResponse::Ok().json(page),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("/{id}/linux-reports", wrap = "AuthGuard")]
+async fn create_linux_report(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  body: web::Json<CreateLinuxReportRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match LinuxReportService::create_report(&client, &user.id, path.into_inner(), body.into_inner()).await {
+    Ok(report) => HttpResponse::Created().json(report),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{id}/linux-reports/{report_id}", wrap = "AuthGuard")]
+async fn delete_linux_report(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (game_id, report_id) = path.into_inner();
+  let moderator = has_permission(&client, &user, Permission::ModerateContent).await;
+  match LinuxReportService::delete_report(&client, &user.id, game_id, &report_id, moderator).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
@@ -1,5 +1,16 @@
 pub(crate) mod health_check;
+pub(crate) mod admin;
 pub(crate) mod auth;
 pub(crate) mod user;
+pub(crate) mod library;
+pub(crate) mod linux;
+pub(crate) mod notifications;
+pub(crate) mod reviews;
+pub(crate) mod reports;
+pub(crate) mod saves;
+pub(crate) mod webhooks;
 pub(crate) mod games;
+pub(crate) mod catalog;
+pub(crate) mod images;
+pub(crate) mod search;
 pub(crate) mod torrent;
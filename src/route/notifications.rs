@@ -0,0 +1,65 @@
+use crate::middleware::auth::AuthenticatedUser;
+use crate::model::dto::notification::NotificationQuery;
+use crate::model::error::ErrorResponse;
+use crate::prisma::PrismaClient;
+use crate::service::notification::NotificationService;
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use serde_json::json;
+
+#[doc = "Mounted under `/user` behind `AuthGuard`, before `user_controller_init`"]
+pub fn notifications_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg.service(
+    web::scope("/notifications")
+      .service(get_notifications)
+      .service(mark_all_read)
+      .service(mark_read)
+      .service(delete_notification),
+  );
+}
+
+#[get("")]
+async fn get_notifications(
+  user: AuthenticatedUser,
+  query: web::Query<NotificationQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match NotificationService::list(&client, &user.id, query.unread.unwrap_or(false)).await {
+    Ok(notifications) => HttpResponse::Ok().json(notifications),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("/read")]
+async fn mark_all_read(
+  user: AuthenticatedUser,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match NotificationService::mark_all_read(&client, &user.id).await {
+    Ok(count) => HttpResponse::Ok().json(json!({ "marked": count })),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("/{id}/read")]
+async fn mark_read(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match NotificationService::mark_read(&client, &user.id, &path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{id}")]
+async fn delete_notification(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match NotificationService::delete(&client, &user.id, &path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
@@ -0,0 +1,50 @@
+use crate::middleware::auth::{AuthGuard, AuthenticatedUser};
+use crate::model::dto::torrent::{ReleaseQuery, ReportReleaseRequest};
+use crate::model::error::ErrorResponse;
+use crate::prisma::PrismaClient;
+use crate::service::reports::ReportService;
+use actix_web::{delete, get, put, web, HttpResponse, Responder};
+
+#[doc = "Configured inside the `/torrent` scope; stats are public, reporting goes through `AuthGuard`"]
+pub fn reports_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg
+    .service(get_report_stats)
+    .service(report_release)
+    .service(remove_report);
+}
+
+#[get("/reports")]
+async fn get_report_stats(
+  query: web::Query<ReleaseQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReportService::stats(&client, &query.torrent).await {
+    Ok(stats) => HttpResponse::Ok().json(stats),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[doc = "Replaces the caller's previous report for the same release"]
+#[put("/reports", wrap = "AuthGuard")]
+async fn report_release(
+  user: AuthenticatedUser,
+  body: web::Json<ReportReleaseRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReportService::report(&client, &user.id, body.into_inner()).await {
+    Ok(stats) => HttpResponse::Ok().json(stats),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/reports", wrap = "AuthGuard")]
+async fn remove_report(
+  user: AuthenticatedUser,
+  query: web::Query<ReleaseQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReportService::remove_report(&client, &user.id, &query.torrent).await {
+    Ok(stats) => HttpResponse::Ok().json(stats),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
@@ -0,0 +1,111 @@
+use crate::middleware::auth::{AuthGuard, AuthenticatedUser};
+use crate::middleware::rbac::has_permission;
+use crate::model::dto::rbac::Permission;
+use crate::model::dto::reviews::{CreateReviewRequest, ReviewQuery, UpdateReviewRequest};
+use crate::model::error::ErrorResponse;
+use crate::prisma::PrismaClient;
+use crate::service::reviews::ReviewService;
+use actix_web::{delete, get, patch, post, put, web, HttpResponse, Responder};
+
+#[doc = "Configured inside the `/games` scope; reading is public, writes go through `AuthGuard`"]
+pub fn reviews_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg
+    .service(get_reviews)
+    .service(create_review)
+    .service(get_review)
+    .service(update_review)
+    .service(delete_review)
+    .service(vote_helpful)
+    .service(remove_vote);
+}
+
+#[get("/{id}/reviews")]
+async fn get_reviews(
+  path: web::Path<i32>,
+  query: web::Query<ReviewQuery>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReviewService::list_reviews(&client, path.into_inner(), query.into_inner()).await {
+    Ok(page) => HttpResponse::Ok().json(page),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("/{id}/reviews", wrap = "AuthGuard")]
+async fn create_review(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  body: web::Json<CreateReviewRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match ReviewService::create_review(&client, &user.id, path.into_inner(), body.into_inner()).await {
+    Ok(review) => HttpResponse::Created().json(review),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/{id}/reviews/{review_id}")]
+async fn get_review(
+  path: web::Path<(i32, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (game_id, review_id) = path.into_inner();
+  match ReviewService::get_review(&client, game_id, &review_id).await {
+    Ok(review) => HttpResponse::Ok().json(review),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[patch("/{id}/reviews/{review_id}", wrap = "AuthGuard")]
+async fn update_review(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, String)>,
+  body: web::Json<UpdateReviewRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (game_id, review_id) = path.into_inner();
+  match ReviewService::update_review(&client, &user.id, game_id, &review_id, body.into_inner()).await {
+    Ok(review) => HttpResponse::Ok().json(review),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{id}/reviews/{review_id}", wrap = "AuthGuard")]
+async fn delete_review(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (game_id, review_id) = path.into_inner();
+  let moderator = has_permission(&client, &user, Permission::ModerateContent).await;
+  match ReviewService::delete_review(&client, &user.id, game_id, &review_id, moderator).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[put("/{id}/reviews/{review_id}/helpful", wrap = "AuthGuard")]
+async fn vote_helpful(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (game_id, review_id) = path.into_inner();
+  match ReviewService::vote_helpful(&client, &user.id, game_id, &review_id).await {
+    Ok(review) => HttpResponse::Ok().json(review),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{id}/reviews/{review_id}/helpful", wrap = "AuthGuard")]
+async fn remove_vote(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, String)>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let (game_id, review_id) = path.into_inner();
+  match ReviewService::remove_vote(&client, &user.id, game_id, &review_id).await {
+    Ok(review) => HttpResponse::Ok().json(review),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
@@ -0,0 +1,151 @@
+use crate::middleware::auth::AuthenticatedUser;
+use crate::model::dto::saves::{SaveInfo, UploadSaveQuery};
+use crate::model::error::{ErrorCode, ErrorResponse};
+use crate::service::saves::{SaveService, SaveStream, MAX_SAVE_BYTES};
+use actix_web::http::header::{ContentType, CONTENT_LENGTH, ETag, EntityTag, IfMatch};
+use actix_web::{delete, get, put, web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use futures::StreamExt;
+use std::sync::Arc;
+
+#[doc = "Mounted under `/user` behind `AuthGuard`, before `user_controller_init`"]
+pub fn saves_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg.service(
+    web::scope("/saves")
+      .service(get_saves)
+      .service(get_save_versions)
+      .service(upload_save)
+      .service(delete_saves)
+      .service(download_latest_save)
+      .service(download_save)
+      .service(delete_save_version),
+  );
+}
+
+fn save_etag(save: &SaveInfo) -> ETag {
+  ETag(EntityTag::new_strong(save.version.to_string()))
+}
+
+#[doc = "Streams the archive from storage, the size is known so the body is sent with Content-Length"]
+fn archive_response(save: SaveInfo, stream: SaveStream) -> HttpResponse {
+  HttpResponse::Ok()
+    .insert_header(save_etag(&save))
+    .insert_header(("X-Content-Sha256", save.hash))
+    .content_type(ContentType::octet_stream())
+    .no_chunking(save.size as u64)
+    .streaming(stream)
+}
+
+#[get("")]
+async fn get_saves(
+  user: AuthenticatedUser,
+  save_service: web::Data<Arc<SaveService>>,
+) -> impl Responder {
+  match save_service.overview(&user.id).await {
+    Ok(overview) => HttpResponse::Ok().json(overview),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/{game_id}")]
+async fn get_save_versions(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  save_service: web::Data<Arc<SaveService>>,
+) -> impl Responder {
+  match save_service.list_versions(&user.id, path.into_inner()).await {
+    Ok(versions) => HttpResponse::Ok().json(versions),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[doc = "Raw archive body, `If-Match` is the version the device last synced (`0` for the first upload) or `*`"]
+#[put("/{game_id}")]
+async fn upload_save(
+  user: AuthenticatedUser,
+  req: HttpRequest,
+  path: web::Path<i32>,
+  query: web::Query<UploadSaveQuery>,
+  payload: web::Payload,
+  save_service: web::Data<Arc<SaveService>>,
+) -> impl Responder {
+  let expected_version = match req.get_header::<IfMatch>() {
+    None => return ErrorResponse::build(ErrorCode::PRECONDITION002),
+    Some(IfMatch::Any) => None,
+    Some(IfMatch::Items(tags)) => {
+      match tags.iter().filter(|tag| !tag.weak).find_map(|tag| tag.tag().parse::<i32>().ok()) {
+        Some(version) => Some(version),
+        None => return ErrorResponse::build(ErrorCode::PRECONDITION001),
+      }
+    }
+  };
+
+  // Заведомо слишком большой архив отклоняется до чтения тела.
+  let declared = req
+    .headers()
+    .get(CONTENT_LENGTH)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.parse::<usize>().ok());
+  if declared.map(|length| length > MAX_SAVE_BYTES).unwrap_or(false) {
+    return ErrorResponse::build(ErrorCode::PAYLOAD001);
+  }
+
+  let body = payload.map(|chunk| chunk.map_err(|e| e.to_string()));
+  let result = save_service
+    .put_save(&user.id, path.into_inner(), expected_version, query.into_inner().device, body)
+    .await;
+  match result {
+    Ok((save, true)) => HttpResponse::Created().insert_header(save_etag(&save)).json(save),
+    Ok((save, false)) => HttpResponse::Ok().insert_header(save_etag(&save)).json(save),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{game_id}")]
+async fn delete_saves(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  save_service: web::Data<Arc<SaveService>>,
+) -> impl Responder {
+  match save_service.delete_game(&user.id, path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/{game_id}/latest")]
+async fn download_latest_save(
+  user: AuthenticatedUser,
+  path: web::Path<i32>,
+  save_service: web::Data<Arc<SaveService>>,
+) -> impl Responder {
+  match save_service.get_save(&user.id, path.into_inner(), None).await {
+    Ok((save, stream)) => archive_response(save, stream),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/{game_id}/{version}")]
+async fn download_save(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, i32)>,
+  save_service: web::Data<Arc<SaveService>>,
+) -> impl Responder {
+  let (game_id, version) = path.into_inner();
+  match save_service.get_save(&user.id, game_id, Some(version)).await {
+    Ok((save, stream)) => archive_response(save, stream),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{game_id}/{version}")]
+async fn delete_save_version(
+  user: AuthenticatedUser,
+  path: web::Path<(i32, i32)>,
+  save_service: web::Data<Arc<SaveService>>,
+) -> impl Responder {
+  let (game_id, version) = path.into_inner();
+  match save_service.delete_version(&user.id, game_id, version).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
@@ -0,0 +1,20 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use crate::model::dto::search::SuggestRequest;
+use crate::service::suggest::SuggestService;
+use std::sync::Arc;
+
+pub fn search_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg.service(
+    web::scope("/search")
+      .service(suggest),
+  );
+}
+
+#[get("/suggest")]
+async fn suggest(
+  service: web::Data<Arc<SuggestService>>,
+  data: web::Query<SuggestRequest>,
+) -> impl Responder {
+  let request = data.into_inner();
+  HttpResponse::Ok().json(service.suggest(&request.q, request.limit))
+}
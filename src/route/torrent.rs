@@ -1,18 +1,24 @@
 use actix_web::{get, web, HttpResponse, Responder};
-use crate::model::dto::torrent::TorrentInfoRequest;
+use crate::model::dto::torrent::{TorrentInfoRequest, TorrentRelease};
+use crate::model::error::ErrorResponse;
+use crate::prisma::PrismaClient;
+use crate::route::reports::reports_controller_init;
+use crate::service::reports::ReportService;
 use crate::service::torrent::TorrentService;
 use std::sync::Arc;
 
 pub fn torrent_controller_init(cfg: &mut web::ServiceConfig) {
   cfg.service(
     web::scope("/torrent")
-      .service(get_torrent_info),
+      .service(get_torrent_info)
+      .configure(reports_controller_init),
   );
 }
 
 #[get("")]
 async fn get_torrent_info(
   service: web::Data<Arc<TorrentService>>,
+  client: web::Data<PrismaClient>,
   data: web::Query<TorrentInfoRequest>,
 ) -> impl Responder {
   let game_name = &data.name;
@@ -21,6 +27,21 @@ async fn get_torrent_info(
     Ok(results) => {
       if results.is_empty() {
         HttpResponse::NotFound().body("Torrent not found")
+      } else if data.with_reports.unwrap_or(false) {
+        let links: Vec<String> = results.iter().map(|(_, torrent)| torrent.clone()).collect();
+        let mut stats = match ReportService::stats_for(&client, &links).await {
+          Ok(stats) => stats,
+          Err(e) => return ErrorResponse::build(e),
+        };
+        let releases: Vec<TorrentRelease> = results
+          .into_iter()
+          .map(|(repacker, torrent)| TorrentRelease {
+            reports: stats.remove(&torrent).unwrap_or_default(),
+            repacker,
+            torrent,
+          })
+          .collect();
+        HttpResponse::Ok().json(releases)
       } else {
         HttpResponse::Ok().json(results)
       }
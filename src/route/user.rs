@@ -1,48 +1,176 @@
+use crate::middleware::auth::AuthenticatedUser;
+use crate::model::dto::profile::UpdateProfileRequest;
+use crate::model::dto::rbac::Permission;
+use crate::model::dto::token::CreateTokenRequest;
+use crate::model::error::{ErrorCode, ErrorResponse};
 use crate::prisma::PrismaClient;
+use crate::route::auth::begin_auth_flow;
+use crate::service::auth::{list_identities, unlink_identity, AuthService};
+use crate::service::images::{ImageError, ImageService, MAX_UPLOAD_BYTES};
+use crate::service::rbac::RbacService;
+use crate::service::session::{SqliteSessionStore, SESSION_ID_KEY};
+use crate::service::settings::{SettingsDocument, SettingsService};
+use crate::service::token::TokenService;
 use crate::service::user::UserService;
-use actix_identity::Identity;
-use actix_web::{get, web, HttpResponse, Responder};
+use actix_session::Session;
+use actix_web::http::header::{ETag, EntityTag, IfMatch};
+use actix_web::{delete, get, post, put, web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use futures::StreamExt;
+use serde_json::Value;
+use std::sync::Arc;
 
+#[doc = "Mounted under `/user` behind `AuthGuard` in `server::get_config`"]
 pub fn user_controller_init(cfg: &mut web::ServiceConfig) {
-  cfg.service(
-    web::scope("/user")
-      .service(get_current_user)
-      .service(get_user_by_id),
-  );
+  cfg
+    .service(get_current_user)
+    .service(update_profile)
+    .service(upload_avatar)
+    .service(delete_avatar)
+    .service(get_settings)
+    .service(put_settings)
+    .service(get_identities)
+    .service(link_provider)
+    .service(unlink_provider)
+    .service(get_sessions)
+    .service(revoke_session)
+    .service(get_tokens)
+    .service(create_token)
+    .service(revoke_token)
+    .service(get_user_by_id);
 }
 
 #[get("")]
 async fn get_current_user(
-  identity: Option<Identity>,
-  client: web::Data<PrismaClient>,
-) -> impl Responder {
-  if let Some(identity) = identity {
-    if let Ok(id) = identity.id() {
-      match UserService::get_user_by_id(&client, &id).await {
-        Ok(Some(user)) => {
-          return HttpResponse::Ok().json(user);
-        },
-        Ok(None) => {
-          log::warn!("Пользователь не найден в базе данных для id: {}", id);
-          return HttpResponse::NotFound().body("User not found");
-        },
-        Err(err) => {
-          log::error!("Ошибка при поиске пользователя: {:?}", err);
-          return HttpResponse::InternalServerError().finish();
-        }
+  user: AuthenticatedUser,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match UserService::get_user_by_id(&client, &user.id).await {
+    Ok(Some(user)) => HttpResponse::Ok().json(user),
+    Ok(None) => {
+      log::warn!("Пользователь не найден в базе данных для id: {}", user.id);
+      HttpResponse::NotFound().body("User not found")
+    },
+    Err(err) => {
+      log::error!("Ошибка при поиске пользователя: {:?}", err);
+      HttpResponse::InternalServerError().finish()
+    }
+  }
+}
+
+#[put("/profile")]
+async fn update_profile(
+  user: AuthenticatedUser,
+  body: web::Json<UpdateProfileRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match UserService::update_profile(&client, &user.id, body.into_inner()).await {
+    Ok(user) => HttpResponse::Ok().json(user),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[doc = "Raw JPEG, PNG or WebP body, stored in the image cache and served from `/api/images/{hash}`"]
+#[put("/avatar")]
+async fn upload_avatar(
+  user: AuthenticatedUser,
+  mut payload: web::Payload,
+  client: web::Data<PrismaClient>,
+  image_service: web::Data<Arc<ImageService>>,
+) -> impl Responder {
+  let mut bytes = web::BytesMut::new();
+  while let Some(chunk) = payload.next().await {
+    let chunk = match chunk {
+      Ok(chunk) => chunk,
+      Err(_) => return ErrorResponse::build(ErrorCode::BADREQUEST("Failed to read request body".to_string())),
+    };
+    if bytes.len() + chunk.len() > MAX_UPLOAD_BYTES {
+      return HttpResponse::PayloadTooLarge().finish();
+    }
+    bytes.extend_from_slice(&chunk);
+  }
+
+  let hash = match image_service.store_avatar(bytes.to_vec()).await {
+    Ok(hash) => hash,
+    Err(ImageError::BadRequest(message)) => return ErrorResponse::build(ErrorCode::BADREQUEST(message)),
+    Err(e) => {
+      log::error!("Не удалось сохранить аватар пользователя {}: {:?}", user.id, e);
+      return HttpResponse::InternalServerError().finish();
+    }
+  };
+
+  match UserService::set_avatar(&client, &user.id, Some(format!("/api/images/{}", hash))).await {
+    Ok(user) => HttpResponse::Ok().json(user),
+    Err(e) => ErrorResponse::build(e.into()),
+  }
+}
+
+#[delete("/avatar")]
+async fn delete_avatar(
+  user: AuthenticatedUser,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match UserService::set_avatar(&client, &user.id, None).await {
+    Ok(user) => HttpResponse::Ok().json(user),
+    Err(e) => ErrorResponse::build(e.into()),
+  }
+}
+
+fn settings_response(settings: SettingsDocument) -> HttpResponse {
+  HttpResponse::Ok()
+    .insert_header(ETag(EntityTag::new_strong(settings.version.to_string())))
+    .json(settings.document)
+}
+
+#[get("/settings")]
+async fn get_settings(
+  user: AuthenticatedUser,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match SettingsService::get_settings(&client, &user.id).await {
+    Ok(settings) => settings_response(settings),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[doc = "Requires `If-Match` with the ETag from the last read, `*` overwrites unconditionally"]
+#[put("/settings")]
+async fn put_settings(
+  user: AuthenticatedUser,
+  req: HttpRequest,
+  body: web::Json<Value>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  let expected_version = match req.get_header::<IfMatch>() {
+    None => return ErrorResponse::build(ErrorCode::PRECONDITION002),
+    Some(IfMatch::Any) => None,
+    Some(IfMatch::Items(tags)) => {
+      match tags.iter().filter(|tag| !tag.weak).find_map(|tag| tag.tag().parse::<i32>().ok()) {
+        Some(version) => Some(version),
+        None => return ErrorResponse::build(ErrorCode::PRECONDITION001),
       }
     }
+  };
+
+  match SettingsService::put_settings(&client, &user.id, expected_version, body.into_inner()).await {
+    Ok(settings) => settings_response(settings),
+    Err(e) => ErrorResponse::build(e),
   }
-  log::warn!("Пользователь не авторизован");
-  HttpResponse::Unauthorized().body("User not logged in")
 }
 
 #[get("/{id}")]
 async fn get_user_by_id(
+  user: AuthenticatedUser,
   path: web::Path<String>,
   client: web::Data<PrismaClient>,
 ) -> impl Responder {
   let id = path.into_inner();
+  if id != user.id {
+    match RbacService::user_permissions(&client, &user.id).await {
+      Ok(permissions) if permissions.contains(&Permission::ReadUsers) => {}
+      Ok(_) => return ErrorResponse::build(ErrorCode::AUTH005),
+      Err(e) => return ErrorResponse::build(e),
+    }
+  }
   match UserService::get_user_by_id(&client, &id).await {
     Ok(Some(user)) => HttpResponse::Ok().json(user),
     Ok(None) => {
@@ -55,3 +183,119 @@ async fn get_user_by_id(
     },
   }
 }
+
+#[get("/identities")]
+async fn get_identities(
+  user: AuthenticatedUser,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match list_identities(&client, &user.id).await {
+    Ok(identities) => HttpResponse::Ok().json(identities),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/identities/{provider}/link")]
+async fn link_provider(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  session: Session,
+  auth_service: web::Data<Arc<AuthService>>,
+) -> impl Responder {
+  if let Err(e) = user.require_session() {
+    return ErrorResponse::build(e);
+  }
+  begin_auth_flow(&session, &auth_service, &path.into_inner(), Some(user.id))
+}
+
+#[delete("/identities/{provider}")]
+async fn unlink_provider(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  if let Err(e) = user.require_session() {
+    return ErrorResponse::build(e);
+  }
+  match unlink_identity(&client, &user.id, &path.into_inner()).await {
+    Ok(identities) => HttpResponse::Ok().json(identities),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/sessions")]
+async fn get_sessions(
+  user: AuthenticatedUser,
+  session: Session,
+  store: web::Data<Arc<SqliteSessionStore>>,
+) -> impl Responder {
+  let current_id = session.get::<String>(SESSION_ID_KEY).ok().flatten();
+  match store.list_sessions(&user.id, current_id.as_deref()).await {
+    Ok(sessions) => HttpResponse::Ok().json(sessions),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/sessions/{id}")]
+async fn revoke_session(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  session: Session,
+  store: web::Data<Arc<SqliteSessionStore>>,
+) -> impl Responder {
+  if let Err(e) = user.require_session() {
+    return ErrorResponse::build(e);
+  }
+  let session_id = path.into_inner();
+  match store.revoke_session(&user.id, &session_id).await {
+    Ok(()) => {
+      if session.get::<String>(SESSION_ID_KEY).ok().flatten().as_deref() == Some(session_id.as_str()) {
+        session.purge();
+      }
+      HttpResponse::NoContent().finish()
+    }
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/tokens")]
+async fn get_tokens(
+  user: AuthenticatedUser,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  match TokenService::list_tokens(&client, &user.id).await {
+    Ok(tokens) => HttpResponse::Ok().json(tokens),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[post("/tokens")]
+async fn create_token(
+  user: AuthenticatedUser,
+  body: web::Json<CreateTokenRequest>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  if let Err(e) = user.require_session() {
+    return ErrorResponse::build(e);
+  }
+  let request = body.into_inner();
+  match TokenService::create_token(&client, &user.id, &request.name, &request.scopes).await {
+    Ok(token) => HttpResponse::Created().json(token),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/tokens/{id}")]
+async fn revoke_token(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  client: web::Data<PrismaClient>,
+) -> impl Responder {
+  if let Err(e) = user.require_session() {
+    return ErrorResponse::build(e);
+  }
+  match TokenService::revoke_token(&client, &user.id, &path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
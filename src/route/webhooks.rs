@@ -0,0 +1,96 @@
+use crate::middleware::auth::AuthenticatedUser;
+use crate::model::dto::webhook::{CreateWebhookRequest, UpdateWebhookRequest};
+use crate::model::error::ErrorResponse;
+use crate::service::webhook::WebhookService;
+use actix_web::{delete, get, patch, post, web, HttpResponse, Responder};
+use std::sync::Arc;
+
+#[doc = "Mounted under `/user` behind `AuthGuard`, before `user_controller_init`"]
+pub fn webhooks_controller_init(cfg: &mut web::ServiceConfig) {
+  cfg.service(
+    web::scope("/webhooks")
+      .service(get_webhooks)
+      .service(create_webhook)
+      .service(update_webhook)
+      .service(delete_webhook)
+      .service(get_deliveries)
+      .service(test_webhook),
+  );
+}
+
+#[get("")]
+async fn get_webhooks(
+  user: AuthenticatedUser,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  match webhook_service.list_webhooks(Some(&user.id)).await {
+    Ok(webhooks) => HttpResponse::Ok().json(webhooks),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[doc = "The signing secret is only returned here, so registering a receiver needs a browser session"]
+#[post("")]
+async fn create_webhook(
+  user: AuthenticatedUser,
+  body: web::Json<CreateWebhookRequest>,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  if let Err(e) = user.require_session() {
+    return ErrorResponse::build(e);
+  }
+  match webhook_service.create_webhook(&user.id, body.into_inner()).await {
+    Ok(webhook) => HttpResponse::Created().json(webhook),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[patch("/{id}")]
+async fn update_webhook(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  body: web::Json<UpdateWebhookRequest>,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  match webhook_service.update_webhook(Some(&user.id), &path.into_inner(), body.into_inner()).await {
+    Ok(webhook) => HttpResponse::Ok().json(webhook),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[delete("/{id}")]
+async fn delete_webhook(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  match webhook_service.delete_webhook(Some(&user.id), &path.into_inner()).await {
+    Ok(()) => HttpResponse::NoContent().finish(),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[get("/{id}/deliveries")]
+async fn get_deliveries(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  match webhook_service.list_deliveries(Some(&user.id), &path.into_inner()).await {
+    Ok(deliveries) => HttpResponse::Ok().json(deliveries),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
+
+#[doc = "Queues a signed `ping` delivery; the result shows up in the delivery log"]
+#[post("/{id}/test")]
+async fn test_webhook(
+  user: AuthenticatedUser,
+  path: web::Path<String>,
+  webhook_service: web::Data<Arc<WebhookService>>,
+) -> impl Responder {
+  match webhook_service.test_webhook(Some(&user.id), &path.into_inner()).await {
+    Ok(delivery) => HttpResponse::Accepted().json(delivery),
+    Err(e) => ErrorResponse::build(e),
+  }
+}
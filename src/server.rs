@@ -1,20 +1,48 @@
+use crate::middleware::auth::AuthGuard;
 use crate::middleware::games::GamesMiddleware;
+use crate::middleware::session::SessionMetadata;
 use crate::modules::constants::API_SECRET;
 use crate::prisma::PrismaClient;
+use crate::route::admin::admin_controller_init;
 use crate::route::auth::auth_controller_init;
+use crate::route::catalog::catalog_controller_init;
 use crate::route::games::games_controller_init;
 use crate::route::health_check::health_check;
+use crate::route::images::images_controller_init;
+use crate::route::library::library_controller_init;
+use crate::route::notifications::notifications_controller_init;
+use crate::route::saves::saves_controller_init;
+use crate::route::search::search_controller_init;
 use crate::route::torrent::torrent_controller_init;
 use crate::route::user::user_controller_init;
-use crate::service::torrent::TorrentService;
+use crate::route::webhooks::webhooks_controller_init;
+use crate::modules::auth::discord::DiscordOAuth;
+use crate::modules::auth::github::GitHubOAuth;
+use crate::modules::auth::google::GoogleOAuth;
+use crate::modules::auth::steam::SteamOpenID;
+use crate::modules::auth::yandex::YandexOAuth;
+use crate::modules::metadata::rawg::ProviderRAWG;
+use crate::modules::metadata::steam::ProviderSteam;
+use crate::modules::storage::local::LocalStorage;
+use crate::service::auth::{sweep_yandex_tokens, AuthService, SWEEP_INTERVAL_MINUTES};
+use crate::service::images::ImageService;
+use crate::service::metadata::MetadataService;
+use crate::service::mirror::{MirrorService, SYNC_PAGES};
+use crate::service::notifier::{Notifier, CHECK_INTERVAL_MINUTES};
+use crate::service::rbac::RbacService;
+use crate::service::saves::SaveService;
+use crate::service::session::{SqliteSessionStore, CLEANUP_INTERVAL_MINUTES};
+use crate::service::suggest::{SuggestService, REFRESH_INTERVAL_MINUTES};
+use crate::service::torrent::{TorrentService, INDEX_WATCH_INTERVAL_MINUTES};
+use crate::service::webhook::WebhookService;
 use actix_identity::{Identity, IdentityMiddleware};
-use actix_session::storage::CookieSessionStore;
 use actix_session::{Session, SessionMiddleware};
-use actix_session::config::PersistentSession;
+use actix_session::config::{PersistentSession, TtlExtensionPolicy};
 use actix_web::cookie::time::Duration;
 use actix_web::dev::Server;
 use actix_web::{get, middleware, web, App, HttpResponse, HttpServer, Responder};
 use log::{info, error};
+use std::env;
 use std::net::TcpListener;
 use std::sync::Arc;
 
@@ -47,8 +75,24 @@ pub fn get_config(conf: &mut web::ServiceConfig) {
 		web::scope("/api")
 			.service(health_check)
 			.configure(auth_controller_init)
-			.configure(user_controller_init)
+			.service(
+				web::scope("/user")
+					.wrap(AuthGuard)
+					.configure(library_controller_init)
+					.configure(notifications_controller_init)
+					.configure(webhooks_controller_init)
+					.configure(saves_controller_init)
+					.configure(user_controller_init)
+			)
+			.service(
+				web::scope("/admin")
+					.wrap(AuthGuard)
+					.configure(admin_controller_init)
+			)
 			.configure(games_controller_init)
+			.configure(catalog_controller_init)
+			.configure(images_controller_init)
+			.configure(search_controller_init)
 			.configure(torrent_controller_init)
 	);
 }
@@ -57,25 +101,160 @@ pub async fn run(tcp_listener: TcpListener, data: PrismaClient) -> Result<Server
 	let data = Arc::new(data);
 	let data_web = web::Data::from(data.clone());
 
-	let games_middleware = GamesMiddleware::new().await;
-	let games_middleware = web::Data::new(Arc::new(games_middleware));
+	if let Err(err) = RbacService::ensure_builtin_roles(&data).await {
+		error!("Ошибка при создании встроенных ролей: {:?}", err);
+	}
+	if let Err(err) = RbacService::bootstrap_admins(&data).await {
+		error!("Ошибка при назначении администраторов: {:?}", err);
+	}
+
+	let auth_service = AuthService::new(vec![
+		Box::new(YandexOAuth::new()),
+		Box::new(SteamOpenID::new()),
+		Box::new(DiscordOAuth::new()),
+		Box::new(GoogleOAuth::new()),
+		Box::new(GitHubOAuth::new()),
+	]);
+	let auth_service = web::Data::new(Arc::new(auth_service));
+
+	let games_middleware = Arc::new(GamesMiddleware::new().await);
+
+	let metadata_service = Arc::new(MetadataService::new(vec![
+		Box::new(ProviderRAWG::new(games_middleware.clone())),
+		Box::new(ProviderSteam::new()),
+	]));
+	let notifier = Arc::new(Notifier::new(data.clone(), metadata_service.clone()));
+	let metadata_service = web::Data::new(metadata_service);
+
+	let mirror_service = Arc::new(MirrorService::new(data.clone()));
+	if env::var("VEK_CATALOG_SYNC").map(|value| value == "true" || value == "1").unwrap_or(false) {
+		let interval_hours = env::var("VEK_CATALOG_SYNC_INTERVAL_HOURS")
+			.ok()
+			.and_then(|value| value.parse::<u64>().ok())
+			.unwrap_or(24)
+			.max(1);
+		let mirror_service = mirror_service.clone();
+		let games_middleware = games_middleware.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_hours * 3600));
+			loop {
+				interval.tick().await;
+				mirror_service.sync_popular(&games_middleware, SYNC_PAGES).await;
+			}
+		});
+	}
+	let mirror_service = web::Data::new(mirror_service);
+
+	let games_middleware = web::Data::new(games_middleware);
+
+	let image_service = web::Data::new(Arc::new(ImageService::new()));
+
+	let save_storage = LocalStorage::new()?;
+	let save_service = web::Data::new(Arc::new(SaveService::new(data.clone(), Box::new(save_storage))));
 
 	let private_key = actix_web::cookie::Key::from(API_SECRET.as_bytes());
 
-	let torrent_service = TorrentService::new(data.clone());
+	let webhook_service = Arc::new(WebhookService::new(data.clone()));
+	{
+		let webhook_service = webhook_service.clone();
+		tokio::spawn(async move {
+			webhook_service.run_worker().await;
+		});
+	}
+
+	let torrent_service = Arc::new(TorrentService::new(data.clone(), webhook_service.clone()));
 	if let Err(err) = torrent_service.initialize_torrents().await {
 		error!("Ошибка при инициализации торрентов: {:?}", err);
 	}
+	{
+		let torrent_service = torrent_service.clone();
+		let notifier = notifier.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(std::time::Duration::from_secs(INDEX_WATCH_INTERVAL_MINUTES * 60));
+			loop {
+				interval.tick().await;
+				match torrent_service.emit_new_torrents().await {
+					Ok(count) if count > 0 => {
+						info!("Новых раздач в индексе: {}", count);
+						// Подписчики узнают о новом репаке сразу, а не на следующем часовом прогоне.
+						if let Err(err) = notifier.check_repacks().await {
+							error!("Ошибка при поиске новых репаков: {:?}", err);
+						}
+					}
+					Ok(_) => {}
+					Err(err) => error!("Ошибка при сравнении индекса торрентов: {:?}", err),
+				}
+			}
+		});
+	}
+
+	let torrent_service = web::Data::new(torrent_service);
+	let webhook_service = web::Data::new(webhook_service);
+
+	// Индекс торрентов собирается выше при старте, поэтому первая проверка сразу видит новые репаки.
+	tokio::spawn(async move {
+		let mut interval = tokio::time::interval(std::time::Duration::from_secs(CHECK_INTERVAL_MINUTES * 60));
+		loop {
+			interval.tick().await;
+			notifier.run().await;
+		}
+	});
 
-	let torrent_service = web::Data::new(Arc::new(torrent_service));
+	let suggest_service = Arc::new(SuggestService::new(data.clone()));
+	{
+		let suggest_service = suggest_service.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(std::time::Duration::from_secs(REFRESH_INTERVAL_MINUTES * 60));
+			loop {
+				interval.tick().await;
+				if let Err(err) = suggest_service.rebuild().await {
+					error!("Ошибка при построении индекса подсказок: {:?}", err);
+				}
+			}
+		});
+	}
+	let suggest_service = web::Data::new(suggest_service);
+
+	{
+		let data = data.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(std::time::Duration::from_secs(SWEEP_INTERVAL_MINUTES * 60));
+			loop {
+				interval.tick().await;
+				sweep_yandex_tokens(&data).await;
+			}
+		});
+	}
+
+	let session_store = SqliteSessionStore::new(data.clone());
+	{
+		let session_store = session_store.clone();
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(std::time::Duration::from_secs(CLEANUP_INTERVAL_MINUTES * 60));
+			loop {
+				interval.tick().await;
+				match session_store.cleanup_expired().await {
+					Ok(count) if count > 0 => info!("Удалено истёкших сессий: {}", count),
+					Ok(_) => {}
+					Err(err) => error!("Ошибка при очистке сессий: {:?}", err),
+				}
+			}
+		});
+	}
+	let session_service = web::Data::new(Arc::new(session_store.clone()));
 
 	let server = HttpServer::new(move || {
 		App::new()
+			.wrap(SessionMetadata)
 			.wrap(IdentityMiddleware::default())
 			.wrap(
-				SessionMiddleware::builder(CookieSessionStore::default(), private_key.clone())
+				SessionMiddleware::builder(session_store.clone(), private_key.clone())
 					.cookie_name("app_session".to_owned())
-					.session_lifecycle(PersistentSession::default().session_ttl(Duration::hours(24)))
+					.session_lifecycle(
+						PersistentSession::default()
+							.session_ttl(Duration::hours(24))
+							.session_ttl_extension_policy(TtlExtensionPolicy::OnEveryRequest),
+					)
 					.cookie_secure(false)
 					.build(),
 			)
@@ -84,6 +263,14 @@ pub async fn run(tcp_listener: TcpListener, data: PrismaClient) -> Result<Server
 			.app_data(data_web.clone()) 
 			.app_data(games_middleware.clone()) 
 			.app_data(torrent_service.clone())
+			.app_data(metadata_service.clone())
+			.app_data(mirror_service.clone())
+			.app_data(image_service.clone())
+			.app_data(suggest_service.clone())
+			.app_data(auth_service.clone())
+			.app_data(session_service.clone())
+			.app_data(webhook_service.clone())
+			.app_data(save_service.clone())
 			.default_service(web::route().to(not_found))
 			.service(index)
 			.configure(get_config)
@@ -1,86 +1,448 @@
-use crate::model::dto::auth::LoginRequest;
+use crate::model::dto::auth::{IdentityInfo, LoginRequest, YandexTokenResponse};
 use crate::model::error::ErrorCode;
-use crate::model::error::ErrorCode::{AUTH001, INTERNAL001};
-use crate::prisma::{user, yandex_auth, PrismaClient};
+use crate::model::error::ErrorCode::{AUTH002, BADREQUEST, DATABASE002, INTERNAL001};
+use crate::prisma::{user, user_identity, PrismaClient};
+use crate::service::rbac::RbacService;
 use crate::service::user::UserService;
-use actix_web::web;
-use reqwest::Client;
-use serde_json::Value;
-use log::info;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use lazy_static::lazy_static;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use log::{error, info, warn};
 
+lazy_static! {
+  #[doc = "Yandex ID user info endpoint, overridable to point at a local stub"]
+  pub(crate) static ref YANDEX_LOGIN_URL: String = env::var("VEK_YANDEX_LOGIN_URL")
+    .unwrap_or_else(|_| "https://login.yandex.ru".to_string())
+    .trim_end_matches('/')
+    .to_string();
+
+  #[doc = "Yandex OAuth server, overridable to point at a local stub"]
+  pub(crate) static ref YANDEX_OAUTH_URL: String = env::var("VEK_YANDEX_OAUTH_URL")
+    .unwrap_or_else(|_| "https://oauth.yandex.ru".to_string())
+    .trim_end_matches('/')
+    .to_string();
+
+  #[doc = "Externally reachable address of the API, used to build provider callback URLs"]
+  pub(crate) static ref PUBLIC_URL: String = env::var("VEK_PUBLIC_URL")
+    .unwrap_or_else(|_| "http://127.0.0.1:8004".to_string())
+    .trim_end_matches('/')
+    .to_string();
+
+  pub(crate) static ref YANDEX_CLIENT_ID: Option<String> = env::var("VEK_YANDEX_CLIENT_ID").ok();
+  static ref YANDEX_CLIENT_SECRET: Option<String> = env::var("VEK_YANDEX_CLIENT_SECRET").ok();
+}
+
+#[doc = "Tokens expiring within this window are refreshed by the sweeper"]
+pub const REFRESH_MARGIN_HOURS: i64 = 24;
+pub const SWEEP_INTERVAL_MINUTES: u64 = 30;
+
+#[derive(Debug, Clone)]
+pub struct ProviderTokens {
+  pub token_type: String,
+  pub access_token: String,
+  pub refresh_token: Option<String>,
+  pub expires_in: Option<i32>,
+  pub scope: Option<String>,
+}
+
+#[doc = "Pending redirect login or link, kept in the session until the callback"]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthFlow {
+  pub provider: String,
+  pub state: String,
+  #[doc = "Set when an authenticated user is linking another account"]
+  pub link_user_id: Option<String>,
+}
+
+pub const AUTH_FLOW_SESSION_KEY: &str = "auth_flow";
+
+#[doc = "External account resolved by a provider"]
+#[derive(Debug, Clone)]
+pub struct ProviderIdentity {
+  #[doc = "Stable account id at the provider"]
+  pub subject: String,
+  pub login: Option<String>,
+  pub tokens: Option<ProviderTokens>,
+  #[doc = "Profile defaults for a user who has not set their own"]
+  pub display_name: Option<String>,
+  pub avatar_url: Option<String>,
+}
+
+#[async_trait::async_trait]
+pub trait AuthProvider: Send + Sync {
+  fn name(&self) -> &'static str;
+
+  #[doc = "Where to send the browser to start login, `None` if the provider is not configured"]
+  fn authorize_url(&self, state: &str, redirect_uri: &str) -> Option<String>;
+
+  #[doc = "Verify callback parameters and resolve the external account"]
+  async fn authenticate(
+    &self,
+    params: &HashMap<String, String>,
+    redirect_uri: &str,
+  ) -> Result<ProviderIdentity, ErrorCode>;
+}
+
+pub struct AuthService {
+  providers: Vec<Box<dyn AuthProvider>>,
+}
+
+impl AuthService {
+  pub fn new(providers: Vec<Box<dyn AuthProvider>>) -> Self {
+    AuthService { providers }
+  }
+
+  pub fn provider(&self, name: &str) -> Option<&dyn AuthProvider> {
+    self
+      .providers
+      .iter()
+      .find(|provider| provider.name() == name)
+      .map(|provider| provider.as_ref())
+  }
+}
+
+#[doc = "Callback URL registered with the provider"]
+pub fn redirect_uri(provider: &str) -> String {
+  format!("{}/api/auth/{}/callback", *PUBLIC_URL, provider)
+}
+
+#[doc = "Yandex login with tokens obtained by the launcher"]
 pub async fn authenticate(
   login_request: LoginRequest,
-  data: web::Data<PrismaClient>,
+  data: &PrismaClient,
+  auth_service: &AuthService,
 ) -> Result<user::Data, ErrorCode> {
-  let client = Client::new();
-  let yandex_response = client
-    .get(format!(
-      "https://login.yandex.ru/info?oauth_token={}",
-      login_request.access_token
-    ))
-    .send()
-    .await;
-
-  let yandex_response = match yandex_response {
-    Ok(resp) => {
-      if resp.status().is_success() {
-        resp
-      } else {
-        return Err(AUTH001);
+  let provider = auth_service.provider("yandex").ok_or(INTERNAL001)?;
+  let params = HashMap::from([
+    ("token_type".to_string(), login_request.token_type),
+    ("access_token".to_string(), login_request.access_token),
+    ("refresh_token".to_string(), login_request.refresh_token),
+    ("expires_in".to_string(), login_request.expires_in.to_string()),
+    ("scope".to_string(), login_request.scope),
+  ]);
+
+  let identity = provider.authenticate(&params, &redirect_uri(provider.name())).await?;
+  login_with_identity(data, provider.name(), identity).await
+}
+
+async fn find_identity(
+  data: &PrismaClient,
+  provider: &str,
+  subject: &str,
+) -> Result<Option<user_identity::Data>, ErrorCode> {
+  Ok(
+    data
+      .user_identity()
+      .find_first(vec![
+        user_identity::provider::equals(provider.to_string()),
+        user_identity::subject::equals(subject.to_string()),
+      ])
+      .exec()
+      .await?,
+  )
+}
+
+fn identity_params(identity: &ProviderIdentity) -> Vec<user_identity::SetParam> {
+  let mut params = vec![user_identity::login::set(identity.login.clone())];
+  if let Some(tokens) = &identity.tokens {
+    params.extend([
+      user_identity::token_type::set(Some(tokens.token_type.clone())),
+      user_identity::access_token::set(Some(tokens.access_token.clone())),
+      user_identity::refresh_token::set(tokens.refresh_token.clone()),
+      user_identity::expires_in::set(tokens.expires_in),
+      user_identity::scope::set(tokens.scope.clone()),
+      user_identity::issued_at::set(Some(Utc::now().into())),
+    ]);
+  }
+  params
+}
+
+async fn create_identity(
+  data: &PrismaClient,
+  user_id: &str,
+  provider: &str,
+  identity: &ProviderIdentity,
+) -> Result<user_identity::Data, ErrorCode> {
+  Ok(
+    data
+      .user_identity()
+      .create(
+        provider.to_string(),
+        identity.subject.clone(),
+        user::id::equals(user_id.to_string()),
+        identity_params(identity),
+      )
+      .exec()
+      .await?,
+  )
+}
+
+async fn update_identity(
+  data: &PrismaClient,
+  identity_id: &str,
+  identity: &ProviderIdentity,
+) -> Result<user_identity::Data, ErrorCode> {
+  Ok(
+    data
+      .user_identity()
+      .update(user_identity::id::equals(identity_id.to_string()), identity_params(identity))
+      .exec()
+      .await?,
+  )
+}
+
+#[doc = "Login for a new account: the provider login if free, otherwise `<provider>_<subject>`"]
+async fn new_user_login(
+  data: &PrismaClient,
+  provider: &str,
+  identity: &ProviderIdentity,
+) -> Result<String, ErrorCode> {
+  let fallback = format!("{}_{}", provider, identity.subject);
+  match &identity.login {
+    Some(login) if UserService::get_user_by_login(data, login).await?.is_none() => Ok(login.clone()),
+    _ => Ok(fallback),
+  }
+}
+
+#[doc = "Sign in with an external account, creating the user on first login"]
+pub async fn login_with_identity(
+  data: &PrismaClient,
+  provider: &str,
+  identity: ProviderIdentity,
+) -> Result<user::Data, ErrorCode> {
+  let user_data = match find_identity(data, provider, &identity.subject).await? {
+    Some(existing) => {
+      update_identity(data, &existing.id, &identity).await?;
+      UserService::update_user_timestamp(data, &existing.user_id).await?
+    }
+    None => {
+      let login = new_user_login(data, provider, &identity).await?;
+      let user_data = UserService::create_user(data, &login).await?;
+      create_identity(data, &user_data.id, provider, &identity).await?;
+      user_data
+    }
+  };
+  let user_data =
+    UserService::apply_provider_profile(data, user_data, identity.display_name, identity.avatar_url).await?;
+
+  // На первом запуске настроенный админ появляется только после входа.
+  if let Err(e) = RbacService::bootstrap_admins(data).await {
+    error!("Ошибка при назначении администраторов: {:?}", e);
+  }
+
+  info!("Аутентификация через {} завершена успешно для пользователя ID: {}", provider, user_data.id);
+  Ok(user_data)
+}
+
+#[doc = "Attach an external account to an existing user"]
+pub async fn link_identity(
+  data: &PrismaClient,
+  user_id: &str,
+  provider: &str,
+  identity: ProviderIdentity,
+) -> Result<Vec<IdentityInfo>, ErrorCode> {
+  match find_identity(data, provider, &identity.subject).await? {
+    Some(existing) if existing.user_id != user_id => {
+      return Err(BADREQUEST("This account is already linked to another user".to_string()));
+    }
+    Some(existing) => {
+      update_identity(data, &existing.id, &identity).await?;
+    }
+    None => {
+      let linked = list_identities(data, user_id).await?;
+      if linked.iter().any(|linked| linked.provider == provider) {
+        return Err(BADREQUEST(format!("Another {} account is already linked, unlink it first", provider)));
       }
+      create_identity(data, user_id, provider, &identity).await?;
     }
-    Err(_) => {
+  }
+
+  info!("Пользователь ID: {} привязал аккаунт {}", user_id, provider);
+  list_identities(data, user_id).await
+}
+
+#[doc = "Detach a provider from the user, keeping at least one way to sign in"]
+pub async fn unlink_identity(
+  data: &PrismaClient,
+  user_id: &str,
+  provider: &str,
+) -> Result<Vec<IdentityInfo>, ErrorCode> {
+  let linked = list_identities(data, user_id).await?;
+  if !linked.iter().any(|linked| linked.provider == provider) {
+    return Err(DATABASE002);
+  }
+  if linked.len() <= 1 {
+    return Err(BADREQUEST("Cannot unlink the only sign-in method".to_string()));
+  }
+
+  data
+    .user_identity()
+    .delete_many(vec![
+      user_identity::user_id::equals(user_id.to_string()),
+      user_identity::provider::equals(provider.to_string()),
+    ])
+    .exec()
+    .await?;
+
+  info!("Пользователь ID: {} отвязал аккаунт {}", user_id, provider);
+  list_identities(data, user_id).await
+}
+
+pub async fn list_identities(data: &PrismaClient, user_id: &str) -> Result<Vec<IdentityInfo>, ErrorCode> {
+  let identities = data
+    .user_identity()
+    .find_many(vec![user_identity::user_id::equals(user_id.to_string())])
+    .exec()
+    .await?;
+
+  Ok(
+    identities
+      .into_iter()
+      .map(|identity| IdentityInfo {
+        provider: identity.provider,
+        subject: identity.subject,
+        login: identity.login,
+        linked_at: identity.created_at.to_rfc3339(),
+      })
+      .collect(),
+  )
+}
+
+#[doc = "Moment the stored access token stops being valid, `None` if its issue time or lifetime is unknown"]
+pub fn token_expires_at(identity: &user_identity::Data) -> Option<DateTime<FixedOffset>> {
+  match (identity.issued_at, identity.expires_in) {
+    (Some(issued_at), Some(expires_in)) => Some(issued_at + Duration::seconds(expires_in as i64)),
+    _ => None,
+  }
+}
+
+#[doc = "Exchange the stored Yandex refresh token for a new token pair and update the row"]
+pub async fn refresh_yandex_token(
+  data: &PrismaClient,
+  identity: &user_identity::Data,
+) -> Result<user_identity::Data, ErrorCode> {
+  let (client_id, client_secret) = match (YANDEX_CLIENT_ID.as_ref(), YANDEX_CLIENT_SECRET.as_ref()) {
+    (Some(client_id), Some(client_secret)) => (client_id, client_secret),
+    _ => {
+      warn!("VEK_YANDEX_CLIENT_ID/VEK_YANDEX_CLIENT_SECRET не заданы, обновление токена невозможно");
       return Err(INTERNAL001);
     }
   };
+  let refresh_token = identity.refresh_token.clone().ok_or(AUTH002)?;
 
-  let yandex_info: Value = serde_json::from_str(&yandex_response.text().await.map_err(|_| INTERNAL001)?)
+  let response = Client::new()
+    .post(format!("{}/token", *YANDEX_OAUTH_URL))
+    .form(&[
+      ("grant_type", "refresh_token"),
+      ("refresh_token", refresh_token.as_str()),
+      ("client_id", client_id.as_str()),
+      ("client_secret", client_secret.as_str()),
+    ])
+    .send()
+    .await
     .map_err(|_| INTERNAL001)?;
 
-  let login = yandex_info["login"].as_str().ok_or(AUTH001)?;
+  // Yandex отвечает 400 invalid_grant, если refresh token отозван или истёк.
+  if response.status() == StatusCode::BAD_REQUEST || response.status() == StatusCode::UNAUTHORIZED {
+    return Err(AUTH002);
+  }
+  if !response.status().is_success() {
+    warn!("Yandex OAuth вернул статус {} при обновлении токена", response.status());
+    return Err(INTERNAL001);
+  }
 
-  let user_data = if let Some(user) = UserService::get_user_by_login(&data, login).await.map_err(|_| INTERNAL001)? {
-    UserService::update_user_timestamp(&data, &user.id).await.map_err(|_| INTERNAL001)?
-  } else {
-    UserService::create_user(&data, login).await.map_err(|_| INTERNAL001)?
-  };
+  let token: YandexTokenResponse = response.json().await.map_err(|_| INTERNAL001)?;
+
+  update_identity(
+    data,
+    &identity.id,
+    &ProviderIdentity {
+      subject: identity.subject.clone(),
+      login: identity.login.clone(),
+      display_name: None,
+      avatar_url: None,
+      tokens: Some(ProviderTokens {
+        token_type: token.token_type,
+        access_token: token.access_token,
+        refresh_token: token.refresh_token.or(Some(refresh_token)),
+        expires_in: Some(token.expires_in),
+        scope: token.scope.or_else(|| identity.scope.clone()),
+      }),
+    },
+  )
+  .await
+}
+
+#[doc = "Whether the Yandex OAuth client is configured, without it tokens cannot be refreshed"]
+fn yandex_refresh_configured() -> bool {
+  YANDEX_CLIENT_ID.is_some() && YANDEX_CLIENT_SECRET.is_some()
+}
+
+#[doc = "Refresh Yandex tokens close to expiry and clear the ones Yandex no longer accepts"]
+pub async fn sweep_yandex_tokens(data: &PrismaClient) {
+  // Без клиента OAuth обновить токены нельзя, и это не повод их удалять.
+  if !yandex_refresh_configured() {
+    return;
+  }
 
-  if let Some(auth) = data
-    .yandex_auth()
-    .find_first(vec![yandex_auth::user_id::equals(Some(user_data.id.clone()))])
+  let identities = match data
+    .user_identity()
+    .find_many(vec![
+      user_identity::provider::equals("yandex".to_string()),
+      user_identity::access_token::not(None),
+    ])
     .exec()
     .await
-    .map_err(|_| INTERNAL001)? 
   {
-    data.yandex_auth()
-      .update(
-        yandex_auth::access_token::equals(auth.access_token.clone()),
-        vec![
-            yandex_auth::token_type::set(login_request.token_type.clone()),
-            yandex_auth::access_token::set(login_request.access_token.clone()),
-            yandex_auth::expires_in::set(login_request.expires_in),
-            yandex_auth::refresh_token::set(login_request.refresh_token.clone()),
-            yandex_auth::scope::set(login_request.scope.clone()),
-        ],
-      )
-      .exec()
-      .await
-      .map_err(|_| INTERNAL001)?;
-  } else {
-    data.yandex_auth()
-      .create(
-        login_request.token_type.clone(),
-        login_request.access_token.clone(),
-        login_request.expires_in,
-        login_request.refresh_token.clone(),
-        login_request.scope.clone(),
-        vec![yandex_auth::user::connect(user::id::equals(user_data.id.clone()))],
-      )
-      .exec()
-      .await
-      .map_err(|_| INTERNAL001)?;
+    Ok(identities) => identities,
+    Err(e) => {
+      warn!("Не удалось загрузить токены Yandex: {:?}", e);
+      return;
+    }
+  };
+
+  let now = Utc::now();
+  let threshold = now + Duration::hours(REFRESH_MARGIN_HOURS);
+  for identity in identities {
+    // Токен с неизвестным временем выдачи мог истечь когда угодно, поэтому обновляется сразу.
+    let expires_at = token_expires_at(&identity);
+    if expires_at.map(|expires_at| expires_at > threshold).unwrap_or(false) {
+      continue;
+    }
+
+    match refresh_yandex_token(data, &identity).await {
+      Ok(_) => info!("Токен Yandex обновлён для пользователя ID: {}", identity.user_id),
+      Err(AUTH002) => {
+        info!("Токен Yandex отозван, очищаем для пользователя ID: {}", identity.user_id);
+        invalidate_tokens(data, &identity).await;
+      }
+      Err(e) if expires_at.map(|expires_at| expires_at <= now).unwrap_or(false) => {
+        warn!("Не удалось обновить истёкший токен Yandex для пользователя ID: {}: {:?}", identity.user_id, e);
+        invalidate_tokens(data, &identity).await;
+      }
+      Err(e) => warn!("Не удалось обновить токен Yandex для пользователя ID: {}: {:?}", identity.user_id, e),
+    }
   }
+}
 
-  info!("Аутентификация завершена успешно для пользователя ID: {}", user_data.id);
-  Ok(user_data)
+#[doc = "Drop stored tokens but keep the identity so the user can still sign in"]
+async fn invalidate_tokens(data: &PrismaClient, identity: &user_identity::Data) {
+  if let Err(e) = data
+    .user_identity()
+    .update(
+      user_identity::id::equals(identity.id.clone()),
+      vec![
+        user_identity::access_token::set(None),
+        user_identity::refresh_token::set(None),
+        user_identity::expires_in::set(None),
+      ],
+    )
+    .exec()
+    .await
+  {
+    warn!("Не удалось очистить токен Yandex: {:?}", e);
+  }
 }
@@ -1,83 +1,185 @@
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use regex::Regex;
 use reqwest::Client;
 use serde_json::Value;
 use crate::model::dto::games::*;
+use crate::modules::cursor::PageCursor;
+use crate::modules::metadata::rawg::BASE_URL;
 
-const BASE_URL: &str = "https://api.rawg.io/api/";
+pub(crate) const DEFAULT_PAGE_SIZE: usize = 10;
+const MAX_PAGE_SIZE: usize = 40;
+const ORDERING_FIELDS: [&str; 7] = ["name", "released", "added", "created", "updated", "rating", "metacritic"];
+
+lazy_static! {
+    static ref FILTER_LIST_REGEX: Regex = Regex::new(r"^[a-z0-9-]+(,[a-z0-9-]+)*$").unwrap();
+}
 
 pub struct GamesService;
 
 impl GamesService {
-    pub async fn get_game_list(api_key: String, request: GameListRequest) -> Result<Value, reqwest::Error> {
-        let url = if let Some(next_url) = request.next {
-            next_url
-        } else {
-            format!(
-                "{}games?key={}&page={}&page_size=10&play_on_desktop=true",
-                BASE_URL,
-                api_key,
-                request.page.unwrap_or(1)
-            )
-        };
+    pub fn game_list_path() -> String {
+        "games".to_string()
+    }
 
+    pub fn game_screenshots_path(id: i32) -> String {
+        format!("games/{}/screenshots", id)
+    }
+
+    pub fn game_movies_path(id: i32) -> String {
+        format!("games/{}/movies", id)
+    }
+
+    pub fn game_relation_path(id: i32, relation: &str) -> String {
+        format!("games/{}/{}", id, relation)
+    }
+
+    async fn fetch(api_key: &str, path: &str, params: &[(String, String)]) -> Result<Value, reqwest::Error> {
         let client = Client::new();
-        let response = client.get(&url).send().await?;
+        let response = client
+            .get(format!("{}{}", BASE_URL.as_str(), path))
+            .query(&[("key", api_key)])
+            .query(params)
+            .send()
+            .await?
+            .error_for_status()?;
         let json = response.json::<Value>().await?;
         Ok(json)
     }
 
-    pub async fn search_game(api_key: String, request: GameSearchRequest) -> Result<Value, reqwest::Error> {
-        let url = if let Some(next_url) = request.next {
-            next_url
-        } else {
-            format!(
-                "{}games?search={}&key={}",
-                BASE_URL,
-                request.query,
-                api_key
-            )
+    /// Проверяет фильтры списка игр и переводит их в параметры запроса RAWG.
+    pub fn game_list_params(request: &GameListRequest) -> Result<Vec<(String, String)>, String> {
+        let mut params = Self::page_params(request.page, request.page_size)?;
+        params.push(("play_on_desktop".to_string(), "true".to_string()));
+
+        let lists = [
+            ("genres", &request.genres),
+            ("tags", &request.tags),
+            ("platforms", &request.platforms),
+            ("developers", &request.developers),
+            ("publishers", &request.publishers),
+        ];
+        for (name, value) in lists {
+            if let Some(value) = value {
+                let value = value.trim().to_lowercase();
+                if !FILTER_LIST_REGEX.is_match(&value) {
+                    return Err(format!("{} must be a comma separated list of ids or slugs", name));
+                }
+                params.push((name.to_string(), value));
+            }
+        }
+
+        if request.released_from.is_some() || request.released_to.is_some() {
+            let parse = |value: &Option<String>, default: &str, name: &str| -> Result<NaiveDate, String> {
+                NaiveDate::parse_from_str(value.as_deref().unwrap_or(default), "%Y-%m-%d")
+                    .map_err(|_| format!("{} must be a date in YYYY-MM-DD format", name))
+            };
+            let from = parse(&request.released_from, "1900-01-01", "released_from")?;
+            let to = parse(&request.released_to, "2100-12-31", "released_to")?;
+            if from > to {
+                return Err("released_from must not be later than released_to".to_string());
+            }
+            params.push(("dates".to_string(), format!("{},{}", from, to)));
+        }
+
+        if request.metacritic_min.is_some() || request.metacritic_max.is_some() {
+            let min = request.metacritic_min.unwrap_or(0);
+            let max = request.metacritic_max.unwrap_or(100);
+            if max > 100 || min > max {
+                return Err("metacritic range must be within 0..100 and min must not exceed max".to_string());
+            }
+            params.push(("metacritic".to_string(), format!("{},{}", min, max)));
+        }
+
+        if let Some(ordering) = &request.ordering {
+            if !ORDERING_FIELDS.contains(&ordering.strip_prefix('-').unwrap_or(ordering)) {
+                return Err(format!("ordering must be one of: {}", ORDERING_FIELDS.join(", ")));
+            }
+            params.push(("ordering".to_string(), ordering.clone()));
+        }
+
+        Ok(params)
+    }
+
+    pub async fn get_game_list(api_key: String, params: Vec<(String, String)>) -> Result<Value, reqwest::Error> {
+        Self::fetch(&api_key, &Self::game_list_path(), &params).await
+    }
+
+    pub async fn search_game(api_key: String, request: GameSearchRequest, cursor: Option<PageCursor>) -> Result<Value, reqwest::Error> {
+        let params = match cursor {
+            Some(cursor) => cursor.params,
+            None => vec![("search".to_string(), request.query)],
         };
 
-        let client = Client::new();
-        let response = client.get(&url).send().await?;
-        let json = response.json::<Value>().await?;
-        Ok(json)
+        Self::fetch(&api_key, &Self::game_list_path(), &params).await
+    }
+
+    /// Параметры страницы для справочников RAWG (жанры, платформы, теги и т.д.).
+    pub fn page_params(page: Option<usize>, page_size: Option<usize>) -> Result<Vec<(String, String)>, String> {
+        let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        if page_size == 0 || page_size > MAX_PAGE_SIZE {
+            return Err(format!("page_size must be between 1 and {}", MAX_PAGE_SIZE));
+        }
+
+        Ok(vec![
+            ("page".to_string(), page.unwrap_or(1).max(1).to_string()),
+            ("page_size".to_string(), page_size.to_string()),
+        ])
+    }
+
+    pub async fn get_catalog_list(api_key: String, resource: &str, params: Vec<(String, String)>) -> Result<Value, reqwest::Error> {
+        Self::fetch(&api_key, resource, &params).await
+    }
+
+    pub async fn get_catalog_details(api_key: String, resource: &str, id: i32) -> Result<Value, reqwest::Error> {
+        Self::fetch(&api_key, &format!("{}/{}", resource, id), &[]).await
     }
 
     pub async fn get_game_details(api_key: String, request: GameDetailsRequest) -> Result<Value, reqwest::Error> {
-        let url = format!("{}games/{}?key={}", BASE_URL, request.id, api_key);
+        Self::fetch(&api_key, &format!("games/{}", request.id), &[]).await
+    }
 
-        let client = Client::new();
-        let response = client.get(&url).send().await?;
-        let json = response.json::<Value>().await?;
-        Ok(json)
+    pub async fn get_game_screenshots(api_key: String, id: i32, request: GameScreenshotsRequest, cursor: Option<PageCursor>) -> Result<Value, reqwest::Error> {
+        let params = match cursor {
+            Some(cursor) => cursor.params,
+            None => vec![("page".to_string(), request.page.unwrap_or(1).to_string())],
+        };
+
+        Self::fetch(&api_key, &Self::game_screenshots_path(id), &params).await
     }
 
-    pub async fn get_game_screenshots(api_key: String, id: i32, request: GameScreenshotsRequest) -> Result<Value, reqwest::Error> {
-        let url = format!(
-            "{}games/{}/screenshots?key={}&page={}",
-            BASE_URL,
-            id,
-            api_key,
-            request.page.unwrap_or(1)
-        );
+    pub async fn get_game_movies(api_key: String, id: i32, request: GameMoviesRequest, cursor: Option<PageCursor>) -> Result<Value, reqwest::Error> {
+        let params = match cursor {
+            Some(cursor) => cursor.params,
+            None => vec![("page".to_string(), request.page.unwrap_or(1).to_string())],
+        };
 
-        let client = Client::new();
-        let response = client.get(&url).send().await?;
-        let json = response.json::<Value>().await?;
-        Ok(json)
+        Self::fetch(&api_key, &Self::game_movies_path(id), &params).await
     }
 
-    pub async fn get_game_movies(api_key: String, id: i32, request: GameMoviesRequest) -> Result<Value, reqwest::Error> {
-        let url = format!("{}games/{}/movies?key={}&page={}",
-            BASE_URL,
-            id,
-            api_key,
-            request.page.unwrap_or(1)
-        );
+    pub async fn get_game_relation(api_key: String, id: i32, relation: &str, params: Vec<(String, String)>) -> Result<Value, reqwest::Error> {
+        Self::fetch(&api_key, &Self::game_relation_path(id, relation), &params).await
+    }
+}
 
-        let client = Client::new();
-        let response = client.get(&url).send().await?;
-        let json = response.json::<Value>().await?;
-        Ok(json)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_request(ordering: &str) -> GameListRequest {
+        serde_json::from_value(serde_json::json!({ "ordering": ordering })).unwrap()
+    }
+
+    #[test]
+    fn accepts_ascending_and_descending_ordering() {
+        assert!(GamesService::game_list_params(&list_request("rating")).is_ok());
+        assert!(GamesService::game_list_params(&list_request("-released")).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_ordering() {
+        assert!(GamesService::game_list_params(&list_request("--name")).is_err());
+        assert!(GamesService::game_list_params(&list_request("-")).is_err());
+        assert!(GamesService::game_list_params(&list_request("price")).is_err());
     }
 }
@@ -0,0 +1,346 @@
+use crate::modules::helpers::random_token;
+use crate::modules::images::{cache_dir, content_hash, is_allowed_host, is_valid_hash, resolve, SOURCE_FILE};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader, Limits};
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+const ORIGINAL_FILE: &str = "original";
+const TEMP_EXTENSION: &str = "tmp";
+const MAX_DIMENSION: u32 = 3840;
+/// Размеры, до которых округляются `w` и `h`, чтобы у изображения был ограниченный набор вариантов.
+const SIZE_BUCKETS: [u32; 10] = [64, 128, 256, 384, 512, 768, 1024, 1280, 1920, MAX_DIMENSION];
+/// Сколько готовых вариантов одного изображения хранится на диске.
+const MAX_VARIANTS: usize = 24;
+/// Ограничения декодера: маленький файл не должен разворачиваться в гигабайты пикселей.
+const MAX_SOURCE_DIMENSION: u32 = 16384;
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+pub const MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+const AVATAR_DIMENSION: u32 = 512;
+
+#[derive(Debug)]
+pub enum ImageError {
+	NotFound,
+	BadRequest(String),
+	Upstream(String),
+	Internal(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+	WebP,
+	Jpeg,
+	Png,
+}
+
+impl OutputFormat {
+	pub fn parse(value: &str) -> Option<Self> {
+		match value.to_lowercase().as_str() {
+			"webp" => Some(OutputFormat::WebP),
+			"jpg" | "jpeg" => Some(OutputFormat::Jpeg),
+			"png" => Some(OutputFormat::Png),
+			_ => None,
+		}
+	}
+
+	fn image_format(self) -> ImageFormat {
+		match self {
+			OutputFormat::WebP => ImageFormat::WebP,
+			OutputFormat::Jpeg => ImageFormat::Jpeg,
+			OutputFormat::Png => ImageFormat::Png,
+		}
+	}
+
+	fn extension(self) -> &'static str {
+		self.image_format().extensions_str()[0]
+	}
+}
+
+pub struct ImageVariant {
+	pub width: Option<u32>,
+	pub height: Option<u32>,
+	pub format: Option<OutputFormat>,
+}
+
+/// Ближайший размер из `SIZE_BUCKETS`, не меньше запрошенного.
+fn snap(dimension: u32) -> Option<u32> {
+	SIZE_BUCKETS.iter().copied().find(|bucket| *bucket >= dimension)
+}
+
+fn decode(bytes: &[u8]) -> Result<DynamicImage, String> {
+	let mut reader = ImageReader::new(Cursor::new(bytes))
+		.with_guessed_format()
+		.map_err(|e| e.to_string())?;
+	let mut limits = Limits::default();
+	limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+	limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+	limits.max_alloc = Some(MAX_DECODE_ALLOC);
+	reader.limits(limits);
+	reader.decode().map_err(|e| e.to_string())
+}
+
+/// Пишет файл через временный и `rename`, чтобы падение или параллельный запрос
+/// не оставили по итоговому пути обрезанный файл.
+async fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+	let token = random_token(8).map_err(|e| std::io::Error::other(e.to_string()))?;
+	let temp = path.with_extension(format!("{}.{}", token, TEMP_EXTENSION));
+	let result = match tokio::fs::write(&temp, bytes).await {
+		Ok(()) => tokio::fs::rename(&temp, path).await,
+		Err(e) => Err(e),
+	};
+	if result.is_err() {
+		let _ = tokio::fs::remove_file(&temp).await;
+	}
+	result
+}
+
+/// Сколько вариантов изображения уже лежит в каталоге, кроме оригинала и ссылки.
+async fn variant_count(dir: &Path) -> usize {
+	let Ok(mut entries) = tokio::fs::read_dir(dir).await else { return 0 };
+	let mut count = 0;
+	while let Ok(Some(entry)) = entries.next_entry().await {
+		let path = entry.path();
+		let name = entry.file_name();
+		let is_variant = name != ORIGINAL_FILE
+			&& name != SOURCE_FILE
+			&& path.extension().map(|extension| extension != TEMP_EXTENSION).unwrap_or(false);
+		if is_variant {
+			count += 1;
+		}
+	}
+	count
+}
+
+pub struct ImageService {
+	client: Client,
+}
+
+impl ImageService {
+	pub fn new() -> Self {
+		// Редиректы проверяются по тому же списку хостов, что и исходная ссылка.
+		let policy = Policy::custom(|attempt| {
+			if attempt.previous().len() < 5 && is_allowed_host(attempt.url().host_str()) {
+				attempt.follow()
+			} else {
+				attempt.stop()
+			}
+		});
+
+		ImageService {
+			client: Client::builder().redirect(policy).build().unwrap_or_default(),
+		}
+	}
+
+	fn image_dir(hash: &str) -> Result<PathBuf, ImageError> {
+		cache_dir()
+			.map(|dir| dir.join(hash))
+			.map_err(|e| ImageError::Internal(e.to_string()))
+	}
+
+	/// Возвращает оригинал из дискового кэша, при необходимости загружая его.
+	async fn original(&self, hash: &str) -> Result<Vec<u8>, ImageError> {
+		let dir = Self::image_dir(hash)?;
+		let path = dir.join(ORIGINAL_FILE);
+		if let Ok(bytes) = tokio::fs::read(&path).await {
+			return Ok(bytes);
+		}
+
+		let url = resolve(hash).await.ok_or(ImageError::NotFound)?;
+		let mut response = self
+			.client
+			.get(&url)
+			.send()
+			.await
+			.and_then(|response| response.error_for_status())
+			.map_err(|e| ImageError::Upstream(e.to_string()))?;
+
+		if response.content_length().unwrap_or(0) as usize > MAX_IMAGE_BYTES {
+			return Err(ImageError::Upstream("Image is too large".to_string()));
+		}
+
+		// Content-Length может не быть, поэтому размер проверяется по мере чтения.
+		let mut bytes = Vec::new();
+		while let Some(chunk) = response.chunk().await.map_err(|e| ImageError::Upstream(e.to_string()))? {
+			if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+				return Err(ImageError::Upstream("Image is too large".to_string()));
+			}
+			bytes.extend_from_slice(&chunk);
+		}
+		image::guess_format(&bytes).map_err(|_| ImageError::Upstream("Upstream did not return an image".to_string()))?;
+
+		tokio::fs::create_dir_all(&dir)
+			.await
+			.map_err(|e| ImageError::Internal(e.to_string()))?;
+		write_atomic(&path, &bytes)
+			.await
+			.map_err(|e| ImageError::Internal(e.to_string()))?;
+		// Ссылка сохраняется на диск только для изображений, которые действительно запрашивали.
+		if let Err(e) = write_atomic(&dir.join(SOURCE_FILE), url.as_bytes()).await {
+			log::warn!("Не удалось сохранить ссылку изображения {}: {}", hash, e);
+		}
+
+		Ok(bytes)
+	}
+
+	/// Отдаёт изображение с изменённым размером и форматом. Размеры округляются вверх
+	/// до `SIZE_BUCKETS`, готовые варианты кэшируются на диске рядом с оригиналом,
+	/// но не больше `MAX_VARIANTS` на изображение.
+	pub async fn get_image(&self, hash: &str, variant: ImageVariant) -> Result<(Vec<u8>, &'static str), ImageError> {
+		if !is_valid_hash(hash) {
+			return Err(ImageError::NotFound);
+		}
+		for dimension in [variant.width, variant.height].into_iter().flatten() {
+			if dimension == 0 || dimension > MAX_DIMENSION {
+				return Err(ImageError::BadRequest(format!("w and h must be between 1 and {}", MAX_DIMENSION)));
+			}
+		}
+		let variant = ImageVariant {
+			width: variant.width.and_then(snap),
+			height: variant.height.and_then(snap),
+			format: variant.format,
+		};
+
+		let original = self.original(hash).await?;
+		let source_format = image::guess_format(&original).map_err(|e| ImageError::Internal(e.to_string()))?;
+
+		if variant.width.is_none() && variant.height.is_none() && variant.format.is_none() {
+			return Ok((original, source_format.to_mime_type()));
+		}
+
+		let format = variant.format.map(OutputFormat::image_format).unwrap_or(source_format);
+		let extension = variant.format.map(OutputFormat::extension).unwrap_or_else(|| source_format.extensions_str()[0]);
+		let dir = Self::image_dir(hash)?;
+		let path = dir.join(format!(
+			"{}x{}.{}",
+			variant.width.unwrap_or(0),
+			variant.height.unwrap_or(0),
+			extension
+		));
+
+		if let Ok(bytes) = tokio::fs::read(&path).await {
+			return Ok((bytes, format.to_mime_type()));
+		}
+		if variant_count(&dir).await >= MAX_VARIANTS {
+			return Err(ImageError::BadRequest("Too many variants of this image, use one of the sizes already requested".to_string()));
+		}
+
+		let (width, height) = (variant.width, variant.height);
+		let bytes = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, String> {
+			let mut img = decode(&original)?;
+			if width.is_some() || height.is_some() {
+				img = img.resize(
+					width.unwrap_or(MAX_DIMENSION).min(img.width()),
+					height.unwrap_or(MAX_DIMENSION).min(img.height()),
+					FilterType::Lanczos3,
+				);
+			}
+			if format == ImageFormat::Jpeg {
+				img = image::DynamicImage::ImageRgb8(img.to_rgb8());
+			}
+
+			let mut output = Cursor::new(Vec::new());
+			img.write_to(&mut output, format).map_err(|e| e.to_string())?;
+			Ok(output.into_inner())
+		})
+		.await
+		.map_err(|e| ImageError::Internal(e.to_string()))?
+		.map_err(ImageError::Internal)?;
+
+		if let Err(e) = write_atomic(&path, &bytes).await {
+			log::warn!("Не удалось сохранить вариант изображения {}: {}", hash, e);
+		}
+
+		Ok((bytes, format.to_mime_type()))
+	}
+
+	/// Сохраняет загруженный аватар в кэш изображений и возвращает его хэш.
+	/// Файл перекодируется в PNG не больше 512x512, так что наружу отдаётся
+	/// только то, что удалось декодировать.
+	pub async fn store_avatar(&self, bytes: Vec<u8>) -> Result<String, ImageError> {
+		if bytes.len() > MAX_UPLOAD_BYTES {
+			return Err(ImageError::BadRequest("Image is too large".to_string()));
+		}
+		match image::guess_format(&bytes) {
+			Ok(ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP) => {}
+			_ => return Err(ImageError::BadRequest("Avatar must be a JPEG, PNG or WebP image".to_string())),
+		}
+
+		let encoded = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, String> {
+			let mut img = decode(&bytes)?;
+			if img.width() > AVATAR_DIMENSION || img.height() > AVATAR_DIMENSION {
+				img = img.resize(AVATAR_DIMENSION, AVATAR_DIMENSION, FilterType::Lanczos3);
+			}
+
+			let mut output = Cursor::new(Vec::new());
+			img.write_to(&mut output, ImageFormat::Png).map_err(|e| e.to_string())?;
+			Ok(output.into_inner())
+		})
+		.await
+		.map_err(|e| ImageError::Internal(e.to_string()))?
+		.map_err(ImageError::BadRequest)?;
+
+		let hash = content_hash(&encoded);
+		let dir = Self::image_dir(&hash)?;
+		tokio::fs::create_dir_all(&dir)
+			.await
+			.map_err(|e| ImageError::Internal(e.to_string()))?;
+		write_atomic(&dir.join(ORIGINAL_FILE), &encoded)
+			.await
+			.map_err(|e| ImageError::Internal(e.to_string()))?;
+
+		Ok(hash)
+	}
+}
+
+impl Default for ImageService {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dimensions_snap_up_to_buckets() {
+		assert_eq!(snap(1), Some(64));
+		assert_eq!(snap(64), Some(64));
+		assert_eq!(snap(65), Some(128));
+		assert_eq!(snap(1000), Some(1024));
+		assert_eq!(snap(MAX_DIMENSION), Some(MAX_DIMENSION));
+		assert_eq!(snap(MAX_DIMENSION + 1), None);
+	}
+
+	#[test]
+	fn decoder_rejects_oversized_images() {
+		let mut small = Cursor::new(Vec::new());
+		DynamicImage::new_luma8(16, 16).write_to(&mut small, ImageFormat::Png).unwrap();
+		assert!(decode(small.get_ref()).is_ok());
+
+		let mut wide = Cursor::new(Vec::new());
+		DynamicImage::new_luma8(MAX_SOURCE_DIMENSION + 1, 1).write_to(&mut wide, ImageFormat::Png).unwrap();
+		assert!(decode(wide.get_ref()).is_err());
+	}
+
+	#[tokio::test]
+	async fn atomic_writes_leave_no_temp_files() {
+		let dir = std::env::temp_dir().join(format!("vek-images-{}", random_token(8).unwrap()));
+		tokio::fs::create_dir_all(&dir).await.unwrap();
+
+		write_atomic(&dir.join("64x0.png"), b"first").await.unwrap();
+		write_atomic(&dir.join("64x0.png"), b"second").await.unwrap();
+		write_atomic(&dir.join(ORIGINAL_FILE), b"original").await.unwrap();
+
+		assert_eq!(tokio::fs::read(dir.join("64x0.png")).await.unwrap(), b"second");
+		assert_eq!(variant_count(&dir).await, 1);
+		let mut entries = tokio::fs::read_dir(&dir).await.unwrap();
+		while let Some(entry) = entries.next_entry().await.unwrap() {
+			assert_ne!(entry.path().extension().and_then(|e| e.to_str()), Some(TEMP_EXTENSION));
+		}
+		tokio::fs::remove_dir_all(&dir).await.unwrap();
+	}
+}
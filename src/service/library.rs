@@ -0,0 +1,337 @@
+use crate::model::dto::library::{
+	CreateLibraryEntryRequest, LibraryEntryInfo, LibraryStatus, PlaySessionInfo, UpdateLibraryEntryRequest, WishlistItem,
+};
+use crate::model::error::ErrorCode;
+use crate::prisma::{library_entry, play_session, user, wishlist_game, PrismaClient};
+use chrono::{Duration, Utc};
+use lazy_static::lazy_static;
+use log::info;
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use prisma_client_rust::Direction;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Окно для `recent_playtime`.
+pub const RECENT_PLAYTIME_DAYS: i64 = 14;
+/// Сессия, которую забыли остановить, засчитывается не больше чем на сутки.
+const MAX_SESSION_SECONDS: i64 = 24 * 3600;
+const MAX_VERSION_LENGTH: usize = 64;
+
+lazy_static! {
+	static ref PATH_HASH_REGEX: Regex = Regex::new(r"^[0-9a-f]{16,128}$").unwrap();
+}
+
+pub struct LibraryService;
+
+impl LibraryService {
+	fn info(entry: library_entry::Data, recent_playtime: i32) -> LibraryEntryInfo {
+		LibraryEntryInfo {
+			status: LibraryStatus::parse(&entry.status).unwrap_or(LibraryStatus::Installed),
+			id: entry.id,
+			game_id: entry.game_id,
+			installed_version: entry.installed_version,
+			install_path_hash: entry.install_path_hash,
+			added_at: entry.added_at.to_rfc3339(),
+			updated_at: entry.updated_at.to_rfc3339(),
+			last_played: entry.last_played.map(|last_played| last_played.to_rfc3339()),
+			playtime: entry.playtime,
+			recent_playtime,
+		}
+	}
+
+	fn session_info(session: play_session::Data, game_id: i32) -> PlaySessionInfo {
+		PlaySessionInfo {
+			id: session.id,
+			game_id,
+			started_at: session.started_at.to_rfc3339(),
+			ended_at: session.ended_at.map(|ended_at| ended_at.to_rfc3339()),
+			duration: session.duration,
+		}
+	}
+
+	/// Пустая строка очищает поле.
+	fn validate_version(version: Option<String>) -> Result<Option<Option<String>>, ErrorCode> {
+		let Some(version) = version else { return Ok(None) };
+		let version = version.trim().to_string();
+		if version.chars().count() > MAX_VERSION_LENGTH {
+			return Err(ErrorCode::BADREQUEST(format!("installed_version must be at most {} characters", MAX_VERSION_LENGTH)));
+		}
+		Ok(Some(Some(version).filter(|version| !version.is_empty())))
+	}
+
+	fn validate_path_hash(path_hash: Option<String>) -> Result<Option<Option<String>>, ErrorCode> {
+		let Some(path_hash) = path_hash else { return Ok(None) };
+		if path_hash.is_empty() {
+			return Ok(Some(None));
+		}
+		if !PATH_HASH_REGEX.is_match(&path_hash) {
+			return Err(ErrorCode::BADREQUEST("install_path_hash must be a lowercase hex digest".to_string()));
+		}
+		Ok(Some(Some(path_hash)))
+	}
+
+	/// Сумма завершённых сессий за последние `RECENT_PLAYTIME_DAYS` дней по записям библиотеки.
+	async fn recent_playtime(client: &PrismaClient, user_id: &str) -> Result<HashMap<String, i32>, ErrorCode> {
+		let since = Utc::now() - Duration::days(RECENT_PLAYTIME_DAYS);
+		let sessions = client
+			.play_session()
+			.find_many(vec![
+				play_session::entry::is(vec![library_entry::user_id::equals(user_id.to_string())]),
+				play_session::started_at::gte(since.into()),
+				play_session::duration::not(None),
+			])
+			.exec()
+			.await?;
+
+		let mut recent = HashMap::new();
+		for session in sessions {
+			*recent.entry(session.entry_id).or_insert(0) += session.duration.unwrap_or(0);
+		}
+		Ok(recent)
+	}
+
+	async fn find_entry(client: &PrismaClient, user_id: &str, game_id: i32) -> Result<library_entry::Data, ErrorCode> {
+		client
+			.library_entry()
+			.find_unique(library_entry::user_id_game_id(user_id.to_string(), game_id))
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)
+	}
+
+	pub async fn list_entries(
+		client: &PrismaClient,
+		user_id: &str,
+		status: Option<LibraryStatus>,
+	) -> Result<Vec<LibraryEntryInfo>, ErrorCode> {
+		let mut filters = vec![library_entry::user_id::equals(user_id.to_string())];
+		if let Some(status) = status {
+			filters.push(library_entry::status::equals(status.as_str().to_string()));
+		}
+
+		let entries = client.library_entry().find_many(filters).exec().await?;
+		let recent = Self::recent_playtime(client, user_id).await?;
+		Ok(entries
+			.into_iter()
+			.map(|entry| {
+				let recent_playtime = recent.get(&entry.id).copied().unwrap_or(0);
+				Self::info(entry, recent_playtime)
+			})
+			.collect())
+	}
+
+	pub async fn get_entry(client: &PrismaClient, user_id: &str, game_id: i32) -> Result<LibraryEntryInfo, ErrorCode> {
+		let entry = Self::find_entry(client, user_id, game_id).await?;
+		let recent_playtime = Self::recent_playtime(client, user_id).await?.get(&entry.id).copied().unwrap_or(0);
+		Ok(Self::info(entry, recent_playtime))
+	}
+
+	pub async fn create_entry(
+		client: &PrismaClient,
+		user_id: &str,
+		request: CreateLibraryEntryRequest,
+	) -> Result<LibraryEntryInfo, ErrorCode> {
+		if request.game_id <= 0 {
+			return Err(ErrorCode::BADREQUEST("game_id must be a RAWG game id".to_string()));
+		}
+		let installed_version = Self::validate_version(request.installed_version)?.flatten();
+		let install_path_hash = Self::validate_path_hash(request.install_path_hash)?.flatten();
+
+		let entry = client
+			.library_entry()
+			.create(
+				request.game_id,
+				request.status.as_str().to_string(),
+				user::id::equals(user_id.to_string()),
+				vec![
+					library_entry::installed_version::set(installed_version),
+					library_entry::install_path_hash::set(install_path_hash),
+				],
+			)
+			.exec()
+			.await
+			.map_err(|e| {
+				if e.is_prisma_error::<UniqueKeyViolation>() {
+					ErrorCode::BADREQUEST("Game is already in the library".to_string())
+				} else {
+					e.into()
+				}
+			})?;
+
+		info!("Пользователь ID: {} добавил игру {} в библиотеку", user_id, request.game_id);
+		Ok(Self::info(entry, 0))
+	}
+
+	pub async fn update_entry(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		request: UpdateLibraryEntryRequest,
+	) -> Result<LibraryEntryInfo, ErrorCode> {
+		let mut params = vec![];
+		if let Some(status) = request.status {
+			params.push(library_entry::status::set(status.as_str().to_string()));
+		}
+		if let Some(installed_version) = Self::validate_version(request.installed_version)? {
+			params.push(library_entry::installed_version::set(installed_version));
+		}
+		if let Some(install_path_hash) = Self::validate_path_hash(request.install_path_hash)? {
+			params.push(library_entry::install_path_hash::set(install_path_hash));
+		}
+
+		client
+			.library_entry()
+			.update(library_entry::user_id_game_id(user_id.to_string(), game_id), params)
+			.exec()
+			.await?;
+		Self::get_entry(client, user_id, game_id).await
+	}
+
+	pub async fn delete_entry(client: &PrismaClient, user_id: &str, game_id: i32) -> Result<(), ErrorCode> {
+		client
+			.library_entry()
+			.delete(library_entry::user_id_game_id(user_id.to_string(), game_id))
+			.exec()
+			.await?;
+
+		info!("Пользователь ID: {} удалил игру {} из библиотеки", user_id, game_id);
+		Ok(())
+	}
+
+	/// Вишлист это записи библиотеки со статусом `wishlist` вместе с тем, что о них знает `Notifier`.
+	pub async fn list_wishlist(client: &PrismaClient, user_id: &str) -> Result<Vec<WishlistItem>, ErrorCode> {
+		let entries = client
+			.library_entry()
+			.find_many(vec![
+				library_entry::user_id::equals(user_id.to_string()),
+				library_entry::status::equals(LibraryStatus::Wishlist.as_str().to_string()),
+			])
+			.order_by(library_entry::added_at::order(Direction::Desc))
+			.exec()
+			.await?;
+		let games: HashMap<i32, wishlist_game::Data> = client
+			.wishlist_game()
+			.find_many(vec![wishlist_game::game_id::in_vec(entries.iter().map(|entry| entry.game_id).collect())])
+			.exec()
+			.await?
+			.into_iter()
+			.map(|game| (game.game_id, game))
+			.collect();
+
+		Ok(entries
+			.into_iter()
+			.map(|entry| {
+				let game = games.get(&entry.game_id);
+				WishlistItem {
+					game_id: entry.game_id,
+					added_at: entry.added_at.to_rfc3339(),
+					name: game.and_then(|game| game.name.clone()),
+					released: game.and_then(|game| game.released.clone()),
+					price: game.and_then(|game| game.price.clone()),
+				}
+			})
+			.collect())
+	}
+
+	pub async fn remove_from_wishlist(client: &PrismaClient, user_id: &str, game_id: i32) -> Result<(), ErrorCode> {
+		let deleted = client
+			.library_entry()
+			.delete_many(vec![
+				library_entry::user_id::equals(user_id.to_string()),
+				library_entry::game_id::equals(game_id),
+				library_entry::status::equals(LibraryStatus::Wishlist.as_str().to_string()),
+			])
+			.exec()
+			.await?;
+
+		if deleted == 0 {
+			return Err(ErrorCode::DATABASE002);
+		}
+		Ok(())
+	}
+
+	pub async fn list_sessions(client: &PrismaClient, user_id: &str, game_id: i32) -> Result<Vec<PlaySessionInfo>, ErrorCode> {
+		let entry = Self::find_entry(client, user_id, game_id).await?;
+		let sessions = client
+			.play_session()
+			.find_many(vec![play_session::entry_id::equals(entry.id)])
+			.order_by(play_session::started_at::order(Direction::Desc))
+			.exec()
+			.await?;
+
+		Ok(sessions.into_iter().map(|session| Self::session_info(session, game_id)).collect())
+	}
+
+	pub async fn start_session(client: &PrismaClient, user_id: &str, game_id: i32) -> Result<PlaySessionInfo, ErrorCode> {
+		let entry = Self::find_entry(client, user_id, game_id).await?;
+		let session = client
+			.play_session()
+			.create(library_entry::id::equals(entry.id), vec![])
+			.exec()
+			.await?;
+
+		Ok(Self::session_info(session, game_id))
+	}
+
+	/// Завершает сессию и добавляет её длительность к общему времени в игре.
+	pub async fn stop_session(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		session_id: &str,
+	) -> Result<PlaySessionInfo, ErrorCode> {
+		let entry = Self::find_entry(client, user_id, game_id).await?;
+		let session = client
+			.play_session()
+			.find_first(vec![
+				play_session::id::equals(session_id.to_string()),
+				play_session::entry_id::equals(entry.id.clone()),
+			])
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)?;
+
+		let now = Utc::now();
+		let duration = now
+			.signed_duration_since(session.started_at)
+			.num_seconds()
+			.clamp(0, MAX_SESSION_SECONDS) as i32;
+
+		// Условие на ended_at не даёт засчитать одну сессию дважды при повторном запросе.
+		let stopped = client
+			.play_session()
+			.update_many(
+				vec![
+					play_session::id::equals(session.id.clone()),
+					play_session::ended_at::equals(None),
+				],
+				vec![
+					play_session::ended_at::set(Some(now.into())),
+					play_session::duration::set(Some(duration)),
+				],
+			)
+			.exec()
+			.await?;
+		if stopped == 0 {
+			return Err(ErrorCode::BADREQUEST("Session is already stopped".to_string()));
+		}
+
+		client
+			.library_entry()
+			.update(
+				library_entry::id::equals(entry.id),
+				vec![
+					library_entry::playtime::increment(duration),
+					library_entry::last_played::set(Some(now.into())),
+				],
+			)
+			.exec()
+			.await?;
+
+		Ok(PlaySessionInfo {
+			ended_at: Some(now.to_rfc3339()),
+			duration: Some(duration),
+			..Self::session_info(session, game_id)
+		})
+	}
+}
@@ -0,0 +1,214 @@
+use crate::model::dto::linux::{
+	CreateLinuxReportRequest, GpuVendor, LinuxRating, LinuxReportInfo, LinuxReportPage, LinuxReportQuery, LinuxRunner,
+	LinuxSummary,
+};
+use crate::model::error::ErrorCode;
+use crate::prisma::{linux_report, torrent, user, PrismaClient};
+use log::info;
+use prisma_client_rust::Direction;
+use std::collections::HashSet;
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+const MAX_FIELD_LENGTH: usize = 64;
+const MAX_NOTES_LENGTH: usize = 2000;
+/// Сколько последних отчётов учитывается в `trending`.
+const TRENDING_REPORTS: usize = 10;
+
+linux_report::select!(linux_report_rating { rating user_id });
+
+/// Отчёты о запуске игр через Proton и Wine, сводка считается по образцу ProtonDB.
+pub struct LinuxReportService;
+
+impl LinuxReportService {
+	fn info(report: linux_report::Data) -> LinuxReportInfo {
+		LinuxReportInfo {
+			runner: LinuxRunner::parse(&report.runner).unwrap_or(LinuxRunner::Proton),
+			gpu_vendor: GpuVendor::parse(&report.gpu_vendor).unwrap_or(GpuVendor::Other),
+			rating: LinuxRating::parse(&report.rating).unwrap_or(LinuxRating::Borked),
+			id: report.id,
+			game_id: report.game_id,
+			user_id: report.user_id,
+			torrent: report.release,
+			runner_version: report.runner_version,
+			distro: report.distro,
+			notes: report.notes,
+			created_at: report.created_at.to_rfc3339(),
+		}
+	}
+
+	fn validate_field(value: String, field: &str) -> Result<String, ErrorCode> {
+		let value = value.trim().to_string();
+		if value.is_empty() || value.chars().count() > MAX_FIELD_LENGTH {
+			return Err(ErrorCode::BADREQUEST(format!("{} must be 1 to {} characters", field, MAX_FIELD_LENGTH)));
+		}
+		Ok(value)
+	}
+
+	fn average(ratings: &[LinuxRating]) -> Option<LinuxRating> {
+		if ratings.is_empty() {
+			return None;
+		}
+		let total: usize = ratings.iter().map(LinuxRating::score).sum();
+		let average = (total as f64 / ratings.len() as f64).round() as usize;
+		LinuxRating::ALL.get(average).copied()
+	}
+
+	/// Учитывается только последний отчёт каждого пользователя, чтобы повторные отчёты не сдвигали оценку.
+	pub async fn summary(client: &PrismaClient, game_id: i32) -> Result<LinuxSummary, ErrorCode> {
+		let reports = client
+			.linux_report()
+			.find_many(vec![linux_report::game_id::equals(game_id)])
+			.order_by(linux_report::created_at::order(Direction::Desc))
+			.select(linux_report_rating::select())
+			.exec()
+			.await?;
+
+		let mut seen = HashSet::new();
+		let ratings: Vec<LinuxRating> = reports
+			.into_iter()
+			.filter(|report| seen.insert(report.user_id.clone()))
+			.filter_map(|report| LinuxRating::parse(&report.rating))
+			.collect();
+
+		let mut summary = LinuxSummary {
+			count: ratings.len() as i64,
+			tier: Self::average(&ratings),
+			trending: Self::average(&ratings[..ratings.len().min(TRENDING_REPORTS)]),
+			best: ratings.iter().max().copied(),
+			..Default::default()
+		};
+		summary.confidence = match ratings.len() {
+			0 => None,
+			1..=2 => Some("low"),
+			3..=9 => Some("moderate"),
+			10..=29 => Some("good"),
+			_ => Some("strong"),
+		}
+		.map(str::to_string);
+		for rating in ratings {
+			let counter = match rating {
+				LinuxRating::Platinum => &mut summary.distribution.platinum,
+				LinuxRating::Gold => &mut summary.distribution.gold,
+				LinuxRating::Silver => &mut summary.distribution.silver,
+				LinuxRating::Bronze => &mut summary.distribution.bronze,
+				LinuxRating::Borked => &mut summary.distribution.borked,
+			};
+			*counter += 1;
+		}
+		Ok(summary)
+	}
+
+	pub async fn list_reports(
+		client: &PrismaClient,
+		game_id: i32,
+		query: LinuxReportQuery,
+	) -> Result<LinuxReportPage, ErrorCode> {
+		let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+		if page_size == 0 || page_size > MAX_PAGE_SIZE {
+			return Err(ErrorCode::BADREQUEST(format!("page_size must be between 1 and {}", MAX_PAGE_SIZE)));
+		}
+		let page = query.page.unwrap_or(1).max(1);
+
+		let mut filters = vec![linux_report::game_id::equals(game_id)];
+		if let Some(runner) = query.runner {
+			filters.push(linux_report::runner::equals(runner.as_str().to_string()));
+		}
+		if let Some(gpu_vendor) = query.gpu_vendor {
+			filters.push(linux_report::gpu_vendor::equals(gpu_vendor.as_str().to_string()));
+		}
+
+		let reports = client
+			.linux_report()
+			.find_many(filters)
+			.order_by(linux_report::created_at::order(Direction::Desc))
+			.skip(((page - 1) * page_size) as i64)
+			.take(page_size as i64)
+			.exec()
+			.await?;
+
+		Ok(LinuxReportPage {
+			summary: Self::summary(client, game_id).await?,
+			page,
+			page_size,
+			results: reports.into_iter().map(Self::info).collect(),
+		})
+	}
+
+	pub async fn create_report(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		request: CreateLinuxReportRequest,
+	) -> Result<LinuxReportInfo, ErrorCode> {
+		let runner_version = Self::validate_field(request.runner_version, "runner_version")?;
+		let distro = Self::validate_field(request.distro, "distro")?;
+		let notes = request.notes.map(|notes| notes.trim().to_string()).filter(|notes| !notes.is_empty());
+		if notes.as_ref().map(|notes| notes.chars().count() > MAX_NOTES_LENGTH).unwrap_or(false) {
+			return Err(ErrorCode::BADREQUEST(format!("notes must be at most {} characters", MAX_NOTES_LENGTH)));
+		}
+
+		// Раздача проверяется по текущему индексу, сохраняется только ссылка.
+		if let Some(link) = &request.torrent {
+			client
+				.torrent()
+				.find_first(vec![torrent::torrent::equals(link.clone())])
+				.exec()
+				.await?
+				.ok_or(ErrorCode::BADREQUEST("torrent is not in the index".to_string()))?;
+		}
+
+		let report = client
+			.linux_report()
+			.create(
+				game_id,
+				request.runner.as_str().to_string(),
+				runner_version,
+				distro,
+				request.gpu_vendor.as_str().to_string(),
+				request.rating.as_str().to_string(),
+				user::id::equals(user_id.to_string()),
+				vec![
+					linux_report::release::set(request.torrent),
+					linux_report::notes::set(notes),
+				],
+			)
+			.exec()
+			.await?;
+
+		info!(
+			"Пользователь ID: {} оценил запуск игры {} в Linux: {}",
+			user_id, game_id, request.rating.as_str()
+		);
+		Ok(Self::info(report))
+	}
+
+	/// Автор удаляет свой отчёт, модератор с `content:moderate` — любой.
+	pub async fn delete_report(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		report_id: &str,
+		moderator: bool,
+	) -> Result<(), ErrorCode> {
+		let report = client
+			.linux_report()
+			.find_first(vec![
+				linux_report::id::equals(report_id.to_string()),
+				linux_report::game_id::equals(game_id),
+			])
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)?;
+		if report.user_id != user_id && !moderator {
+			return Err(ErrorCode::AUTH005);
+		}
+
+		client
+			.linux_report()
+			.delete(linux_report::id::equals(report.id))
+			.exec()
+			.await?;
+		Ok(())
+	}
+}
@@ -0,0 +1,277 @@
+use futures::future::join_all;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::env;
+use tokio::sync::Mutex;
+
+use log::warn;
+
+/// Порядок провайдеров по умолчанию: RAWG первым находит Steam appid,
+/// поэтому сбор данных всегда идёт в порядке регистрации, а приоритет
+/// влияет только на слияние полей.
+const DEFAULT_PRECEDENCE: &str = "rawg,steam";
+/// Сколько найденных Steam appid держать в памяти, прежде чем начать заново.
+const MAX_CACHED_STEAM_IDS: usize = 10_000;
+
+#[derive(Debug, Clone, Default)]
+pub struct GameReference {
+	pub rawg_id: i32,
+	pub name: Option<String>,
+	pub steam_app_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GameMetadata {
+	pub name: Option<String>,
+	pub description: Option<String>,
+	pub released: Option<String>,
+	pub background_image: Option<String>,
+	pub website: Option<String>,
+	pub metacritic: Option<i64>,
+	pub developers: Vec<String>,
+	pub publishers: Vec<String>,
+	pub genres: Vec<String>,
+	pub price: Option<String>,
+	pub steam_app_id: Option<u32>,
+	#[serde(skip)]
+	pub raw: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataError {
+	/// Провайдер ответил, что такой игры у него нет.
+	NotFound,
+	/// Провайдер недоступен: сетевая ошибка или ответ 5xx. Только в этом случае
+	/// данные берутся из локального каталога.
+	Unavailable(String),
+	/// Провайдер отклонил запрос по другой причине.
+	Rejected(String),
+}
+
+impl MetadataError {
+	/// Ошибка запроса к провайдеру. Адрес из ошибки убирается, потому что в нём бывает ключ API.
+	pub fn from_http(provider: &str, error: reqwest::Error) -> Self {
+		match error.status() {
+			Some(StatusCode::NOT_FOUND) => MetadataError::NotFound,
+			Some(status) if !status.is_server_error() => MetadataError::Rejected(format!("{} responded with {}", provider, status)),
+			_ => MetadataError::Unavailable(format!("{} request failed: {}", provider, error.without_url())),
+		}
+	}
+
+	/// Сообщение для клиента, без подробностей апстрима.
+	pub fn public_message(&self) -> &'static str {
+		match self {
+			MetadataError::NotFound => "Game not found",
+			MetadataError::Unavailable(_) => "Game details are unavailable",
+			MetadataError::Rejected(_) => "Metadata provider rejected the request",
+		}
+	}
+}
+
+impl std::fmt::Display for MetadataError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			MetadataError::NotFound => write!(f, "not found"),
+			MetadataError::Unavailable(message) | MetadataError::Rejected(message) => write!(f, "{}", message),
+		}
+	}
+}
+
+#[async_trait::async_trait]
+pub trait GameMetadataProvider: Send + Sync {
+	fn name(&self) -> &'static str;
+
+	/// Хватает ли провайдеру уже известных идентификаторов игры. Провайдеры, которым
+	/// не хватает, опрашиваются после тех, кто может найти недостающее.
+	fn ready(&self, _game: &GameReference) -> bool {
+		true
+	}
+
+	async fn fetch_metadata(&self, game: &GameReference) -> Result<Option<GameMetadata>, MetadataError>;
+}
+
+pub struct MetadataService {
+	providers: Vec<Box<dyn GameMetadataProvider>>,
+	precedence: Vec<String>,
+	/// Steam appid по id RAWG: с ним все провайдеры опрашиваются одновременно.
+	steam_app_ids: Mutex<HashMap<i32, u32>>,
+}
+
+impl MetadataService {
+	pub fn new(providers: Vec<Box<dyn GameMetadataProvider>>) -> Self {
+		let precedence = env::var("VEK_METADATA_PRECEDENCE")
+			.unwrap_or_else(|_| DEFAULT_PRECEDENCE.to_string())
+			.split(',')
+			.map(|name| name.trim().to_lowercase())
+			.filter(|name| !name.is_empty())
+			.collect();
+
+		MetadataService {
+			providers,
+			precedence,
+			steam_app_ids: Mutex::new(HashMap::new()),
+		}
+	}
+
+	fn rank(&self, provider: &str) -> usize {
+		self.precedence
+			.iter()
+			.position(|name| name == provider)
+			.unwrap_or(self.precedence.len())
+	}
+
+	/// Собирает данные об игре у всех провайдеров и сливает их по приоритету.
+	/// Основой ответа служит исходный JSON RAWG, чтобы формат `/api/games/{id}` не менялся.
+	/// Готовые провайдеры опрашиваются одновременно, остальные — следующим кругом,
+	/// когда предыдущие найдут недостающие идентификаторы.
+	pub async fn get_game_details(&self, rawg_id: i32) -> Result<Value, MetadataError> {
+		let steam_app_id = self.steam_app_ids.lock().await.get(&rawg_id).copied();
+		let mut reference = GameReference { rawg_id, steam_app_id, ..Default::default() };
+		let mut collected: Vec<(&'static str, GameMetadata)> = Vec::new();
+		let mut errors: Vec<MetadataError> = Vec::new();
+		let mut pending: Vec<&dyn GameMetadataProvider> = self.providers.iter().map(|provider| provider.as_ref()).collect();
+
+		loop {
+			let (ready, waiting): (Vec<_>, Vec<_>) = pending.into_iter().partition(|provider| provider.ready(&reference));
+			if ready.is_empty() {
+				break;
+			}
+			pending = waiting;
+
+			let results = join_all(ready.iter().map(|provider| provider.fetch_metadata(&reference))).await;
+			for (provider, result) in ready.into_iter().zip(results) {
+				match result {
+					Ok(Some(metadata)) => collected.push((provider.name(), metadata)),
+					Ok(None) => {}
+					Err(e) => {
+						warn!("Провайдер метаданных {} вернул ошибку: {}", provider.name(), e);
+						errors.push(e);
+					}
+				}
+			}
+
+			// Идентификаторы берутся в порядке регистрации провайдеров.
+			for (_, metadata) in &collected {
+				if reference.name.is_none() {
+					reference.name = metadata.name.clone();
+				}
+				if reference.steam_app_id.is_none() {
+					reference.steam_app_id = metadata.steam_app_id;
+				}
+			}
+		}
+
+		if collected.is_empty() {
+			// Недоступность важнее «не найдено»: игра могла бы найтись у недоступного провайдера.
+			let unavailable = errors.iter().find(|e| matches!(e, MetadataError::Unavailable(_)));
+			let rejected = errors.iter().find(|e| matches!(e, MetadataError::Rejected(_)));
+			return Err(unavailable.or(rejected).cloned().unwrap_or(MetadataError::NotFound));
+		}
+		if let (None, Some(app_id)) = (steam_app_id, reference.steam_app_id) {
+			let mut cache = self.steam_app_ids.lock().await;
+			if cache.len() >= MAX_CACHED_STEAM_IDS {
+				cache.clear();
+			}
+			cache.insert(rawg_id, app_id);
+		}
+
+		collected.sort_by_key(|(name, _)| self.rank(name));
+		Ok(Self::merge(collected))
+	}
+
+	fn merge(collected: Vec<(&'static str, GameMetadata)>) -> Value {
+		let mut base = collected
+			.iter()
+			.find_map(|(_, metadata)| metadata.raw.clone())
+			.and_then(|raw| match raw {
+				Value::Object(map) => Some(map),
+				_ => None,
+			})
+			.unwrap_or_else(Map::new);
+
+		let pick = |get: &dyn Fn(&GameMetadata) -> Option<Value>| {
+			collected.iter().find_map(|(_, metadata)| get(metadata))
+		};
+		let pick_list = |get: &dyn Fn(&GameMetadata) -> &Vec<String>| {
+			collected
+				.iter()
+				.map(|(_, metadata)| get(metadata))
+				.find(|list| !list.is_empty())
+				.map(|list| json!(list))
+		};
+
+		let fields: Vec<(&str, Option<Value>)> = vec![
+			("name", pick(&|m| m.name.clone().map(Value::from))),
+			("description_raw", pick(&|m| m.description.clone().map(Value::from))),
+			("released", pick(&|m| m.released.clone().map(Value::from))),
+			("background_image", pick(&|m| m.background_image.clone().map(Value::from))),
+			("website", pick(&|m| m.website.clone().map(Value::from))),
+			("metacritic", pick(&|m| m.metacritic.map(Value::from))),
+			("price", pick(&|m| m.price.clone().map(Value::from))),
+			("steam_app_id", pick(&|m| m.steam_app_id.map(Value::from))),
+			("developer_names", pick_list(&|m| &m.developers)),
+			("publisher_names", pick_list(&|m| &m.publishers)),
+			("genre_names", pick_list(&|m| &m.genres)),
+		];
+
+		for (field, value) in fields {
+			if let Some(value) = value {
+				base.insert(field.to_string(), value);
+			}
+		}
+
+		let sources: Vec<&str> = collected.iter().map(|(name, _)| *name).collect();
+		base.insert("metadata_sources".to_string(), json!(sources));
+
+		Value::Object(base)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::middleware::games::GamesMiddleware;
+	use crate::modules::metadata::rawg::ProviderRAWG;
+	use crate::modules::metadata::steam::ProviderSteam;
+	use crate::modules::stub;
+	use std::sync::Arc;
+
+	fn service() -> MetadataService {
+		MetadataService::new(vec![
+			Box::new(ProviderRAWG::new(Arc::new(GamesMiddleware::with_api_key(stub::RAWG_API_KEY)))),
+			Box::new(ProviderSteam::with_base_url(stub::steam())),
+		])
+	}
+
+	#[tokio::test]
+	async fn merges_rawg_and_steam_metadata() {
+		let service = service();
+		let details = service.get_game_details(3498).await.unwrap();
+
+		assert_eq!(details["name"], "Grand Theft Auto V");
+		assert_eq!(details["metacritic"], 92);
+		assert_eq!(details["price"], "$14.99");
+		assert_eq!(details["steam_app_id"], 271590);
+		assert_eq!(details["developer_names"], json!(["Rockstar North"]));
+		assert_eq!(details["metadata_sources"], json!(["rawg", "steam"]));
+		assert!(details["platforms"].is_array(), "RAWG response is the base of the result");
+
+		// Второй запрос знает appid заранее и опрашивает провайдеров одновременно.
+		assert_eq!(service.steam_app_ids.lock().await.get(&3498), Some(&271590));
+		assert_eq!(service.get_game_details(3498).await.unwrap()["price"], "$14.99");
+	}
+
+	#[tokio::test]
+	async fn unknown_games_are_not_found() {
+		assert_eq!(service().get_game_details(404404).await.unwrap_err(), MetadataError::NotFound);
+	}
+
+	#[tokio::test]
+	async fn upstream_failures_are_unavailable() {
+		let error = service().get_game_details(0).await.unwrap_err();
+		assert!(matches!(error, MetadataError::Unavailable(_)), "{:?}", error);
+		assert!(!error.to_string().contains(stub::RAWG_API_KEY));
+	}
+}
@@ -0,0 +1,276 @@
+use crate::middleware::games::GamesMiddleware;
+use crate::prisma::{game, genre, platform, screenshot, PrismaClient};
+use crate::service::games::GamesService;
+use chrono::Utc;
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Сколько страниц популярных игр (по 40 штук) забирает фоновая синхронизация.
+pub const SYNC_PAGES: usize = 5;
+
+/// Локальное зеркало каталога RAWG: сохраняет всё, что API получает от RAWG,
+/// чтобы отдавать страницы игр, когда RAWG недоступен.
+pub struct MirrorService {
+	prisma_client: Arc<PrismaClient>,
+}
+
+struct Entity {
+	id: i32,
+	slug: String,
+	name: String,
+}
+
+impl MirrorService {
+	pub fn new(prisma_client: Arc<PrismaClient>) -> Self {
+		MirrorService { prisma_client }
+	}
+
+	/// Разбирает массив жанров (`[{id, slug, name}]`) или платформ (`[{platform: {...}}]`).
+	fn entities(value: &Value, nested: Option<&str>) -> Vec<Entity> {
+		value
+			.as_array()
+			.map(|items| {
+				items
+					.iter()
+					.map(|item| match nested {
+						Some(field) => &item[field],
+						None => item,
+					})
+					.filter_map(|item| {
+						Some(Entity {
+							id: item["id"].as_i64()? as i32,
+							slug: item["slug"].as_str()?.to_string(),
+							name: item["name"].as_str()?.to_string(),
+						})
+					})
+					.collect()
+			})
+			.unwrap_or_default()
+	}
+
+	async fn upsert_entities(&self, genres: &[Entity], platforms: &[Entity]) -> Result<(), String> {
+		for entity in genres {
+			self.prisma_client
+				.genre()
+				.upsert(
+					genre::id::equals(entity.id),
+					genre::create(entity.id, entity.slug.clone(), entity.name.clone(), vec![]),
+					vec![genre::slug::set(entity.slug.clone()), genre::name::set(entity.name.clone())],
+				)
+				.exec()
+				.await
+				.map_err(|e| format!("Failed to store genre: {}", e))?;
+		}
+
+		for entity in platforms {
+			self.prisma_client
+				.platform()
+				.upsert(
+					platform::id::equals(entity.id),
+					platform::create(entity.id, entity.slug.clone(), entity.name.clone(), vec![]),
+					vec![platform::slug::set(entity.slug.clone()), platform::name::set(entity.name.clone())],
+				)
+				.exec()
+				.await
+				.map_err(|e| format!("Failed to store platform: {}", e))?;
+		}
+
+		Ok(())
+	}
+
+	/// Сохраняет игру из списка или из детальной страницы RAWG.
+	/// Для детальной страницы дополнительно сохраняется весь ответ целиком.
+	pub async fn store_game(&self, entry: &Value, with_details: bool) -> Result<(), String> {
+		let (id, slug, name) = match (entry["id"].as_i64(), entry["slug"].as_str(), entry["name"].as_str()) {
+			(Some(id), Some(slug), Some(name)) => (id as i32, slug.to_string(), name.to_string()),
+			_ => return Err("Game entry has no id, slug or name".to_string()),
+		};
+
+		let genres = Self::entities(&entry["genres"], None);
+		let platforms = Self::entities(&entry["platforms"], Some("platform"));
+		self.upsert_entities(&genres, &platforms).await?;
+
+		let fields = || {
+			let mut fields = vec![
+				game::released::set(entry["released"].as_str().map(str::to_string)),
+				game::background_image::set(entry["background_image"].as_str().map(str::to_string)),
+				game::rating::set(entry["rating"].as_f64()),
+				game::metacritic::set(entry["metacritic"].as_i64().map(|score| score as i32)),
+				game::synced_at::set(Utc::now().into()),
+			];
+			if with_details {
+				fields.push(game::details::set(Some(entry.to_string())));
+			}
+			fields
+		};
+		let genre_ids = || genres.iter().map(|entity| genre::id::equals(entity.id)).collect::<Vec<_>>();
+		let platform_ids = || platforms.iter().map(|entity| platform::id::equals(entity.id)).collect::<Vec<_>>();
+
+		let mut create = fields();
+		create.push(game::genres::connect(genre_ids()));
+		create.push(game::platforms::connect(platform_ids()));
+
+		let mut update = fields();
+		update.push(game::slug::set(slug.clone()));
+		update.push(game::name::set(name.clone()));
+		update.push(game::genres::set(genre_ids()));
+		update.push(game::platforms::set(platform_ids()));
+
+		self.prisma_client
+			.game()
+			.upsert(game::id::equals(id), game::create(id, slug, name, create), update)
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to store game {}: {}", id, e))?;
+
+		if let Some(shots) = entry["short_screenshots"].as_array() {
+			self.upsert_screenshots(id, shots).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Сохраняет все игры из страницы списка или поиска.
+	pub async fn store_games(&self, page: &Value) {
+		if let Some(results) = page["results"].as_array() {
+			for game in results {
+				if let Err(e) = self.store_game(game, false).await {
+					warn!("Не удалось сохранить игру в локальный каталог: {}", e);
+				}
+			}
+		}
+	}
+
+	/// Сохраняет страницу скриншотов для игры, которая уже есть в каталоге.
+	pub async fn store_screenshots(&self, game_id: i32, page: &Value) {
+		let exists = self
+			.prisma_client
+			.game()
+			.find_unique(game::id::equals(game_id))
+			.exec()
+			.await
+			.map(|game| game.is_some())
+			.unwrap_or(false);
+
+		if let (true, Some(results)) = (exists, page["results"].as_array()) {
+			if let Err(e) = self.upsert_screenshots(game_id, results).await {
+				warn!("Не удалось сохранить скриншоты игры {}: {}", game_id, e);
+			}
+		}
+	}
+
+	async fn upsert_screenshots(&self, game_id: i32, shots: &[Value]) -> Result<(), String> {
+		for shot in shots {
+			let (id, image) = match (shot["id"].as_i64(), shot["image"].as_str()) {
+				(Some(id), Some(image)) if id > 0 => (id as i32, image.to_string()),
+				_ => continue,
+			};
+			let width = shot["width"].as_i64().map(|width| width as i32);
+			let height = shot["height"].as_i64().map(|height| height as i32);
+
+			self.prisma_client
+				.screenshot()
+				.upsert(
+					screenshot::id::equals(id),
+					screenshot::create(
+						id,
+						image.clone(),
+						game::id::equals(game_id),
+						vec![screenshot::width::set(width), screenshot::height::set(height)],
+					),
+					vec![
+						screenshot::image::set(image),
+						screenshot::width::set(width),
+						screenshot::height::set(height),
+					],
+				)
+				.exec()
+				.await
+				.map_err(|e| format!("Failed to store screenshot {}: {}", id, e))?;
+		}
+
+		Ok(())
+	}
+
+	/// Возвращает игру из локального каталога в формате RAWG с отметкой свежести.
+	pub async fn get_game_details(&self, id: i32) -> Result<Option<Value>, String> {
+		let game = self
+			.prisma_client
+			.game()
+			.find_unique(game::id::equals(id))
+			.with(game::genres::fetch(vec![]))
+			.with(game::platforms::fetch(vec![]))
+			.with(game::screenshots::fetch(vec![]))
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to read game {}: {}", id, e))?;
+
+		let game = match game {
+			Some(game) => game,
+			None => return Ok(None),
+		};
+
+		let mut details = game
+			.details
+			.as_deref()
+			.and_then(|details| serde_json::from_str::<Value>(details).ok())
+			.filter(Value::is_object)
+			.unwrap_or_else(|| {
+				json!({
+					"id": game.id,
+					"slug": game.slug,
+					"name": game.name,
+					"released": game.released,
+					"background_image": game.background_image,
+					"rating": game.rating,
+					"metacritic": game.metacritic,
+					"genres": game.genres.as_deref().unwrap_or_default().iter()
+						.map(|genre| json!({ "id": genre.id, "slug": genre.slug, "name": genre.name }))
+						.collect::<Vec<_>>(),
+					"platforms": game.platforms.as_deref().unwrap_or_default().iter()
+						.map(|platform| json!({ "platform": { "id": platform.id, "slug": platform.slug, "name": platform.name } }))
+						.collect::<Vec<_>>(),
+				})
+			});
+
+		details["short_screenshots"] = json!(game
+			.screenshots
+			.as_deref()
+			.unwrap_or_default()
+			.iter()
+			.map(|shot| json!({ "id": shot.id, "image": shot.image }))
+			.collect::<Vec<_>>());
+		details["from_cache"] = json!(true);
+		details["synced_at"] = json!(game.synced_at.to_rfc3339());
+
+		Ok(Some(details))
+	}
+
+	/// Загружает популярные игры RAWG в локальный каталог.
+	pub async fn sync_popular(&self, middleware: &GamesMiddleware, pages: usize) {
+		for page in 1..=pages {
+			let params = vec![
+				("page".to_string(), page.to_string()),
+				("page_size".to_string(), "40".to_string()),
+				("ordering".to_string(), "-added".to_string()),
+			];
+
+			match middleware
+				.execute_with_retry(move |api_key| {
+					let params = params.clone();
+					async move { GamesService::get_game_list(api_key, params).await }
+				})
+				.await
+			{
+				Ok(result) => self.store_games(&result).await,
+				Err(e) => {
+					warn!("Синхронизация каталога остановлена на странице {}: {}", page, e);
+					break;
+				}
+			}
+		}
+
+		info!("Синхронизация локального каталога завершена");
+	}
+}
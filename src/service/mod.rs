@@ -1,4 +1,20 @@
 pub(crate) mod auth;
 pub(crate) mod user;
 pub(crate) mod games;
+pub(crate) mod images;
+pub(crate) mod library;
+pub(crate) mod linux;
+pub(crate) mod metadata;
+pub(crate) mod mirror;
+pub(crate) mod notification;
+pub(crate) mod notifier;
+pub(crate) mod rbac;
+pub(crate) mod reports;
+pub(crate) mod reviews;
+pub(crate) mod saves;
+pub(crate) mod session;
+pub(crate) mod settings;
+pub(crate) mod suggest;
+pub(crate) mod token;
 pub(crate) mod torrent;
+pub(crate) mod webhook;
@@ -0,0 +1,155 @@
+use crate::model::dto::notification::{NotificationInfo, NotificationKind, NotificationList};
+use crate::model::error::ErrorCode;
+use crate::prisma::{notification, user, PrismaClient};
+use chrono::Utc;
+use log::warn;
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use prisma_client_rust::Direction;
+
+/// Сколько последних уведомлений отдаёт `GET /api/user/notifications`.
+const LIST_LIMIT: i64 = 100;
+
+pub struct NotificationService;
+
+impl NotificationService {
+	fn info(notification: notification::Data) -> NotificationInfo {
+		NotificationInfo {
+			kind: NotificationKind::parse(&notification.kind).unwrap_or(NotificationKind::Release),
+			read: notification.read_at.is_some(),
+			id: notification.id,
+			game_id: notification.game_id,
+			title: notification.title,
+			body: notification.body,
+			created_at: notification.created_at.to_rfc3339(),
+		}
+	}
+
+	pub async fn list(client: &PrismaClient, user_id: &str, unread_only: bool) -> Result<NotificationList, ErrorCode> {
+		let mut filters = vec![
+			notification::user_id::equals(user_id.to_string()),
+			notification::deleted_at::equals(None),
+		];
+		if unread_only {
+			filters.push(notification::read_at::equals(None));
+		}
+
+		let notifications = client
+			.notification()
+			.find_many(filters)
+			.order_by(notification::created_at::order(Direction::Desc))
+			.take(LIST_LIMIT)
+			.exec()
+			.await?;
+		let unread = client
+			.notification()
+			.count(vec![
+				notification::user_id::equals(user_id.to_string()),
+				notification::read_at::equals(None),
+				notification::deleted_at::equals(None),
+			])
+			.exec()
+			.await?;
+
+		Ok(NotificationList {
+			unread,
+			notifications: notifications.into_iter().map(Self::info).collect(),
+		})
+	}
+
+	pub async fn mark_read(client: &PrismaClient, user_id: &str, notification_id: &str) -> Result<(), ErrorCode> {
+		let notification = client
+			.notification()
+			.find_first(vec![
+				notification::id::equals(notification_id.to_string()),
+				notification::user_id::equals(user_id.to_string()),
+				notification::deleted_at::equals(None),
+			])
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)?;
+
+		if notification.read_at.is_none() {
+			client
+				.notification()
+				.update(
+					notification::id::equals(notification.id),
+					vec![notification::read_at::set(Some(Utc::now().into()))],
+				)
+				.exec()
+				.await?;
+		}
+		Ok(())
+	}
+
+	pub async fn mark_all_read(client: &PrismaClient, user_id: &str) -> Result<i64, ErrorCode> {
+		Ok(client
+			.notification()
+			.update_many(
+				vec![
+					notification::user_id::equals(user_id.to_string()),
+					notification::read_at::equals(None),
+					notification::deleted_at::equals(None),
+				],
+				vec![notification::read_at::set(Some(Utc::now().into()))],
+			)
+			.exec()
+			.await?)
+	}
+
+	/// Уведомление только помечается удалённым: строка с тем же `key` не даёт
+	/// следующей проверке создать его заново.
+	pub async fn delete(client: &PrismaClient, user_id: &str, notification_id: &str) -> Result<(), ErrorCode> {
+		let deleted = client
+			.notification()
+			.update_many(
+				vec![
+					notification::id::equals(notification_id.to_string()),
+					notification::user_id::equals(user_id.to_string()),
+					notification::deleted_at::equals(None),
+				],
+				vec![notification::deleted_at::set(Some(Utc::now().into()))],
+			)
+			.exec()
+			.await?;
+
+		if deleted == 0 {
+			return Err(ErrorCode::DATABASE002);
+		}
+		Ok(())
+	}
+
+	/// Создаёт уведомление каждому пользователю. `key` уникален в пределах игры,
+	/// поэтому повторная проверка не дублирует уже отправленные или удалённые уведомления.
+	pub async fn notify(
+		client: &PrismaClient,
+		user_ids: &[String],
+		game_id: i32,
+		kind: NotificationKind,
+		key: &str,
+		title: &str,
+		body: Option<String>,
+	) -> usize {
+		let mut created = 0;
+		for user_id in user_ids {
+			let result = client
+				.notification()
+				.create(
+					kind.as_str().to_string(),
+					key.to_string(),
+					game_id,
+					title.to_string(),
+					user::id::equals(user_id.clone()),
+					vec![notification::body::set(body.clone())],
+				)
+				.exec()
+				.await;
+
+			match result {
+				Ok(_) => created += 1,
+				Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => {}
+				Err(e) => warn!("Не удалось создать уведомление для пользователя {}: {:?}", user_id, e),
+			}
+		}
+		created
+	}
+}
@@ -0,0 +1,239 @@
+use crate::model::dto::library::LibraryStatus;
+use crate::model::dto::notification::NotificationKind;
+use crate::model::error::ErrorCode;
+use crate::modules::helpers::{link_release, normalize_name};
+use crate::prisma::{library_entry, wishlist_game, PrismaClient};
+use crate::service::metadata::MetadataService;
+use crate::service::notification::NotificationService;
+use crate::service::reports::ReportService;
+use chrono::{Duration, Utc};
+use log::{info, warn};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Как часто фоновая задача проверяет вишлисты.
+pub const CHECK_INTERVAL_MINUTES: u64 = 60;
+/// Метаданные игры из вишлиста перезапрашиваются не чаще этого.
+const METADATA_TTL_HOURS: i64 = 12;
+
+/// Следит за играми из вишлистов: выход, снижение цены и новые репаки в индексе торрентов.
+pub struct Notifier {
+	prisma_client: Arc<PrismaClient>,
+	metadata_service: Arc<MetadataService>,
+	/// Проверку репаков запускают и таймер, и наблюдатель индекса, одновременно она идти не должна.
+	repacks_lock: Mutex<()>,
+}
+
+impl Notifier {
+	pub fn new(prisma_client: Arc<PrismaClient>, metadata_service: Arc<MetadataService>) -> Self {
+		Notifier {
+			prisma_client,
+			metadata_service,
+			repacks_lock: Mutex::new(()),
+		}
+	}
+
+	/// Цена из Steam приходит строкой вроде "$19.99" или "1 199 руб.",
+	/// для сравнения в одной валюте достаточно оставить цифры.
+	fn parse_price(price: &str) -> Option<u64> {
+		let digits: String = price.chars().filter(char::is_ascii_digit).collect();
+		digits.parse().ok()
+	}
+
+	/// Игры из вишлистов и пользователи, которые их добавили.
+	async fn wishlisted(&self) -> Result<HashMap<i32, Vec<String>>, ErrorCode> {
+		let entries = self
+			.prisma_client
+			.library_entry()
+			.find_many(vec![library_entry::status::equals(LibraryStatus::Wishlist.as_str().to_string())])
+			.exec()
+			.await?;
+
+		let mut wishlisted: HashMap<i32, Vec<String>> = HashMap::new();
+		for entry in entries {
+			wishlisted.entry(entry.game_id).or_default().push(entry.user_id);
+		}
+		Ok(wishlisted)
+	}
+
+	async fn refresh_game(
+		&self,
+		game_id: i32,
+		user_ids: &[String],
+		previous: Option<wishlist_game::Data>,
+	) -> Result<wishlist_game::Data, ErrorCode> {
+		let details = self.metadata_service.get_game_details(game_id).await.map_err(|e| {
+			warn!("Не удалось получить метаданные игры {} из вишлиста: {}", game_id, e);
+			ErrorCode::INTERNAL001
+		})?;
+		let name = details["name"].as_str().map(str::to_string);
+		let released = details["released"].as_str().map(str::to_string);
+		let price = details["price"].as_str().map(str::to_string);
+
+		let previous_price = previous.as_ref().and_then(|game| game.price.as_deref()).and_then(Self::parse_price);
+		if let (Some(old), Some(new)) = (previous_price, price.as_deref()) {
+			if Self::parse_price(new).map(|new_price| new_price < old).unwrap_or(false) {
+				NotificationService::notify(
+					&self.prisma_client,
+					user_ids,
+					game_id,
+					NotificationKind::Price,
+					&format!("price:{}", new),
+					&format!("Price dropped to {}", new),
+					name.clone(),
+				)
+				.await;
+			}
+		}
+
+		let fields = || {
+			vec![
+				wishlist_game::name::set(name.clone()),
+				wishlist_game::released::set(released.clone()),
+				wishlist_game::price::set(price.clone()),
+				wishlist_game::checked_at::set(Utc::now().into()),
+			]
+		};
+		Ok(self
+			.prisma_client
+			.wishlist_game()
+			.upsert(wishlist_game::game_id::equals(game_id), wishlist_game::create(game_id, fields()), fields())
+			.exec()
+			.await?)
+	}
+
+	/// Обновляет метаданные игр из вишлистов и сообщает о выходе сегодня и о снижении цены.
+	pub async fn check_wishlist(&self) -> Result<(), ErrorCode> {
+		let wishlisted = self.wishlisted().await?;
+		self.prisma_client
+			.wishlist_game()
+			.delete_many(vec![wishlist_game::game_id::not_in_vec(wishlisted.keys().copied().collect())])
+			.exec()
+			.await?;
+
+		let today = Utc::now().format("%Y-%m-%d").to_string();
+		for (game_id, user_ids) in &wishlisted {
+			let cached = self
+				.prisma_client
+				.wishlist_game()
+				.find_unique(wishlist_game::game_id::equals(*game_id))
+				.exec()
+				.await?;
+			let stale = cached
+				.as_ref()
+				.map(|game| Utc::now().signed_duration_since(game.checked_at) > Duration::hours(METADATA_TTL_HOURS))
+				.unwrap_or(true);
+
+			let game = match cached {
+				Some(game) if !stale => game,
+				previous => match self.refresh_game(*game_id, user_ids, previous).await {
+					Ok(game) => game,
+					Err(_) => continue,
+				},
+			};
+
+			if game.released.as_deref() == Some(today.as_str()) {
+				NotificationService::notify(
+					&self.prisma_client,
+					user_ids,
+					*game_id,
+					NotificationKind::Release,
+					"release",
+					"Released today",
+					game.name.clone(),
+				)
+				.await;
+			}
+		}
+		Ok(())
+	}
+
+	/// Сравнивает репакеры в индексе торрентов с уже виденными. При первой проверке игры
+	/// репакеры только запоминаются, чтобы не присылать уведомления о старых раздачах.
+	/// Раздачи привязываются к играм так же, как для вебхука `game.linked`.
+	pub async fn check_repacks(&self) -> Result<(), ErrorCode> {
+		let _guard = self.repacks_lock.lock().await;
+		let wishlisted = self.wishlisted().await?;
+		let games = self
+			.prisma_client
+			.wishlist_game()
+			.find_many(vec![wishlist_game::game_id::in_vec(wishlisted.keys().copied().collect())])
+			.exec()
+			.await?;
+
+		let names: Vec<(String, i32)> = games
+			.iter()
+			.filter_map(|game| game.name.as_deref().map(|name| (normalize_name(name), game.game_id)))
+			.filter(|(normalized, _)| !normalized.is_empty())
+			.collect();
+		if names.is_empty() {
+			return Ok(());
+		}
+
+		// Скрытая модератором раздача не должна приходить уведомлением о новом репаке.
+		let hidden = ReportService::all_hidden_links(&self.prisma_client).await?;
+		let mut repackers: HashMap<i32, BTreeSet<String>> = HashMap::new();
+		for torrent in self.prisma_client.torrent().find_many(vec![]).exec().await? {
+			if hidden.contains(&torrent.torrent) {
+				continue;
+			}
+			if let Some(game_id) = link_release(&normalize_name(&torrent.name), &names) {
+				repackers.entry(*game_id).or_default().insert(torrent.repacker);
+			}
+		}
+
+		for game in games {
+			let Some(name) = game.name.clone() else { continue };
+			if !names.iter().any(|(_, game_id)| *game_id == game.game_id) {
+				continue;
+			}
+			let current = repackers.remove(&game.game_id).unwrap_or_default();
+
+			let seen: Option<BTreeSet<String>> = game
+				.repackers
+				.as_deref()
+				.map(|repackers| repackers.split(',').filter(|r| !r.is_empty()).map(str::to_string).collect());
+			if let Some(seen) = &seen {
+				let user_ids = wishlisted.get(&game.game_id).map(Vec::as_slice).unwrap_or_default();
+				for repacker in current.difference(seen) {
+					NotificationService::notify(
+						&self.prisma_client,
+						user_ids,
+						game.game_id,
+						NotificationKind::Repack,
+						&format!("repack:{}", repacker),
+						&format!("New {} repack available", repacker),
+						Some(name.clone()),
+					)
+					.await;
+				}
+			}
+
+			// Репакер, пропавший из индекса после неудачного парсинга, не должен прийти повторно.
+			let known: BTreeSet<String> = seen.unwrap_or_default().union(&current).cloned().collect();
+			let known = known.into_iter().collect::<Vec<_>>().join(",");
+			if game.repackers.as_deref() != Some(known.as_str()) {
+				self.prisma_client
+					.wishlist_game()
+					.update(
+						wishlist_game::game_id::equals(game.game_id),
+						vec![wishlist_game::repackers::set(Some(known))],
+					)
+					.exec()
+					.await?;
+			}
+		}
+		Ok(())
+	}
+
+	pub async fn run(&self) {
+		if let Err(e) = self.check_wishlist().await {
+			warn!("Ошибка при проверке вишлистов: {:?}", e);
+		}
+		if let Err(e) = self.check_repacks().await {
+			warn!("Ошибка при поиске новых репаков: {:?}", e);
+		}
+		info!("Проверка вишлистов завершена");
+	}
+}
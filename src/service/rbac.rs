@@ -0,0 +1,196 @@
+use crate::model::dto::rbac::{Permission, RoleInfo};
+use crate::model::error::ErrorCode;
+use crate::prisma::{role, user, user_identity, PrismaClient};
+use log::{info, warn};
+use std::collections::HashSet;
+use std::env;
+
+pub const ADMIN_ROLE: &str = "admin";
+pub const MODERATOR_ROLE: &str = "moderator";
+
+/// Встроенные роли пересоздаются при каждом запуске, чтобы набор прав совпадал с кодом.
+fn builtin_roles() -> Vec<(&'static str, Vec<Permission>)> {
+	vec![
+		(ADMIN_ROLE, Permission::ALL.to_vec()),
+		(MODERATOR_ROLE, vec![Permission::ModerateContent]),
+	]
+}
+
+/// Разбирает список `провайдер:subject` через запятую, например `yandex:alice,github:583231`.
+/// Голый логин не принимается: логин на сайте может занять кто угодно, войдя через другого провайдера.
+fn parse_admin_identities(value: &str) -> Vec<(String, String)> {
+	value
+		.split(',')
+		.map(str::trim)
+		.filter(|entry| !entry.is_empty())
+		.filter_map(|entry| match entry.split_once(':') {
+			Some((provider, subject)) if !provider.trim().is_empty() && !subject.trim().is_empty() => {
+				Some((provider.trim().to_lowercase(), subject.trim().to_string()))
+			}
+			_ => {
+				warn!("Пропущена запись VEK_ADMIN_IDENTITIES без провайдера: {}", entry);
+				None
+			}
+		})
+		.collect()
+}
+
+/// Аккаунты из `VEK_ADMIN_IDENTITIES`, получающие роль admin, пока в системе нет ни одного админа.
+fn configured_admins() -> Vec<(String, String)> {
+	env::var("VEK_ADMIN_IDENTITIES")
+		.map(|value| parse_admin_identities(&value))
+		.unwrap_or_default()
+}
+
+pub struct RbacService;
+
+impl RbacService {
+	fn join(permissions: &[Permission]) -> String {
+		permissions.iter().map(Permission::as_str).collect::<Vec<_>>().join(",")
+	}
+
+	fn info(role: role::Data) -> RoleInfo {
+		RoleInfo {
+			name: role.name,
+			permissions: role.permissions.split(',').filter_map(Permission::parse).collect(),
+		}
+	}
+
+	pub async fn ensure_builtin_roles(client: &PrismaClient) -> Result<(), ErrorCode> {
+		for (name, permissions) in builtin_roles() {
+			let permissions = Self::join(&permissions);
+			client
+				.role()
+				.upsert(
+					role::name::equals(name.to_string()),
+					role::create(name.to_string(), permissions.clone(), vec![]),
+					vec![role::permissions::set(permissions)],
+				)
+				.exec()
+				.await?;
+		}
+		Ok(())
+	}
+
+	async fn admin_count(client: &PrismaClient) -> Result<i64, ErrorCode> {
+		Ok(client
+			.user()
+			.count(vec![user::roles::some(vec![role::name::equals(ADMIN_ROLE.to_string())])])
+			.exec()
+			.await?)
+	}
+
+	/// Назначает админов из конфигурации, если их ещё нет. Вызывается при старте
+	/// и при входе, так как на первом запуске пользователей может ещё не быть.
+	pub async fn bootstrap_admins(client: &PrismaClient) -> Result<(), ErrorCode> {
+		let admins = configured_admins();
+		if admins.is_empty() || Self::admin_count(client).await? > 0 {
+			return Ok(());
+		}
+
+		let identities = client
+			.user_identity()
+			.find_many(vec![user_identity::WhereParam::Or(
+				admins
+					.into_iter()
+					.map(|(provider, subject)| {
+						user_identity::WhereParam::And(vec![
+							user_identity::provider::equals(provider),
+							user_identity::subject::equals(subject),
+						])
+					})
+					.collect(),
+			)])
+			.exec()
+			.await?;
+		for identity in identities {
+			Self::grant_role(client, &identity.user_id, ADMIN_ROLE).await?;
+			info!(
+				"Пользователь ID: {} назначен администратором из конфигурации ({}:{})",
+				identity.user_id, identity.provider, identity.subject
+			);
+		}
+		Ok(())
+	}
+
+	pub async fn list_roles(client: &PrismaClient) -> Result<Vec<RoleInfo>, ErrorCode> {
+		let roles = client.role().find_many(vec![]).exec().await?;
+		Ok(roles.into_iter().map(Self::info).collect())
+	}
+
+	pub async fn user_roles(client: &PrismaClient, user_id: &str) -> Result<Vec<RoleInfo>, ErrorCode> {
+		let roles = client
+			.role()
+			.find_many(vec![role::users::some(vec![user::id::equals(user_id.to_string())])])
+			.exec()
+			.await?;
+		Ok(roles.into_iter().map(Self::info).collect())
+	}
+
+	pub async fn user_permissions(client: &PrismaClient, user_id: &str) -> Result<HashSet<Permission>, ErrorCode> {
+		Ok(Self::user_roles(client, user_id)
+			.await?
+			.into_iter()
+			.flat_map(|role| role.permissions)
+			.collect())
+	}
+
+	pub async fn grant_role(client: &PrismaClient, user_id: &str, role_name: &str) -> Result<Vec<RoleInfo>, ErrorCode> {
+		client
+			.role()
+			.find_unique(role::name::equals(role_name.to_string()))
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)?;
+
+		client
+			.user()
+			.update(
+				user::id::equals(user_id.to_string()),
+				vec![user::roles::connect(vec![role::name::equals(role_name.to_string())])],
+			)
+			.exec()
+			.await?;
+
+		Self::user_roles(client, user_id).await
+	}
+
+	pub async fn revoke_role(client: &PrismaClient, user_id: &str, role_name: &str) -> Result<Vec<RoleInfo>, ErrorCode> {
+		let roles = Self::user_roles(client, user_id).await?;
+		if !roles.iter().any(|role| role.name == role_name) {
+			return Err(ErrorCode::DATABASE002);
+		}
+		if role_name == ADMIN_ROLE && Self::admin_count(client).await? <= 1 {
+			warn!("Попытка снять роль с последнего администратора {}", user_id);
+			return Err(ErrorCode::BADREQUEST("Cannot revoke the last admin".to_string()));
+		}
+
+		client
+			.user()
+			.update(
+				user::id::equals(user_id.to_string()),
+				vec![user::roles::disconnect(vec![role::name::equals(role_name.to_string())])],
+			)
+			.exec()
+			.await?;
+
+		Self::user_roles(client, user_id).await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn admin_identities_need_a_provider() {
+		assert_eq!(
+			parse_admin_identities(" yandex:alice, GitHub:583231 ,,alice, :bob, steam: "),
+			vec![
+				("yandex".to_string(), "alice".to_string()),
+				("github".to_string(), "583231".to_string()),
+			]
+		);
+		assert!(parse_admin_identities("").is_empty());
+	}
+}
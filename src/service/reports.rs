@@ -0,0 +1,301 @@
+use crate::model::dto::torrent::{
+	HiddenReleaseInfo, HideReleaseRequest, ReleaseReportStats, ReportKind, ReportNote, ReportReleaseRequest,
+	ReportReviewQuery, ReportedRelease,
+};
+use crate::model::error::ErrorCode;
+use crate::prisma::{hidden_release, release_report, torrent, user, PrismaClient};
+use log::info;
+use prisma_client_rust::{raw, Direction, PrismaValue};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+const MAX_NOTES_LENGTH: usize = 1000;
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+/// Сколько последних отчётов показывать у релиза в списке модерации.
+const NOTES_PER_RELEASE: usize = 10;
+
+/// Строка сводки отчётов по одной ссылке из `review_list`.
+#[derive(Deserialize)]
+struct ReportCounts {
+	link: String,
+	#[serde(flatten)]
+	stats: ReleaseReportStats,
+}
+
+/// Отчёты о качестве раздач. Отчёт привязан к ссылке раздачи, а не к `Torrent.id`,
+/// потому что индекс торрентов пересобирается при каждом запуске.
+pub struct ReportService;
+
+impl ReportService {
+	fn validate_text(value: Option<String>, field: &str) -> Result<Option<String>, ErrorCode> {
+		let Some(value) = value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty()) else {
+			return Ok(None);
+		};
+		if value.chars().count() > MAX_NOTES_LENGTH {
+			return Err(ErrorCode::BADREQUEST(format!("{} must be at most {} characters", field, MAX_NOTES_LENGTH)));
+		}
+		Ok(Some(value))
+	}
+
+	async fn indexed_release(client: &PrismaClient, link: &str) -> Result<torrent::Data, ErrorCode> {
+		client
+			.torrent()
+			.find_first(vec![torrent::torrent::equals(link.to_string())])
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)
+	}
+
+	/// Сводка отчётов по каждой из ссылок, ссылки без отчётов в ответ не попадают.
+	pub async fn stats_for(client: &PrismaClient, links: &[String]) -> Result<HashMap<String, ReleaseReportStats>, ErrorCode> {
+		if links.is_empty() {
+			return Ok(HashMap::new());
+		}
+
+		let reports = client
+			.release_report()
+			.find_many(vec![release_report::link::in_vec(links.to_vec())])
+			.exec()
+			.await?;
+
+		let mut stats: HashMap<String, ReleaseReportStats> = HashMap::new();
+		for report in reports {
+			if let Some(kind) = ReportKind::parse(&report.kind) {
+				stats.entry(report.link).or_default().add(kind);
+			}
+		}
+		Ok(stats)
+	}
+
+	pub async fn stats(client: &PrismaClient, link: &str) -> Result<ReleaseReportStats, ErrorCode> {
+		Ok(Self::stats_for(client, &[link.to_string()])
+			.await?
+			.remove(link)
+			.unwrap_or_default())
+	}
+
+	/// Один отчёт на пользователя и раздачу, повторный заменяет предыдущий.
+	pub async fn report(
+		client: &PrismaClient,
+		user_id: &str,
+		request: ReportReleaseRequest,
+	) -> Result<ReleaseReportStats, ErrorCode> {
+		let notes = Self::validate_text(request.notes, "notes")?;
+		let release = Self::indexed_release(client, &request.torrent).await?;
+
+		let kind = request.kind.as_str().to_string();
+		client
+			.release_report()
+			.upsert(
+				release_report::user_id_link(user_id.to_string(), release.torrent.clone()),
+				release_report::create(
+					release.torrent.clone(),
+					kind.clone(),
+					user::id::equals(user_id.to_string()),
+					vec![release_report::notes::set(notes.clone())],
+				),
+				vec![release_report::kind::set(kind), release_report::notes::set(notes)],
+			)
+			.exec()
+			.await?;
+
+		info!(
+			"Пользователь ID: {} отметил раздачу {} ({}) как {}",
+			user_id, release.name, release.repacker, request.kind.as_str()
+		);
+		Self::stats(client, &release.torrent).await
+	}
+
+	pub async fn remove_report(client: &PrismaClient, user_id: &str, link: &str) -> Result<ReleaseReportStats, ErrorCode> {
+		let deleted = client
+			.release_report()
+			.delete_many(vec![
+				release_report::user_id::equals(user_id.to_string()),
+				release_report::link::equals(link.to_string()),
+			])
+			.exec()
+			.await?;
+
+		if deleted == 0 {
+			return Err(ErrorCode::DATABASE002);
+		}
+		Self::stats(client, link).await
+	}
+
+	/// Список для модерации: раздачи с отчётами, сначала с наибольшим числом жалоб, среди равных свежие.
+	/// Отчёты сводятся по ссылкам в базе, в память попадает только запрошенная страница.
+	pub async fn review_list(client: &PrismaClient, query: ReportReviewQuery) -> Result<Vec<ReportedRelease>, ErrorCode> {
+		let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+		if page_size == 0 || page_size > MAX_PAGE_SIZE {
+			return Err(ErrorCode::BADREQUEST(format!("page_size must be between 1 and {}", MAX_PAGE_SIZE)));
+		}
+		let page = query.page.unwrap_or(1).max(1);
+		let kind = query.kind.map(|kind| kind.as_str()).unwrap_or_default().to_string();
+
+		let counts: Vec<ReportCounts> = client
+			._query_raw(raw!(
+				"SELECT link, \
+					SUM(kind = 'working') AS working, \
+					SUM(kind = 'broken') AS broken, \
+					SUM(kind = 'wrong_game') AS wrong_game, \
+					SUM(kind = 'suspicious') AS suspicious \
+				FROM ReleaseReport \
+				GROUP BY link \
+				HAVING {} = '' OR SUM(kind = {}) > 0 \
+				ORDER BY SUM(kind IN ('broken', 'wrong_game', 'suspicious')) DESC, MAX(updated_at) DESC, link \
+				LIMIT {} OFFSET {}",
+				PrismaValue::String(kind.clone()),
+				PrismaValue::String(kind),
+				PrismaValue::Int(page_size as i64),
+				PrismaValue::Int(((page - 1) * page_size) as i64)
+			))
+			.exec()
+			.await?;
+
+		let links: Vec<String> = counts.iter().map(|counts| counts.link.clone()).collect();
+		let reports = client
+			.release_report()
+			.find_many(vec![release_report::link::in_vec(links.clone())])
+			.order_by(release_report::updated_at::order(Direction::Desc))
+			.exec()
+			.await?;
+		let mut notes: HashMap<String, Vec<release_report::Data>> = HashMap::new();
+		for report in reports {
+			let release = notes.entry(report.link.clone()).or_default();
+			if release.len() < NOTES_PER_RELEASE {
+				release.push(report);
+			}
+		}
+
+		let hidden = Self::hidden_links(client, &links).await?;
+		let indexed: HashMap<String, torrent::Data> = client
+			.torrent()
+			.find_many(vec![torrent::torrent::in_vec(links)])
+			.exec()
+			.await?
+			.into_iter()
+			.map(|release| (release.torrent.clone(), release))
+			.collect();
+
+		Ok(counts
+			.into_iter()
+			.map(|counts| {
+				let reports = notes.remove(&counts.link).unwrap_or_default();
+				let indexed = indexed.get(&counts.link);
+				ReportedRelease {
+					name: indexed.map(|indexed| indexed.name.clone()),
+					repacker: indexed.map(|indexed| indexed.repacker.clone()),
+					stats: counts.stats,
+					hidden: hidden.contains(&counts.link),
+					last_reported: reports
+						.first()
+						.map(|report| report.updated_at.to_rfc3339())
+						.unwrap_or_default(),
+					reports: reports
+						.into_iter()
+						.filter_map(|report| {
+							Some(ReportNote {
+								kind: ReportKind::parse(&report.kind)?,
+								user_id: report.user_id,
+								notes: report.notes,
+								updated_at: report.updated_at.to_rfc3339(),
+							})
+						})
+						.collect(),
+					torrent: counts.link,
+				}
+			})
+			.collect())
+	}
+
+	/// Какие из ссылок скрыты модераторами.
+	pub async fn hidden_links(client: &PrismaClient, links: &[String]) -> Result<HashSet<String>, ErrorCode> {
+		if links.is_empty() {
+			return Ok(HashSet::new());
+		}
+
+		Ok(client
+			.hidden_release()
+			.find_many(vec![hidden_release::link::in_vec(links.to_vec())])
+			.exec()
+			.await?
+			.into_iter()
+			.map(|hidden| hidden.link)
+			.collect())
+	}
+
+	/// Все скрытые ссылки, для фоновых задач, которые проходят по всему индексу.
+	/// Скрывают раздачи вручную, поэтому таблица остаётся небольшой.
+	pub async fn all_hidden_links(client: &PrismaClient) -> Result<HashSet<String>, ErrorCode> {
+		Ok(client
+			.hidden_release()
+			.find_many(vec![])
+			.exec()
+			.await?
+			.into_iter()
+			.map(|hidden| hidden.link)
+			.collect())
+	}
+
+	pub async fn list_hidden(client: &PrismaClient) -> Result<Vec<HiddenReleaseInfo>, ErrorCode> {
+		let hidden = client
+			.hidden_release()
+			.find_many(vec![])
+			.order_by(hidden_release::hidden_at::order(Direction::Desc))
+			.exec()
+			.await?;
+
+		Ok(hidden
+			.into_iter()
+			.map(|hidden| HiddenReleaseInfo {
+				torrent: hidden.link,
+				reason: hidden.reason,
+				hidden_by: hidden.hidden_by,
+				hidden_at: hidden.hidden_at.to_rfc3339(),
+			})
+			.collect())
+	}
+
+	/// Скрытая раздача пропадает из `/api/torrent`, даже если провайдер найдёт её снова.
+	pub async fn hide(client: &PrismaClient, moderator_id: &str, request: HideReleaseRequest) -> Result<(), ErrorCode> {
+		let reason = Self::validate_text(request.reason, "reason")?;
+		if request.torrent.trim().is_empty() {
+			return Err(ErrorCode::BADREQUEST("torrent is required".to_string()));
+		}
+
+		client
+			.hidden_release()
+			.upsert(
+				hidden_release::link::equals(request.torrent.clone()),
+				hidden_release::create(
+					request.torrent.clone(),
+					moderator_id.to_string(),
+					vec![hidden_release::reason::set(reason.clone())],
+				),
+				vec![
+					hidden_release::reason::set(reason),
+					hidden_release::hidden_by::set(moderator_id.to_string()),
+				],
+			)
+			.exec()
+			.await?;
+
+		info!("Модератор ID: {} скрыл раздачу {}", moderator_id, request.torrent);
+		Ok(())
+	}
+
+	pub async fn unhide(client: &PrismaClient, moderator_id: &str, link: &str) -> Result<(), ErrorCode> {
+		let deleted = client
+			.hidden_release()
+			.delete_many(vec![hidden_release::link::equals(link.to_string())])
+			.exec()
+			.await?;
+
+		if deleted == 0 {
+			return Err(ErrorCode::DATABASE002);
+		}
+		info!("Модератор ID: {} вернул раздачу {}", moderator_id, link);
+		Ok(())
+	}
+}
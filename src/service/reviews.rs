@@ -0,0 +1,282 @@
+use crate::model::dto::library::LibraryStatus;
+use crate::model::dto::reviews::{
+	CommunityScore, CreateReviewRequest, ReviewInfo, ReviewPage, ReviewQuery, ReviewSort, UpdateReviewRequest,
+};
+use crate::model::error::ErrorCode;
+use crate::prisma::{library_entry, review, review_vote, user, PrismaClient};
+use log::info;
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use prisma_client_rust::Direction;
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+const MAX_BODY_LENGTH: usize = 5000;
+
+review::select!(review_rating { rating });
+
+pub struct ReviewService;
+
+impl ReviewService {
+	fn info(review: review::Data) -> ReviewInfo {
+		let author = review
+			.user
+			.as_ref()
+			.map(|user| user.display_name.clone().unwrap_or_else(|| user.login.clone()))
+			.unwrap_or_default();
+
+		ReviewInfo {
+			id: review.id,
+			game_id: review.game_id,
+			user_id: review.user_id,
+			author,
+			rating: review.rating,
+			body: review.body,
+			helpful: review.helpful,
+			created_at: review.created_at.to_rfc3339(),
+			updated_at: review.updated_at.to_rfc3339(),
+		}
+	}
+
+	fn validate_rating(rating: i32) -> Result<i32, ErrorCode> {
+		if !(1..=5).contains(&rating) {
+			return Err(ErrorCode::BADREQUEST("rating must be between 1 and 5".to_string()));
+		}
+		Ok(rating)
+	}
+
+	/// Пустой текст превращается в оценку без отзыва.
+	fn validate_body(body: String) -> Result<Option<String>, ErrorCode> {
+		let body = body.trim().to_string();
+		if body.chars().count() > MAX_BODY_LENGTH {
+			return Err(ErrorCode::BADREQUEST(format!("body must be at most {} characters", MAX_BODY_LENGTH)));
+		}
+		Ok(Some(body).filter(|body| !body.is_empty()))
+	}
+
+	async fn find_review(client: &PrismaClient, game_id: i32, review_id: &str) -> Result<review::Data, ErrorCode> {
+		client
+			.review()
+			.find_first(vec![
+				review::id::equals(review_id.to_string()),
+				review::game_id::equals(game_id),
+			])
+			.with(review::user::fetch())
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)
+	}
+
+	pub async fn community_score(client: &PrismaClient, game_id: i32) -> Result<CommunityScore, ErrorCode> {
+		let ratings = client
+			.review()
+			.find_many(vec![review::game_id::equals(game_id)])
+			.select(review_rating::select())
+			.exec()
+			.await?;
+
+		let mut score = CommunityScore::default();
+		let mut total = 0i64;
+		for rating in ratings.iter().map(|review| review.rating).filter(|rating| (1..=5).contains(rating)) {
+			score.distribution[(rating - 1) as usize] += 1;
+			score.count += 1;
+			total += rating as i64;
+		}
+		if score.count > 0 {
+			// Два знака после запятой, как у `rating` в ответах RAWG.
+			score.average = Some((total as f64 / score.count as f64 * 100.0).round() / 100.0);
+		}
+		Ok(score)
+	}
+
+	pub async fn list_reviews(client: &PrismaClient, game_id: i32, query: ReviewQuery) -> Result<ReviewPage, ErrorCode> {
+		let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+		if page_size == 0 || page_size > MAX_PAGE_SIZE {
+			return Err(ErrorCode::BADREQUEST(format!("page_size must be between 1 and {}", MAX_PAGE_SIZE)));
+		}
+		let page = query.page.unwrap_or(1).max(1);
+
+		let mut request = client.review().find_many(vec![review::game_id::equals(game_id)]);
+		if query.sort.unwrap_or_default() == ReviewSort::Helpful {
+			request = request.order_by(review::helpful::order(Direction::Desc));
+		}
+		let reviews = request
+			.order_by(review::created_at::order(Direction::Desc))
+			.skip(((page - 1) * page_size) as i64)
+			.take(page_size as i64)
+			.with(review::user::fetch())
+			.exec()
+			.await?;
+
+		Ok(ReviewPage {
+			score: Self::community_score(client, game_id).await?,
+			page,
+			page_size,
+			results: reviews.into_iter().map(Self::info).collect(),
+		})
+	}
+
+	pub async fn get_review(client: &PrismaClient, game_id: i32, review_id: &str) -> Result<ReviewInfo, ErrorCode> {
+		Ok(Self::info(Self::find_review(client, game_id, review_id).await?))
+	}
+
+	/// Оценить можно только игру из своей библиотеки, вишлист не считается.
+	pub async fn create_review(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		request: CreateReviewRequest,
+	) -> Result<ReviewInfo, ErrorCode> {
+		let rating = Self::validate_rating(request.rating)?;
+		let body = match request.body {
+			Some(body) => Self::validate_body(body)?,
+			None => None,
+		};
+
+		let owned = client
+			.library_entry()
+			.find_unique(library_entry::user_id_game_id(user_id.to_string(), game_id))
+			.exec()
+			.await?
+			.map(|entry| entry.status != LibraryStatus::Wishlist.as_str())
+			.unwrap_or(false);
+		if !owned {
+			return Err(ErrorCode::BADREQUEST("Only games in your library can be reviewed".to_string()));
+		}
+
+		let result = client
+			.review()
+			.create(
+				game_id,
+				rating,
+				user::id::equals(user_id.to_string()),
+				vec![review::body::set(body)],
+			)
+			.with(review::user::fetch())
+			.exec()
+			.await;
+
+		match result {
+			Ok(review) => {
+				info!("Пользователь ID: {} оценил игру {} на {}", user_id, game_id, rating);
+				Ok(Self::info(review))
+			}
+			Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => {
+				Err(ErrorCode::BADREQUEST("Game is already reviewed, edit the existing review".to_string()))
+			}
+			Err(e) => Err(e.into()),
+		}
+	}
+
+	pub async fn update_review(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		review_id: &str,
+		request: UpdateReviewRequest,
+	) -> Result<ReviewInfo, ErrorCode> {
+		let review = Self::find_review(client, game_id, review_id).await?;
+		if review.user_id != user_id {
+			return Err(ErrorCode::AUTH005);
+		}
+
+		let mut params = vec![];
+		if let Some(rating) = request.rating {
+			params.push(review::rating::set(Self::validate_rating(rating)?));
+		}
+		if let Some(body) = request.body {
+			params.push(review::body::set(Self::validate_body(body)?));
+		}
+
+		let review = client
+			.review()
+			.update(review::id::equals(review.id), params)
+			.with(review::user::fetch())
+			.exec()
+			.await?;
+		Ok(Self::info(review))
+	}
+
+	/// Автор удаляет свой отзыв, модератор с `content:moderate` — любой.
+	pub async fn delete_review(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		review_id: &str,
+		moderator: bool,
+	) -> Result<(), ErrorCode> {
+		let review = Self::find_review(client, game_id, review_id).await?;
+		if review.user_id != user_id && !moderator {
+			return Err(ErrorCode::AUTH005);
+		}
+
+		client.review().delete(review::id::equals(review.id.clone())).exec().await?;
+		if review.user_id != user_id {
+			info!("Модератор ID: {} удалил отзыв {} пользователя {}", user_id, review.id, review.user_id);
+		}
+		Ok(())
+	}
+
+	/// Голос «полезно» от пользователя учитывается один раз, за свой отзыв голосовать нельзя.
+	pub async fn vote_helpful(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		review_id: &str,
+	) -> Result<ReviewInfo, ErrorCode> {
+		let review = Self::find_review(client, game_id, review_id).await?;
+		if review.user_id == user_id {
+			return Err(ErrorCode::BADREQUEST("You cannot vote for your own review".to_string()));
+		}
+
+		let result = client
+			.review_vote()
+			.create(
+				review::id::equals(review.id.clone()),
+				user::id::equals(user_id.to_string()),
+				vec![],
+			)
+			.exec()
+			.await;
+		match result {
+			Ok(_) => {}
+			Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => return Ok(Self::info(review)),
+			Err(e) => return Err(e.into()),
+		}
+
+		let review = client
+			.review()
+			.update(review::id::equals(review.id), vec![review::helpful::increment(1)])
+			.with(review::user::fetch())
+			.exec()
+			.await?;
+		Ok(Self::info(review))
+	}
+
+	pub async fn remove_vote(
+		client: &PrismaClient,
+		user_id: &str,
+		game_id: i32,
+		review_id: &str,
+	) -> Result<ReviewInfo, ErrorCode> {
+		let review = Self::find_review(client, game_id, review_id).await?;
+		let deleted = client
+			.review_vote()
+			.delete_many(vec![
+				review_vote::review_id::equals(review.id.clone()),
+				review_vote::user_id::equals(user_id.to_string()),
+			])
+			.exec()
+			.await?;
+		if deleted == 0 {
+			return Ok(Self::info(review));
+		}
+
+		let review = client
+			.review()
+			.update(review::id::equals(review.id), vec![review::helpful::decrement(1)])
+			.with(review::user::fetch())
+			.exec()
+			.await?;
+		Ok(Self::info(review))
+	}
+}
@@ -0,0 +1,402 @@
+use crate::model::dto::saves::{SaveGameSummary, SaveInfo, SaveOverview};
+use crate::model::error::ErrorCode;
+use crate::modules::helpers::random_token;
+use crate::prisma::{cloud_save, user, PrismaClient};
+use log::{error, info, warn};
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use prisma_client_rust::Direction;
+use actix_web::web::Bytes;
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Размер одного архива, больше не принимается ни при какой квоте.
+pub const MAX_SAVE_BYTES: usize = 256 * 1024 * 1024;
+const DEFAULT_MAX_VERSIONS: usize = 5;
+const DEFAULT_QUOTA_MB: i64 = 1024;
+const MAX_DEVICE_LENGTH: usize = 64;
+
+/// Архив, который отдаётся клиенту по частям, не загружаясь в память целиком.
+pub type SaveStream = Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>;
+
+/// Объект, который записывается в хранилище по частям. До `commit` он не виден по ключу,
+/// а брошенная без `commit` запись удаляется.
+#[async_trait::async_trait]
+pub trait SaveUpload: Send {
+	async fn write(&mut self, chunk: &[u8]) -> Result<(), String>;
+
+	async fn commit(self: Box<Self>) -> Result<(), String>;
+
+	async fn abort(self: Box<Self>);
+}
+
+/// Хранилище архивов сохранений. Ключи выдаёт `SaveService`, метаданные лежат в базе.
+#[async_trait::async_trait]
+pub trait SaveStorage: Send + Sync {
+	fn name(&self) -> &'static str;
+
+	/// Начинает запись объекта, чтобы архив не приходилось держать в памяти целиком.
+	async fn begin_put(&self, key: &str) -> Result<Box<dyn SaveUpload>, String>;
+
+	/// `None`, если объекта с таким ключом нет.
+	async fn get(&self, key: &str) -> Result<Option<SaveStream>, String>;
+
+	/// Удаление отсутствующего объекта не считается ошибкой.
+	async fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/// Куда встаёт загруженный архив.
+enum Placement {
+	/// Архив совпал с последней версией.
+	Unchanged(cloud_save::Data),
+	/// Номер новой версии и версии, которые она вытеснит из истории.
+	New(i32, Vec<cloud_save::Data>),
+}
+
+/// Облачные сохранения: версии архивов по пользователю и RAWG id игры.
+pub struct SaveService {
+	prisma_client: Arc<PrismaClient>,
+	storage: Box<dyn SaveStorage>,
+	max_versions: usize,
+	quota_bytes: i64,
+}
+
+impl SaveService {
+	/// Число хранимых версий и квота задаются через `VEK_SAVES_MAX_VERSIONS` и `VEK_SAVES_QUOTA_MB`.
+	pub fn new(prisma_client: Arc<PrismaClient>, storage: Box<dyn SaveStorage>) -> Self {
+		let max_versions = env::var("VEK_SAVES_MAX_VERSIONS")
+			.ok()
+			.and_then(|value| value.parse::<usize>().ok())
+			.unwrap_or(DEFAULT_MAX_VERSIONS)
+			.max(1);
+		let quota_mb = env::var("VEK_SAVES_QUOTA_MB")
+			.ok()
+			.and_then(|value| value.parse::<i64>().ok())
+			.unwrap_or(DEFAULT_QUOTA_MB)
+			.max(1);
+
+		info!("Хранилище сохранений: {}", storage.name());
+		SaveService {
+			prisma_client,
+			storage,
+			max_versions,
+			quota_bytes: quota_mb * 1024 * 1024,
+		}
+	}
+
+	fn info(save: cloud_save::Data) -> SaveInfo {
+		SaveInfo {
+			id: save.id,
+			game_id: save.game_id,
+			version: save.version,
+			hash: save.hash,
+			size: save.size,
+			device: save.device,
+			created_at: save.created_at.to_rfc3339(),
+		}
+	}
+
+	/// Версии игры от новой к старой.
+	async fn versions(&self, user_id: &str, game_id: i32) -> Result<Vec<cloud_save::Data>, ErrorCode> {
+		Ok(self
+			.prisma_client
+			.cloud_save()
+			.find_many(vec![
+				cloud_save::user_id::equals(user_id.to_string()),
+				cloud_save::game_id::equals(game_id),
+			])
+			.order_by(cloud_save::version::order(Direction::Desc))
+			.exec()
+			.await?)
+	}
+
+	async fn used_bytes(&self, user_id: &str) -> Result<i64, ErrorCode> {
+		let saves = self
+			.prisma_client
+			.cloud_save()
+			.find_many(vec![cloud_save::user_id::equals(user_id.to_string())])
+			.exec()
+			.await?;
+		Ok(saves.iter().map(|save| save.size as i64).sum())
+	}
+
+	/// Удаляет запись, затем архив. Архив без записи никому не виден, поэтому ошибка хранилища только логируется.
+	async fn remove(&self, save: cloud_save::Data) -> Result<(), ErrorCode> {
+		self.prisma_client
+			.cloud_save()
+			.delete(cloud_save::id::equals(save.id.clone()))
+			.exec()
+			.await?;
+		if let Err(e) = self.storage.delete(&save.storage_key).await {
+			warn!("Не удалось удалить архив сохранения {}: {}", save.storage_key, e);
+		}
+		Ok(())
+	}
+
+	pub async fn overview(&self, user_id: &str) -> Result<SaveOverview, ErrorCode> {
+		let saves = self
+			.prisma_client
+			.cloud_save()
+			.find_many(vec![cloud_save::user_id::equals(user_id.to_string())])
+			.order_by(cloud_save::version::order(Direction::Desc))
+			.exec()
+			.await?;
+
+		let used = saves.iter().map(|save| save.size as i64).sum();
+		let mut games: Vec<SaveGameSummary> = vec![];
+		for save in saves {
+			let size = save.size as i64;
+			match games.iter_mut().find(|game| game.game_id == save.game_id) {
+				Some(game) => {
+					game.versions += 1;
+					game.size += size;
+				}
+				None => games.push(SaveGameSummary {
+					game_id: save.game_id,
+					latest: Self::info(save),
+					versions: 1,
+					size,
+				}),
+			}
+		}
+		games.sort_by(|a, b| b.latest.created_at.cmp(&a.latest.created_at));
+
+		Ok(SaveOverview {
+			used,
+			quota: self.quota_bytes,
+			games,
+		})
+	}
+
+	pub async fn list_versions(&self, user_id: &str, game_id: i32) -> Result<Vec<SaveInfo>, ErrorCode> {
+		Ok(self.versions(user_id, game_id).await?.into_iter().map(Self::info).collect())
+	}
+
+	/// Архив указанной версии или последней, если `version` не задана.
+	pub async fn get_save(
+		&self,
+		user_id: &str,
+		game_id: i32,
+		version: Option<i32>,
+	) -> Result<(SaveInfo, SaveStream), ErrorCode> {
+		let mut filters = vec![
+			cloud_save::user_id::equals(user_id.to_string()),
+			cloud_save::game_id::equals(game_id),
+		];
+		if let Some(version) = version {
+			filters.push(cloud_save::version::equals(version));
+		}
+
+		let save = self
+			.prisma_client
+			.cloud_save()
+			.find_first(filters)
+			.order_by(cloud_save::version::order(Direction::Desc))
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)?;
+
+		let stream = match self.storage.get(&save.storage_key).await {
+			Ok(Some(stream)) => stream,
+			Ok(None) => {
+				error!("Архив сохранения {} отсутствует в хранилище", save.storage_key);
+				return Err(ErrorCode::INTERNAL001);
+			}
+			Err(e) => {
+				error!("Не удалось прочитать архив сохранения {}: {}", save.storage_key, e);
+				return Err(ErrorCode::INTERNAL001);
+			}
+		};
+		Ok((Self::info(save), stream))
+	}
+
+	/// Сколько байт может занять новая версия игры: свободное место в квоте плюс версии,
+	/// которые она вытеснит из истории.
+	async fn upload_budget(&self, user_id: &str, game_id: i32) -> Result<i64, ErrorCode> {
+		let versions = self.versions(user_id, game_id).await?;
+		let freed: i64 = versions.iter().skip(self.max_versions - 1).map(|save| save.size as i64).sum();
+		Ok(self.quota_bytes - self.used_bytes(user_id).await? + freed)
+	}
+
+	/// Принимает архив в хранилище по частям, считая размер и хэш на лету. Запись прерывается,
+	/// как только архив перерос `MAX_SAVE_BYTES` или оставшуюся квоту `budget`.
+	async fn receive<S>(
+		&self,
+		storage_key: &str,
+		budget: i64,
+		mut body: S,
+	) -> Result<(Box<dyn SaveUpload>, String, usize), ErrorCode>
+	where
+		S: Stream<Item = Result<Bytes, String>> + Unpin,
+	{
+		let mut upload = self.storage.begin_put(storage_key).await.map_err(|e| {
+			error!("Не удалось начать запись архива сохранения {}: {}", storage_key, e);
+			ErrorCode::INTERNAL001
+		})?;
+
+		let mut hasher = Sha256::new();
+		let mut size = 0;
+		while let Some(chunk) = body.next().await {
+			let chunk = match chunk {
+				Ok(chunk) => chunk,
+				Err(e) => {
+					upload.abort().await;
+					return Err(ErrorCode::BADREQUEST(format!("Failed to read request body: {}", e)));
+				}
+			};
+			size += chunk.len();
+			if size > MAX_SAVE_BYTES {
+				upload.abort().await;
+				return Err(ErrorCode::PAYLOAD001);
+			}
+			if size as i64 > budget {
+				upload.abort().await;
+				return Err(ErrorCode::QUOTA001);
+			}
+			hasher.update(&chunk);
+			if let Err(e) = upload.write(&chunk).await {
+				error!("Не удалось записать архив сохранения {}: {}", storage_key, e);
+				upload.abort().await;
+				return Err(ErrorCode::INTERNAL001);
+			}
+		}
+
+		let hash = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+		Ok((upload, hash, size))
+	}
+
+	async fn place(
+		&self,
+		user_id: &str,
+		game_id: i32,
+		expected_version: Option<i32>,
+		hash: &str,
+		size: usize,
+	) -> Result<Placement, ErrorCode> {
+		let mut versions = self.versions(user_id, game_id).await?;
+		let latest_version = versions.first().map(|save| save.version).unwrap_or(0);
+		if versions.first().map(|save| save.hash == hash).unwrap_or(false) {
+			return Ok(Placement::Unchanged(versions.remove(0)));
+		}
+		if expected_version.map(|expected| expected != latest_version).unwrap_or(false) {
+			return Err(ErrorCode::PRECONDITION001);
+		}
+
+		// Версии, которые новая вытеснит из истории, в квоту не засчитываются. Квота проверяется
+		// ещё раз, потому что параллельная загрузка могла занять место, пока читалось тело.
+		let pruned = versions.split_off(versions.len().min(self.max_versions - 1));
+		let freed: i64 = pruned.iter().map(|save| save.size as i64).sum();
+		if self.used_bytes(user_id).await? - freed + size as i64 > self.quota_bytes {
+			return Err(ErrorCode::QUOTA001);
+		}
+		Ok(Placement::New(latest_version + 1, pruned))
+	}
+
+	/// Сохраняет новую версию поверх `expected_version`. Если другое устройство успело загрузить
+	/// свою версию с той же базы, возвращается конфликт, и клиент сам решает, какую оставить.
+	/// `None` соответствует `If-Match: *`. Повторная загрузка того же архива возвращает последнюю
+	/// версию без создания новой, второе значение в ответе говорит, создана ли версия.
+	/// Тело читается потоком и сразу пишется в хранилище, целиком в памяти архив не держится.
+	pub async fn put_save<S>(
+		&self,
+		user_id: &str,
+		game_id: i32,
+		expected_version: Option<i32>,
+		device: Option<String>,
+		body: S,
+	) -> Result<(SaveInfo, bool), ErrorCode>
+	where
+		S: Stream<Item = Result<Bytes, String>> + Unpin,
+	{
+		let device = device.map(|device| device.trim().to_string()).filter(|device| !device.is_empty());
+		if device.as_ref().map(|device| device.chars().count() > MAX_DEVICE_LENGTH).unwrap_or(false) {
+			return Err(ErrorCode::BADREQUEST(format!("device must be at most {} characters", MAX_DEVICE_LENGTH)));
+		}
+
+		let token = random_token(16).map_err(|_| ErrorCode::INTERNAL001)?;
+		let storage_key = format!("{}/{}/{}", user_id, game_id, token);
+		let budget = self.upload_budget(user_id, game_id).await?;
+		let (upload, hash, size) = self.receive(&storage_key, budget, body).await?;
+		if size == 0 {
+			upload.abort().await;
+			return Err(ErrorCode::BADREQUEST("Save archive is empty".to_string()));
+		}
+
+		let (version, pruned) = match self.place(user_id, game_id, expected_version, &hash, size).await {
+			Ok(Placement::New(version, pruned)) => (version, pruned),
+			Ok(Placement::Unchanged(latest)) => {
+				upload.abort().await;
+				return Ok((Self::info(latest), false));
+			}
+			Err(e) => {
+				upload.abort().await;
+				return Err(e);
+			}
+		};
+		if let Err(e) = upload.commit().await {
+			error!("Не удалось записать архив сохранения {}: {}", storage_key, e);
+			return Err(ErrorCode::INTERNAL001);
+		}
+
+		let result = self
+			.prisma_client
+			.cloud_save()
+			.create(
+				game_id,
+				version,
+				hash,
+				size as i32,
+				storage_key.clone(),
+				user::id::equals(user_id.to_string()),
+				vec![cloud_save::device::set(device)],
+			)
+			.exec()
+			.await;
+
+		let save = match result {
+			Ok(save) => save,
+			Err(e) => {
+				let _ = self.storage.delete(&storage_key).await;
+				// Другое устройство заняло этот номер версии между проверкой и записью.
+				if e.is_prisma_error::<UniqueKeyViolation>() {
+					return Err(ErrorCode::PRECONDITION001);
+				}
+				return Err(e.into());
+			}
+		};
+
+		for old in pruned {
+			self.remove(old).await?;
+		}
+
+		info!(
+			"Пользователь ID: {} загрузил сохранение игры {} версии {}",
+			user_id, game_id, save.version
+		);
+		Ok((Self::info(save), true))
+	}
+
+	pub async fn delete_version(&self, user_id: &str, game_id: i32, version: i32) -> Result<(), ErrorCode> {
+		let save = self
+			.prisma_client
+			.cloud_save()
+			.find_unique(cloud_save::user_id_game_id_version(user_id.to_string(), game_id, version))
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)?;
+		self.remove(save).await
+	}
+
+	pub async fn delete_game(&self, user_id: &str, game_id: i32) -> Result<(), ErrorCode> {
+		let versions = self.versions(user_id, game_id).await?;
+		if versions.is_empty() {
+			return Err(ErrorCode::DATABASE002);
+		}
+		for save in versions {
+			self.remove(save).await?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,221 @@
+use crate::model::dto::session::SessionInfo;
+use crate::model::error::ErrorCode;
+use crate::modules::helpers::random_token;
+use crate::prisma::{session, user, PrismaClient};
+use actix_session::storage::{LoadError, SaveError, SessionKey, SessionStore, UpdateError};
+use actix_web::cookie::time::Duration;
+use chrono::{DateTime, FixedOffset, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Ключи состояния сессии, которые заполняет `SessionMetadata`, а хранилище раскладывает по колонкам.
+pub const SESSION_ID_KEY: &str = "_sid";
+pub const SESSION_IP_KEY: &str = "_ip";
+pub const SESSION_USER_AGENT_KEY: &str = "_ua";
+pub const SESSION_DEVICE_KEY: &str = "_device";
+
+/// Ключ, под которым actix-identity хранит id пользователя.
+const IDENTITY_KEY: &str = "actix_identity.user_id";
+
+pub const CLEANUP_INTERVAL_MINUTES: u64 = 60;
+
+type SessionState = HashMap<String, String>;
+
+/// Хранилище сессий actix-session в SQLite. Cookie содержит только ключ сессии,
+/// поэтому удаление строки из таблицы завершает сессию на любом устройстве.
+#[derive(Clone)]
+pub struct SqliteSessionStore {
+	prisma_client: Arc<PrismaClient>,
+}
+
+impl SqliteSessionStore {
+	pub fn new(prisma_client: Arc<PrismaClient>) -> Self {
+		SqliteSessionStore { prisma_client }
+	}
+
+	/// В базе лежит только хеш ключа, чтобы содержимое таблицы нельзя было использовать как cookie.
+	fn key_hash(session_key: &SessionKey) -> String {
+		format!("{:x}", Sha256::digest(session_key.as_ref().as_bytes()))
+	}
+
+	/// Значения в состоянии сессии хранятся как JSON.
+	fn value(state: &SessionState, key: &str) -> Option<String> {
+		state.get(key).and_then(|value| serde_json::from_str::<String>(value).ok())
+	}
+
+	fn expires_at(ttl: &Duration) -> DateTime<FixedOffset> {
+		(Utc::now() + chrono::Duration::seconds(ttl.whole_seconds())).into()
+	}
+
+	/// Метаданные устройства и владелец сессии.
+	fn metadata_params(state: &SessionState) -> Vec<session::SetParam> {
+		let mut params = vec![
+			session::last_seen::set(Utc::now().into()),
+			session::device::set(Self::value(state, SESSION_DEVICE_KEY)),
+			session::ip::set(Self::value(state, SESSION_IP_KEY)),
+			session::user_agent::set(Self::value(state, SESSION_USER_AGENT_KEY)),
+		];
+		if let Some(user_id) = Self::value(state, IDENTITY_KEY) {
+			params.push(session::user::connect(user::id::equals(user_id)));
+		}
+		params
+	}
+
+	pub async fn list_sessions(&self, user_id: &str, current_id: Option<&str>) -> Result<Vec<SessionInfo>, ErrorCode> {
+		let sessions = self
+			.prisma_client
+			.session()
+			.find_many(vec![
+				session::user_id::equals(Some(user_id.to_string())),
+				session::expires_at::gt(Utc::now().into()),
+			])
+			.exec()
+			.await?;
+
+		Ok(sessions
+			.into_iter()
+			.map(|session| SessionInfo {
+				current: current_id == Some(session.id.as_str()),
+				id: session.id,
+				device: session.device,
+				ip: session.ip,
+				user_agent: session.user_agent,
+				created_at: session.created_at.to_rfc3339(),
+				last_seen: session.last_seen.to_rfc3339(),
+				expires_at: session.expires_at.to_rfc3339(),
+			})
+			.collect())
+	}
+
+	pub async fn revoke_session(&self, user_id: &str, session_id: &str) -> Result<(), ErrorCode> {
+		let deleted = self
+			.prisma_client
+			.session()
+			.delete_many(vec![
+				session::id::equals(session_id.to_string()),
+				session::user_id::equals(Some(user_id.to_string())),
+			])
+			.exec()
+			.await?;
+
+		if deleted == 0 {
+			return Err(ErrorCode::DATABASE002);
+		}
+		Ok(())
+	}
+
+	/// Удаляет истёкшие сессии, возвращает их количество.
+	pub async fn cleanup_expired(&self) -> Result<i64, String> {
+		self.prisma_client
+			.session()
+			.delete_many(vec![session::expires_at::lt(Utc::now().into())])
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to delete expired sessions: {}", e))
+	}
+}
+
+impl SessionStore for SqliteSessionStore {
+	async fn load(&self, session_key: &SessionKey) -> Result<Option<SessionState>, LoadError> {
+		let session = self
+			.prisma_client
+			.session()
+			.find_first(vec![
+				session::key_hash::equals(Self::key_hash(session_key)),
+				session::expires_at::gt(Utc::now().into()),
+			])
+			.exec()
+			.await
+			.map_err(|e| LoadError::Other(anyhow::Error::msg(e.to_string())))?;
+
+		match session {
+			Some(session) => serde_json::from_str(&session.state)
+				.map(Some)
+				.map_err(|e| LoadError::Deserialization(e.into())),
+			None => Ok(None),
+		}
+	}
+
+	async fn save(&self, session_state: SessionState, ttl: &Duration) -> Result<SessionKey, SaveError> {
+		let session_key = random_token(48)
+			.ok()
+			.and_then(|key| SessionKey::try_from(key).ok())
+			.ok_or_else(|| SaveError::Other(anyhow::anyhow!("Failed to generate session key")))?;
+		let id = match Self::value(&session_state, SESSION_ID_KEY) {
+			Some(id) => id,
+			None => random_token(16).map_err(|e| SaveError::Other(anyhow::Error::msg(e.to_string())))?,
+		};
+		let key_hash = Self::key_hash(&session_key);
+		let state = serde_json::to_string(&session_state).map_err(|e| SaveError::Serialization(e.into()))?;
+
+		// При renew() старая строка удаляется, а `_sid` переходит в новое состояние,
+		// поэтому строку с тем же id может понадобиться обновить.
+		let mut update = Self::metadata_params(&session_state);
+		update.extend([
+			session::key_hash::set(key_hash.clone()),
+			session::state::set(state.clone()),
+			session::expires_at::set(Self::expires_at(ttl)),
+		]);
+
+		self.prisma_client
+			.session()
+			.upsert(
+				session::id::equals(id.clone()),
+				session::create(id, key_hash, state, Self::expires_at(ttl), Self::metadata_params(&session_state)),
+				update,
+			)
+			.exec()
+			.await
+			.map_err(|e| SaveError::Other(anyhow::Error::msg(e.to_string())))?;
+
+		Ok(session_key)
+	}
+
+	async fn update(&self, session_key: SessionKey, session_state: SessionState, ttl: &Duration) -> Result<SessionKey, UpdateError> {
+		let state = serde_json::to_string(&session_state).map_err(|e| UpdateError::Serialization(e.into()))?;
+		let mut params = Self::metadata_params(&session_state);
+		params.extend([
+			session::state::set(state),
+			session::expires_at::set(Self::expires_at(ttl)),
+		]);
+		if Self::value(&session_state, IDENTITY_KEY).is_none() {
+			params.push(session::user::disconnect());
+		}
+
+		self.prisma_client
+			.session()
+			.update(session::key_hash::equals(Self::key_hash(&session_key)), params)
+			.exec()
+			.await
+			.map_err(|e| UpdateError::Other(anyhow::Error::msg(e.to_string())))?;
+
+		Ok(session_key)
+	}
+
+	async fn update_ttl(&self, session_key: &SessionKey, ttl: &Duration) -> Result<(), anyhow::Error> {
+		self.prisma_client
+			.session()
+			.update_many(
+				vec![session::key_hash::equals(Self::key_hash(session_key))],
+				vec![
+					session::expires_at::set(Self::expires_at(ttl)),
+					session::last_seen::set(Utc::now().into()),
+				],
+			)
+			.exec()
+			.await
+			.map_err(|e| anyhow::Error::msg(e.to_string()))?;
+		Ok(())
+	}
+
+	async fn delete(&self, session_key: &SessionKey) -> Result<(), anyhow::Error> {
+		self.prisma_client
+			.session()
+			.delete_many(vec![session::key_hash::equals(Self::key_hash(session_key))])
+			.exec()
+			.await
+			.map_err(|e| anyhow::Error::msg(e.to_string()))?;
+		Ok(())
+	}
+}
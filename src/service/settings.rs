@@ -0,0 +1,111 @@
+use crate::model::error::ErrorCode;
+use crate::prisma::{user, user_settings, PrismaClient};
+use log::info;
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use serde_json::{json, Value};
+
+pub const MAX_SETTINGS_BYTES: usize = 64 * 1024;
+
+/// Документ настроек лаунчера и его версия, которая отдаётся как ETag.
+/// Версия 0 означает, что настройки ещё ни разу не сохранялись.
+pub struct SettingsDocument {
+	pub version: i32,
+	pub document: Value,
+}
+
+impl SettingsDocument {
+	fn from_data(settings: user_settings::Data) -> Self {
+		SettingsDocument {
+			version: settings.version,
+			document: serde_json::from_str(&settings.document).unwrap_or_else(|_| json!({})),
+		}
+	}
+}
+
+pub struct SettingsService;
+
+impl SettingsService {
+	pub async fn get_settings(client: &PrismaClient, user_id: &str) -> Result<SettingsDocument, ErrorCode> {
+		let settings = client
+			.user_settings()
+			.find_unique(user_settings::user_id::equals(user_id.to_string()))
+			.exec()
+			.await?;
+
+		Ok(settings.map(SettingsDocument::from_data).unwrap_or(SettingsDocument {
+			version: 0,
+			document: json!({}),
+		}))
+	}
+
+	/// Сохраняет документ, если текущая версия совпадает с `expected_version`.
+	/// `None` соответствует `If-Match: *` и перезаписывает без проверки.
+	pub async fn put_settings(
+		client: &PrismaClient,
+		user_id: &str,
+		expected_version: Option<i32>,
+		document: Value,
+	) -> Result<SettingsDocument, ErrorCode> {
+		if !document.is_object() {
+			return Err(ErrorCode::BADREQUEST("Settings must be a JSON object".to_string()));
+		}
+		let serialized = document.to_string();
+		if serialized.len() > MAX_SETTINGS_BYTES {
+			return Err(ErrorCode::BADREQUEST(format!("Settings must be at most {} bytes", MAX_SETTINGS_BYTES)));
+		}
+
+		let version = match expected_version {
+			None => {
+				client
+					.user_settings()
+					.upsert(
+						user_settings::user_id::equals(user_id.to_string()),
+						user_settings::create(serialized.clone(), user::id::equals(user_id.to_string()), vec![]),
+						vec![
+							user_settings::document::set(serialized),
+							user_settings::version::increment(1),
+						],
+					)
+					.exec()
+					.await?
+					.version
+			}
+			Some(0) => {
+				// Второй экземпляр, создающий настройки одновременно, упрётся в уникальный userId.
+				match client
+					.user_settings()
+					.create(serialized, user::id::equals(user_id.to_string()), vec![])
+					.exec()
+					.await
+				{
+					Ok(settings) => settings.version,
+					Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => return Err(ErrorCode::PRECONDITION001),
+					Err(e) => return Err(e.into()),
+				}
+			}
+			Some(expected_version) => {
+				let updated = client
+					.user_settings()
+					.update_many(
+						vec![
+							user_settings::user_id::equals(user_id.to_string()),
+							user_settings::version::equals(expected_version),
+						],
+						vec![
+							user_settings::document::set(serialized),
+							user_settings::version::increment(1),
+						],
+					)
+					.exec()
+					.await?;
+				if updated == 0 {
+					return Err(ErrorCode::PRECONDITION001);
+				}
+				expected_version + 1
+			}
+		};
+
+		info!("Пользователь ID: {} сохранил настройки, версия {}", user_id, version);
+		Ok(SettingsDocument { version, document })
+	}
+}
@@ -0,0 +1,170 @@
+use crate::model::dto::search::{Suggestion, SuggestionKind};
+use crate::modules::helpers::{link_release, normalize_name};
+use crate::prisma::{game, PrismaClient};
+use crate::service::reports::ReportService;
+use log::info;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 25;
+pub const MIN_QUERY_LENGTH: usize = 2;
+pub const REFRESH_INTERVAL_MINUTES: u64 = 10;
+
+game::select!(game_suggestion { id name background_image });
+
+/// Префиксный индекс по названиям игр из локального каталога и релизов из торрент-индекса.
+/// Каждое название индексируется со всех слов, чтобы "witcher" находил "The Witcher 3".
+#[derive(Default)]
+struct SuggestIndex {
+	suggestions: Vec<Suggestion>,
+	normalized: Vec<String>,
+	keys: BTreeMap<String, Vec<usize>>,
+}
+
+impl SuggestIndex {
+	fn insert(&mut self, suggestion: Suggestion) {
+		let normalized = normalize_name(&suggestion.name);
+		if normalized.is_empty() {
+			return;
+		}
+
+		let index = self.suggestions.len();
+		self.suggestions.push(suggestion);
+		self.normalized.push(normalized.clone());
+
+		let mut rest = normalized.as_str();
+		loop {
+			self.keys.entry(rest.to_string()).or_default().push(index);
+			match rest.find(' ') {
+				Some(position) => rest = &rest[position + 1..],
+				None => break,
+			}
+		}
+	}
+
+	fn search(&self, query: &str, limit: usize) -> Vec<Suggestion> {
+		let query = normalize_name(query);
+		if query.len() < MIN_QUERY_LENGTH {
+			return vec![];
+		}
+
+		let mut matches: Vec<(bool, usize)> = self
+			.keys
+			.range(query.clone()..)
+			.take_while(|(key, _)| key.starts_with(&query))
+			.flat_map(|(key, indexes)| {
+				indexes.iter().map(move |&index| {
+					let from_start = self.normalized[index] == *key;
+					(from_start, index)
+				})
+			})
+			.collect();
+
+		// Совпадения с начала названия выше, затем более короткие названия.
+		matches.sort_by_key(|&(from_start, index)| (!from_start, self.suggestions[index].name.len(), index));
+
+		let mut seen = HashSet::new();
+		matches
+			.into_iter()
+			.filter(|&(_, index)| seen.insert(index))
+			.take(limit)
+			.map(|(_, index)| self.suggestions[index].clone())
+			.collect()
+	}
+}
+
+pub struct SuggestService {
+	prisma_client: Arc<PrismaClient>,
+	index: RwLock<SuggestIndex>,
+}
+
+impl SuggestService {
+	pub fn new(prisma_client: Arc<PrismaClient>) -> Self {
+		SuggestService {
+			prisma_client,
+			index: RwLock::new(SuggestIndex::default()),
+		}
+	}
+
+	/// Перестраивает индекс из таблиц `Game` и `Torrent`, скрытые модераторами раздачи пропускаются.
+	pub async fn rebuild(&self) -> Result<(), String> {
+		let games = self
+			.prisma_client
+			.game()
+			.find_many(vec![])
+			.select(game_suggestion::select())
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to load games: {}", e))?;
+
+		let torrents = self
+			.prisma_client
+			.torrent()
+			.find_many(vec![])
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to load torrents: {}", e))?;
+		let hidden = ReportService::all_hidden_links(&self.prisma_client)
+			.await
+			.map_err(|e| format!("Failed to load hidden releases: {:?}", e))?;
+
+		let mut index = SuggestIndex::default();
+		// Раздача привязывается к игре так же, как в уведомлениях и has_torrent. Название игры
+		// должно быть началом названия раздачи, поэтому кандидаты группируются по первому слову.
+		let mut known_games: HashMap<String, Vec<(String, (i32, Option<String>))>> = HashMap::new();
+
+		for game in games {
+			let normalized = normalize_name(&game.name);
+			if let Some(first_word) = normalized.split(' ').next().filter(|word| !word.is_empty()) {
+				known_games
+					.entry(first_word.to_string())
+					.or_default()
+					.push((normalized.clone(), (game.id, game.background_image.clone())));
+			}
+			index.insert(Suggestion {
+				kind: SuggestionKind::Game,
+				name: game.name,
+				rawg_id: Some(game.id),
+				cover: game.background_image,
+			});
+		}
+
+		let mut seen_releases = HashSet::new();
+		for torrent in torrents.into_iter().filter(|torrent| !hidden.contains(&torrent.torrent)) {
+			let normalized = normalize_name(&torrent.name);
+			if !seen_releases.insert(normalized.clone()) {
+				continue;
+			}
+
+			let (rawg_id, cover) = normalized
+				.split(' ')
+				.next()
+				.and_then(|first_word| known_games.get(first_word))
+				.and_then(|games| link_release(&normalized, games))
+				.cloned()
+				.map(|(id, cover)| (Some(id), cover))
+				.unwrap_or((None, None));
+			index.insert(Suggestion {
+				kind: SuggestionKind::Release,
+				name: torrent.name,
+				rawg_id,
+				cover,
+			});
+		}
+
+		info!("Индекс подсказок перестроен: {} записей", index.suggestions.len());
+		if let Ok(mut current) = self.index.write() {
+			*current = index;
+		}
+		Ok(())
+	}
+
+	pub fn suggest(&self, query: &str, limit: Option<usize>) -> Vec<Suggestion> {
+		let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+		self.index
+			.read()
+			.map(|index| index.search(query, limit))
+			.unwrap_or_default()
+	}
+}
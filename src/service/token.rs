@@ -0,0 +1,206 @@
+use crate::model::dto::token::{AccessTokenInfo, CreatedTokenResponse, TokenScope};
+use crate::model::error::ErrorCode;
+use crate::modules::helpers::random_token;
+use crate::prisma::{access_token, user, PrismaClient};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::{Duration, Utc};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Instant;
+
+/// Токены выглядят как `vek_<prefix>.<secret>`: по prefix ищется строка, secret проверяется argon2.
+pub const TOKEN_PREFIX: &str = "vek_";
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// Проверенный argon2 токен не перепроверяется это время, отзыв при этом действует сразу.
+const VERIFIED_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+/// `last_used` обновляется не чаще раза в минуту.
+const LAST_USED_RESOLUTION_SECONDS: i64 = 60;
+
+lazy_static! {
+	static ref VERIFIED_TOKENS: RwLock<HashMap<String, (Instant, String)>> = RwLock::new(HashMap::new());
+}
+
+/// Владелец токена и его права.
+#[derive(Debug, Clone)]
+pub struct TokenOwner {
+	pub user_id: String,
+	pub scopes: Vec<TokenScope>,
+}
+
+pub struct TokenService;
+
+impl TokenService {
+	fn parse_scopes(scopes: &str) -> Vec<TokenScope> {
+		scopes.split(',').filter_map(TokenScope::parse).collect()
+	}
+
+	fn info(token: access_token::Data) -> AccessTokenInfo {
+		AccessTokenInfo {
+			scopes: Self::parse_scopes(&token.scopes),
+			id: token.id,
+			name: token.name,
+			prefix: token.prefix,
+			created_at: token.created_at.to_rfc3339(),
+			last_used: token.last_used.map(|last_used| last_used.to_rfc3339()),
+		}
+	}
+
+	fn cache_key(raw_token: &str) -> String {
+		format!("{:x}", Sha256::digest(raw_token.as_bytes()))
+	}
+
+	pub async fn create_token(
+		client: &PrismaClient,
+		user_id: &str,
+		name: &str,
+		scopes: &[TokenScope],
+	) -> Result<CreatedTokenResponse, ErrorCode> {
+		let name = name.trim();
+		if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+			return Err(ErrorCode::BADREQUEST(format!("name must be 1 to {} characters", MAX_NAME_LENGTH)));
+		}
+		if scopes.is_empty() {
+			return Err(ErrorCode::BADREQUEST("at least one scope is required".to_string()));
+		}
+
+		let prefix = random_token(6).map_err(|_| ErrorCode::INTERNAL001)?;
+		let secret = random_token(32).map_err(|_| ErrorCode::INTERNAL001)?;
+		let token_hash = tokio::task::spawn_blocking(move || Self::hash_secret(&secret).map(|hash| (hash, secret)))
+			.await
+			.map_err(|_| ErrorCode::INTERNAL001)?;
+		let (token_hash, secret) = token_hash?;
+
+		let mut scopes: Vec<&str> = scopes.iter().map(TokenScope::as_str).collect();
+		scopes.sort_unstable();
+		scopes.dedup();
+
+		let token = client
+			.access_token()
+			.create(
+				name.to_string(),
+				prefix.clone(),
+				token_hash,
+				scopes.join(","),
+				user::id::equals(user_id.to_string()),
+				vec![],
+			)
+			.exec()
+			.await?;
+
+		info!("Пользователь ID: {} создал токен доступа {}", user_id, token.id);
+		Ok(CreatedTokenResponse {
+			token: format!("{}{}.{}", TOKEN_PREFIX, prefix, secret),
+			info: Self::info(token),
+		})
+	}
+
+	fn hash_secret(secret: &str) -> Result<String, ErrorCode> {
+		let mut salt = [0u8; 16];
+		getrandom::getrandom(&mut salt).map_err(|_| ErrorCode::INTERNAL001)?;
+		let salt = SaltString::encode_b64(&salt).map_err(|_| ErrorCode::INTERNAL001)?;
+		Argon2::default()
+			.hash_password(secret.as_bytes(), &salt)
+			.map(|hash| hash.to_string())
+			.map_err(|_| ErrorCode::INTERNAL001)
+	}
+
+	pub async fn list_tokens(client: &PrismaClient, user_id: &str) -> Result<Vec<AccessTokenInfo>, ErrorCode> {
+		let tokens = client
+			.access_token()
+			.find_many(vec![access_token::user_id::equals(user_id.to_string())])
+			.exec()
+			.await?;
+
+		Ok(tokens.into_iter().map(Self::info).collect())
+	}
+
+	pub async fn revoke_token(client: &PrismaClient, user_id: &str, token_id: &str) -> Result<(), ErrorCode> {
+		let deleted = client
+			.access_token()
+			.delete_many(vec![
+				access_token::id::equals(token_id.to_string()),
+				access_token::user_id::equals(user_id.to_string()),
+			])
+			.exec()
+			.await?;
+
+		if deleted == 0 {
+			return Err(ErrorCode::DATABASE002);
+		}
+		info!("Пользователь ID: {} отозвал токен доступа {}", user_id, token_id);
+		Ok(())
+	}
+
+	/// Проверяет `vek_...` токен из заголовка `Authorization` и отмечает время использования.
+	pub async fn authenticate(client: &PrismaClient, raw_token: &str) -> Result<TokenOwner, ErrorCode> {
+		let (prefix, secret) = raw_token
+			.strip_prefix(TOKEN_PREFIX)
+			.and_then(|token| token.split_once('.'))
+			.ok_or(ErrorCode::AUTH003)?;
+
+		let token = client
+			.access_token()
+			.find_unique(access_token::prefix::equals(prefix.to_string()))
+			.exec()
+			.await?
+			.ok_or(ErrorCode::AUTH003)?;
+
+		let cache_key = Self::cache_key(raw_token);
+		let cached = VERIFIED_TOKENS
+			.read()
+			.ok()
+			.and_then(|cache| cache.get(&cache_key).cloned())
+			.filter(|(verified_at, token_id)| verified_at.elapsed() < VERIFIED_CACHE_TTL && *token_id == token.id)
+			.is_some();
+
+		if !cached {
+			let token_hash = token.token_hash.clone();
+			let secret = secret.to_string();
+			let valid = tokio::task::spawn_blocking(move || {
+				PasswordHash::new(&token_hash)
+					.map(|hash| Argon2::default().verify_password(secret.as_bytes(), &hash).is_ok())
+					.unwrap_or(false)
+			})
+			.await
+			.map_err(|_| ErrorCode::INTERNAL001)?;
+			if !valid {
+				warn!("Неверный секрет токена доступа {}", token.id);
+				return Err(ErrorCode::AUTH003);
+			}
+
+			if let Ok(mut cache) = VERIFIED_TOKENS.write() {
+				cache.retain(|_, (verified_at, _)| verified_at.elapsed() < VERIFIED_CACHE_TTL);
+				cache.insert(cache_key, (Instant::now(), token.id.clone()));
+			}
+		}
+
+		let now = Utc::now();
+		let stale = token
+			.last_used
+			.map(|last_used| now.signed_duration_since(last_used) > Duration::seconds(LAST_USED_RESOLUTION_SECONDS))
+			.unwrap_or(true);
+		if stale {
+			if let Err(e) = client
+				.access_token()
+				.update(
+					access_token::id::equals(token.id.clone()),
+					vec![access_token::last_used::set(Some(now.into()))],
+				)
+				.exec()
+				.await
+			{
+				warn!("Не удалось обновить last_used токена {}: {:?}", token.id, e);
+			}
+		}
+
+		Ok(TokenOwner {
+			user_id: token.user_id,
+			scopes: Self::parse_scopes(&token.scopes),
+		})
+	}
+}
@@ -1,6 +1,14 @@
 use crate::prisma::PrismaClient;
-use crate::prisma::torrent;
+use crate::prisma::{game, torrent};
+use crate::model::dto::webhook::WebhookEvent;
+use crate::modules::helpers::{link_release, normalize_name, release_matches};
+use crate::service::reports::ReportService;
+use crate::service::webhook::WebhookService;
+use log::{error, info};
+use serde_json::json;
+use std::collections::HashSet;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 use crate::modules::providers::dodi::ProviderDODI;
 use crate::modules::providers::emperss::Provider0xEMPRESS;
@@ -11,13 +19,46 @@ use crate::modules::providers::onlinefix::ProviderOnlineFix;
 use crate::modules::providers::tinyrepacks::ProviderTinyRepacks;
 use crate::modules::providers::xatab::ProviderXatab;
 
+game::select!(game_name { id name });
+
+/// Как часто индекс торрентов сравнивается с прошлым снимком для вебхуков.
+pub const INDEX_WATCH_INTERVAL_MINUTES: u64 = 5;
+
+/// Ссылки на раздачи, о которых уже известно подписчикам вебхуков.
+struct IndexSnapshot {
+	known: HashSet<String>,
+	/// Пока индекс строится с нуля, новые раздачи только запоминаются.
+	settled: bool,
+}
+
 pub struct TorrentService {
 	prisma_client: Arc<PrismaClient>,
+	webhook_service: Arc<WebhookService>,
+	snapshot: Mutex<IndexSnapshot>,
 }
 
 impl TorrentService {
-	pub fn new(prisma_client: Arc<PrismaClient>) -> Self {
-		TorrentService { prisma_client }
+	pub fn new(prisma_client: Arc<PrismaClient>, webhook_service: Arc<WebhookService>) -> Self {
+		TorrentService {
+			prisma_client,
+			webhook_service,
+			snapshot: Mutex::new(IndexSnapshot {
+				known: HashSet::new(),
+				settled: false,
+			}),
+		}
+	}
+
+	async fn torrent_links(&self) -> Result<HashSet<String>, String> {
+		let torrents = self
+			.prisma_client
+			.torrent()
+			.find_many(vec![])
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to load torrents: {}", e))?;
+
+		Ok(torrents.into_iter().map(|t| t.torrent).collect())
 	}
 
 	async fn clear_torrents(&self) -> Result<(), String> {
@@ -28,11 +69,18 @@ impl TorrentService {
 			.await
 			.map_err(|e| format!("Failed to clear torrents: {}", e))?;
 
-		println!("Таблица торрентов очищена.");
+		info!("Таблица торрентов очищена.");
 		Ok(())
 	}
 
 	pub async fn initialize_torrents(&self) -> Result<(), String> {
+		// Раздачи из прошлого запуска не считаются новыми, когда провайдеры найдут их снова.
+		let known = self.torrent_links().await?;
+		{
+			let mut snapshot = self.snapshot.lock().await;
+			snapshot.settled = !known.is_empty();
+			snapshot.known = known;
+		}
 		self.clear_torrents().await?;
 		
 		let providers: Vec<(&str, Box<dyn TorrentProvider>)> = vec![
@@ -57,14 +105,78 @@ impl TorrentService {
 							.await;
 					}
 				}
-				Err(_) => {}
+				Err(e) => {
+					error!("Ошибка парсинга провайдера {}: {}", name, e);
+					self.webhook_service
+						.emit(WebhookEvent::ScrapeFailed, json!({ "provider": name, "error": e }))
+						.await;
+				}
 			}
-			println!("Завершен парсинг провайдера: {}", name);
+			info!("Завершен парсинг провайдера: {}", name);
 		}
 
 		Ok(())
 	}
 
+	/// Сообщает вебхукам о раздачах, появившихся с прошлой проверки. Провайдеры наполняют
+	/// индекс в фоне, поэтому при сборке с нуля события начинаются, когда индекс перестал расти.
+	pub async fn emit_new_torrents(&self) -> Result<usize, String> {
+		let mut snapshot = self.snapshot.lock().await;
+		let torrents = self
+			.prisma_client
+			.torrent()
+			.find_many(vec![])
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to load torrents: {}", e))?;
+		let added: Vec<torrent::Data> = torrents
+			.into_iter()
+			.filter(|t| !snapshot.known.contains(&t.torrent))
+			.collect();
+
+		snapshot.known.extend(added.iter().map(|t| t.torrent.clone()));
+		if !snapshot.settled {
+			snapshot.settled = added.is_empty() && !snapshot.known.is_empty();
+			return Ok(0);
+		}
+		if added.is_empty() {
+			return Ok(0);
+		}
+
+		let games: Vec<(String, (i32, String))> = self
+			.prisma_client
+			.game()
+			.find_many(vec![])
+			.select(game_name::select())
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to load games: {}", e))?
+			.into_iter()
+			.map(|g| (normalize_name(&g.name), (g.id, g.name)))
+			.filter(|(normalized, _)| !normalized.is_empty())
+			.collect();
+
+		for torrent in &added {
+			let data = json!({
+				"name": torrent.name,
+				"repacker": torrent.repacker,
+				"torrent": torrent.torrent,
+			});
+			self.webhook_service.emit(WebhookEvent::TorrentAdded, data.clone()).await;
+
+			if let Some((game_id, game_name)) = link_release(&normalize_name(&torrent.name), &games) {
+				self.webhook_service
+					.emit(
+						WebhookEvent::GameLinked,
+						json!({ "game_id": game_id, "game_name": game_name, "torrent": data }),
+					)
+					.await;
+			}
+		}
+
+		Ok(added.len())
+	}
+
 	pub async fn search_torrent(&self, game_name: &str) -> Result<Vec<(String, String)>, String> {
 		let torrents = self
 			.prisma_client
@@ -74,7 +186,61 @@ impl TorrentService {
 			.await
 			.map_err(|e| format!("Failed to search torrent: {}", e))?;
 
-		Ok(torrents.into_iter().map(|t| (t.repacker, t.torrent)).collect())
+		let links: Vec<String> = torrents.iter().map(|t| t.torrent.clone()).collect();
+		let hidden = ReportService::hidden_links(&self.prisma_client, &links)
+			.await
+			.map_err(|e| format!("Failed to load hidden releases: {:?}", e))?;
+
+		Ok(torrents
+			.into_iter()
+			.filter(|t| !hidden.contains(&t.torrent))
+			.map(|t| (t.repacker, t.torrent))
+			.collect())
+	}
+
+	/// Какие из игр есть в индексе, одним запросом на весь список. Кандидаты отбираются
+	/// в базе по самому длинному слову названия, а совпадение проверяется `release_matches`.
+	pub async fn games_with_torrent(&self, game_names: &[String]) -> Result<HashSet<String>, String> {
+		let games: Vec<(&String, String)> = game_names
+			.iter()
+			.map(|name| (name, normalize_name(name)))
+			.filter(|(_, normalized)| !normalized.is_empty())
+			.collect();
+		let filters: Vec<torrent::WhereParam> = games
+			.iter()
+			.filter_map(|(_, normalized)| normalized.split(' ').max_by_key(|word| word.len()))
+			.collect::<HashSet<&str>>()
+			.into_iter()
+			.map(|word| torrent::name::contains(word.to_string()))
+			.collect();
+		if filters.is_empty() {
+			return Ok(HashSet::new());
+		}
+
+		let torrents = self
+			.prisma_client
+			.torrent()
+			.find_many(vec![torrent::WhereParam::Or(filters)])
+			.exec()
+			.await
+			.map_err(|e| format!("Failed to search torrent: {}", e))?;
+
+		// Игра, у которой остались только скрытые раздачи, в `/api/torrent` ничего не найдёт.
+		let links: Vec<String> = torrents.iter().map(|t| t.torrent.clone()).collect();
+		let hidden = ReportService::hidden_links(&self.prisma_client, &links)
+			.await
+			.map_err(|e| format!("Failed to load hidden releases: {:?}", e))?;
+		let releases: Vec<String> = torrents
+			.into_iter()
+			.filter(|t| !hidden.contains(&t.torrent))
+			.map(|t| normalize_name(&t.name))
+			.collect();
+
+		Ok(games
+			.into_iter()
+			.filter(|(_, normalized)| releases.iter().any(|release| release_matches(release, normalized)))
+			.map(|(name, _)| name.clone())
+			.collect())
 	}
 }
 
@@ -1,6 +1,17 @@
+use crate::model::dto::profile::UpdateProfileRequest;
+use crate::model::error::ErrorCode;
 use crate::prisma::{user, PrismaClient};
 use chrono::Utc;
+use lazy_static::lazy_static;
 use prisma_client_rust::QueryError;
+use regex::Regex;
+
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 64;
+
+lazy_static! {
+	static ref LOCALE_REGEX: Regex = Regex::new(r"^[a-z]{2,3}(-[A-Z]{2})?$").unwrap();
+	static ref TIMEZONE_REGEX: Regex = Regex::new(r"^(UTC|[A-Z][A-Za-z_]+(/[A-Za-z0-9_+\-]+){1,2})$").unwrap();
+}
 
 pub struct UserService;
 
@@ -51,4 +62,78 @@ impl UserService {
 			.exec()
 			.await
 	}
+
+	/// Подставляет имя и аватар от провайдера, только если пользователь не задал свои.
+	pub async fn apply_provider_profile(
+		client: &PrismaClient,
+		user_data: user::Data,
+		display_name: Option<String>,
+		avatar_url: Option<String>,
+	) -> Result<user::Data, QueryError> {
+		let mut params = vec![];
+		if user_data.display_name.is_none() && display_name.is_some() {
+			params.push(user::display_name::set(display_name));
+		}
+		if user_data.avatar_url.is_none() && avatar_url.is_some() {
+			params.push(user::avatar_url::set(avatar_url));
+		}
+		if params.is_empty() {
+			return Ok(user_data);
+		}
+
+		client
+			.user()
+			.update(user::id::equals(user_data.id), params)
+			.exec()
+			.await
+	}
+
+	pub async fn update_profile(
+		client: &PrismaClient,
+		user_id: &str,
+		request: UpdateProfileRequest,
+	) -> Result<user::Data, ErrorCode> {
+		let mut params = vec![];
+		if let Some(display_name) = request.display_name {
+			let display_name = display_name.trim().to_string();
+			if display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+				return Err(ErrorCode::BADREQUEST(format!("display_name must be at most {} characters", MAX_DISPLAY_NAME_LENGTH)));
+			}
+			params.push(user::display_name::set(Some(display_name).filter(|name| !name.is_empty())));
+		}
+		if let Some(locale) = request.locale {
+			if !LOCALE_REGEX.is_match(&locale) {
+				return Err(ErrorCode::BADREQUEST("locale must look like ru or en-US".to_string()));
+			}
+			params.push(user::locale::set(locale));
+		}
+		if let Some(timezone) = request.timezone {
+			if !TIMEZONE_REGEX.is_match(&timezone) {
+				return Err(ErrorCode::BADREQUEST("timezone must be an IANA name like Europe/Moscow".to_string()));
+			}
+			params.push(user::timezone::set(timezone));
+		}
+
+		Ok(client
+			.user()
+			.update(user::id::equals(user_id.to_string()), params)
+			.exec()
+			.await?)
+	}
+
+	/// `None` убирает аватар, следующий вход через Yandex подставит его снова.
+	pub async fn set_avatar(
+		client: &PrismaClient,
+		user_id: &str,
+		avatar_url: Option<String>,
+	) -> Result<user::Data, QueryError> {
+		client
+			.user()
+			.update(
+				user::id::equals(user_id.to_string()),
+				vec![user::avatar_url::set(avatar_url)],
+			)
+			.exec()
+			.await
+	}
 }
@@ -0,0 +1,358 @@
+use crate::model::dto::webhook::{
+	CreateWebhookRequest, CreatedWebhookResponse, UpdateWebhookRequest, WebhookDeliveryInfo, WebhookEvent, WebhookInfo,
+};
+use crate::model::error::ErrorCode;
+use crate::modules::helpers::random_token;
+use crate::modules::webhook::{validate_url, WebhookClient};
+use crate::prisma::{user, webhook, webhook_delivery, PrismaClient};
+use chrono::{Duration, Utc};
+use log::{error, info, warn};
+use prisma_client_rust::Direction;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+pub const MAX_WEBHOOKS_PER_USER: i64 = 10;
+/// Событие для `POST /webhooks/{id}/test`, на него нельзя подписаться.
+pub const PING_EVENT: &str = "ping";
+
+/// Повтор через 30 с, 1, 2, 4 и 8 минут, после шестой попытки доставка считается неудачной.
+const MAX_ATTEMPTS: i32 = 6;
+const BASE_BACKOFF_SECONDS: i64 = 30;
+const WORKER_INTERVAL_SECONDS: u64 = 15;
+const DELIVERY_BATCH: i64 = 50;
+const DELIVERY_LOG_LIMIT: i64 = 50;
+const LOG_RETENTION_DAYS: i64 = 14;
+const MAX_ERROR_LENGTH: usize = 500;
+
+/// Исходящие вебхуки: подписки пользователей, очередь доставок с повторами и журнал.
+pub struct WebhookService {
+	prisma_client: Arc<PrismaClient>,
+	client: WebhookClient,
+	wake: Notify,
+}
+
+impl WebhookService {
+	pub fn new(prisma_client: Arc<PrismaClient>) -> Self {
+		WebhookService {
+			prisma_client,
+			client: WebhookClient::from_env(),
+			wake: Notify::new(),
+		}
+	}
+
+	fn info(webhook: webhook::Data) -> WebhookInfo {
+		WebhookInfo {
+			events: webhook.events.split(',').filter_map(WebhookEvent::parse).collect(),
+			id: webhook.id,
+			url: webhook.url,
+			active: webhook.active,
+			created_at: webhook.created_at.to_rfc3339(),
+		}
+	}
+
+	fn delivery_info(delivery: webhook_delivery::Data) -> WebhookDeliveryInfo {
+		WebhookDeliveryInfo {
+			id: delivery.id,
+			event: delivery.event,
+			attempts: delivery.attempts,
+			status_code: delivery.status_code,
+			error: delivery.error,
+			delivered_at: delivery.delivered_at.map(|delivered_at| delivered_at.to_rfc3339()),
+			next_attempt: delivery.next_attempt.map(|next_attempt| next_attempt.to_rfc3339()),
+			created_at: delivery.created_at.to_rfc3339(),
+		}
+	}
+
+	fn join_events(events: &[WebhookEvent]) -> Result<String, ErrorCode> {
+		if events.is_empty() {
+			return Err(ErrorCode::BADREQUEST("at least one event is required".to_string()));
+		}
+		let mut events: Vec<&str> = events.iter().map(WebhookEvent::as_str).collect();
+		events.sort_unstable();
+		events.dedup();
+		Ok(events.join(","))
+	}
+
+	/// `user_id` равен `None` для администратора, который видит все вебхуки.
+	async fn find_webhook(&self, user_id: Option<&str>, webhook_id: &str) -> Result<webhook::Data, ErrorCode> {
+		let mut filters = vec![webhook::id::equals(webhook_id.to_string())];
+		if let Some(user_id) = user_id {
+			filters.push(webhook::user_id::equals(user_id.to_string()));
+		}
+
+		self.prisma_client
+			.webhook()
+			.find_first(filters)
+			.exec()
+			.await?
+			.ok_or(ErrorCode::DATABASE002)
+	}
+
+	pub async fn create_webhook(
+		&self,
+		user_id: &str,
+		request: CreateWebhookRequest,
+	) -> Result<CreatedWebhookResponse, ErrorCode> {
+		let url = validate_url(&request.url).map_err(ErrorCode::BADREQUEST)?;
+		let events = Self::join_events(&request.events)?;
+
+		let count = self
+			.prisma_client
+			.webhook()
+			.count(vec![webhook::user_id::equals(user_id.to_string())])
+			.exec()
+			.await?;
+		if count >= MAX_WEBHOOKS_PER_USER {
+			return Err(ErrorCode::BADREQUEST(format!("at most {} webhooks per user", MAX_WEBHOOKS_PER_USER)));
+		}
+
+		let secret = random_token(32).map_err(|_| ErrorCode::INTERNAL001)?;
+		let webhook = self
+			.prisma_client
+			.webhook()
+			.create(
+				url.to_string(),
+				secret.clone(),
+				events,
+				user::id::equals(user_id.to_string()),
+				vec![],
+			)
+			.exec()
+			.await?;
+
+		info!("Пользователь ID: {} создал вебхук {}", user_id, webhook.id);
+		Ok(CreatedWebhookResponse {
+			secret,
+			info: Self::info(webhook),
+		})
+	}
+
+	pub async fn list_webhooks(&self, user_id: Option<&str>) -> Result<Vec<WebhookInfo>, ErrorCode> {
+		let filters = user_id
+			.map(|user_id| vec![webhook::user_id::equals(user_id.to_string())])
+			.unwrap_or_default();
+		let webhooks = self.prisma_client.webhook().find_many(filters).exec().await?;
+		Ok(webhooks.into_iter().map(Self::info).collect())
+	}
+
+	pub async fn update_webhook(
+		&self,
+		user_id: Option<&str>,
+		webhook_id: &str,
+		request: UpdateWebhookRequest,
+	) -> Result<WebhookInfo, ErrorCode> {
+		let webhook = self.find_webhook(user_id, webhook_id).await?;
+
+		let mut params = vec![];
+		if let Some(url) = request.url {
+			let url = validate_url(&url).map_err(ErrorCode::BADREQUEST)?;
+			params.push(webhook::url::set(url.to_string()));
+		}
+		if let Some(events) = request.events {
+			params.push(webhook::events::set(Self::join_events(&events)?));
+		}
+		if let Some(active) = request.active {
+			params.push(webhook::active::set(active));
+		}
+
+		let webhook = self
+			.prisma_client
+			.webhook()
+			.update(webhook::id::equals(webhook.id), params)
+			.exec()
+			.await?;
+		Ok(Self::info(webhook))
+	}
+
+	pub async fn delete_webhook(&self, user_id: Option<&str>, webhook_id: &str) -> Result<(), ErrorCode> {
+		let webhook = self.find_webhook(user_id, webhook_id).await?;
+		self.prisma_client
+			.webhook()
+			.delete(webhook::id::equals(webhook.id.clone()))
+			.exec()
+			.await?;
+
+		info!("Удалён вебхук {}", webhook.id);
+		Ok(())
+	}
+
+	pub async fn list_deliveries(
+		&self,
+		user_id: Option<&str>,
+		webhook_id: &str,
+	) -> Result<Vec<WebhookDeliveryInfo>, ErrorCode> {
+		let webhook = self.find_webhook(user_id, webhook_id).await?;
+		let deliveries = self
+			.prisma_client
+			.webhook_delivery()
+			.find_many(vec![webhook_delivery::webhook_id::equals(webhook.id)])
+			.order_by(webhook_delivery::created_at::order(Direction::Desc))
+			.take(DELIVERY_LOG_LIMIT)
+			.exec()
+			.await?;
+		Ok(deliveries.into_iter().map(Self::delivery_info).collect())
+	}
+
+	/// Ставит в очередь `ping`, чтобы проверить приёмник и подпись.
+	pub async fn test_webhook(&self, user_id: Option<&str>, webhook_id: &str) -> Result<WebhookDeliveryInfo, ErrorCode> {
+		let webhook = self.find_webhook(user_id, webhook_id).await?;
+		let delivery = self
+			.enqueue(&webhook.id, PING_EVENT, json!({ "webhook_id": webhook.id }))
+			.await?;
+		self.wake.notify_one();
+		Ok(Self::delivery_info(delivery))
+	}
+
+	async fn enqueue(&self, webhook_id: &str, event: &str, data: Value) -> Result<webhook_delivery::Data, ErrorCode> {
+		let payload = json!({
+			"event": event,
+			"created_at": Utc::now().to_rfc3339(),
+			"data": data,
+		});
+
+		Ok(self
+			.prisma_client
+			.webhook_delivery()
+			.create(
+				event.to_string(),
+				payload.to_string(),
+				webhook::id::equals(webhook_id.to_string()),
+				vec![webhook_delivery::next_attempt::set(Some(Utc::now().into()))],
+			)
+			.exec()
+			.await?)
+	}
+
+	/// Ставит событие в очередь всем активным подпискам. Ошибки только логируются,
+	/// чтобы сбой вебхуков не мешал обновлению индекса.
+	pub async fn emit(&self, event: WebhookEvent, data: Value) {
+		let webhooks = match self
+			.prisma_client
+			.webhook()
+			.find_many(vec![
+				webhook::active::equals(true),
+				webhook::events::contains(event.as_str().to_string()),
+			])
+			.exec()
+			.await
+		{
+			Ok(webhooks) => webhooks,
+			Err(e) => {
+				error!("Не удалось получить подписки на событие {}: {:?}", event.as_str(), e);
+				return;
+			}
+		};
+
+		for webhook in webhooks {
+			if !webhook.events.split(',').any(|subscribed| subscribed == event.as_str()) {
+				continue;
+			}
+			if let Err(e) = self.enqueue(&webhook.id, event.as_str(), data.clone()).await {
+				error!("Не удалось поставить доставку вебхука {} в очередь: {:?}", webhook.id, e);
+			}
+		}
+		self.wake.notify_one();
+	}
+
+	fn backoff(attempts: i32) -> Duration {
+		Duration::seconds(BASE_BACKOFF_SECONDS << (attempts - 1).clamp(0, 16))
+	}
+
+	async fn deliver(&self, delivery: webhook_delivery::Data, webhook: &webhook::Data) -> Result<(), ErrorCode> {
+		let attempts = delivery.attempts + 1;
+		let result = if webhook.active {
+			self.client
+				.post(&webhook.url, &delivery.event, &delivery.id, &webhook.secret, &delivery.payload)
+				.await
+				.map(|status| status as i32)
+				.map_err(|(status, message)| (status.map(|status| status as i32), message))
+		} else {
+			Err((None, "Webhook is disabled".to_string()))
+		};
+
+		let now = Utc::now();
+		let params = match result {
+			Ok(status_code) => vec![
+				webhook_delivery::attempts::set(attempts),
+				webhook_delivery::status_code::set(Some(status_code)),
+				webhook_delivery::error::set(None),
+				webhook_delivery::delivered_at::set(Some(now.into())),
+				webhook_delivery::next_attempt::set(None),
+			],
+			Err((status_code, message)) => {
+				let give_up = attempts >= MAX_ATTEMPTS || !webhook.active;
+				if give_up {
+					warn!("Доставка {} вебхука {} не удалась: {}", delivery.id, webhook.id, message);
+				}
+				vec![
+					webhook_delivery::attempts::set(attempts),
+					webhook_delivery::status_code::set(status_code),
+					webhook_delivery::error::set(Some(message.chars().take(MAX_ERROR_LENGTH).collect())),
+					webhook_delivery::next_attempt::set((!give_up).then(|| (now + Self::backoff(attempts)).into())),
+				]
+			}
+		};
+
+		self.prisma_client
+			.webhook_delivery()
+			.update(webhook_delivery::id::equals(delivery.id), params)
+			.exec()
+			.await?;
+		Ok(())
+	}
+
+	async fn process_due(&self) -> Result<(), ErrorCode> {
+		let due = self
+			.prisma_client
+			.webhook_delivery()
+			.find_many(vec![webhook_delivery::next_attempt::lte(Utc::now().into())])
+			.order_by(webhook_delivery::next_attempt::order(Direction::Asc))
+			.take(DELIVERY_BATCH)
+			.with(webhook_delivery::webhook::fetch())
+			.exec()
+			.await?;
+
+		for mut delivery in due {
+			let Some(webhook) = delivery.webhook.take() else { continue };
+			self.deliver(delivery, &webhook).await?;
+		}
+		Ok(())
+	}
+
+	async fn cleanup_log(&self) -> Result<i64, ErrorCode> {
+		Ok(self
+			.prisma_client
+			.webhook_delivery()
+			.delete_many(vec![
+				webhook_delivery::created_at::lt((Utc::now() - Duration::days(LOG_RETENTION_DAYS)).into()),
+				webhook_delivery::next_attempt::equals(None),
+			])
+			.exec()
+			.await?)
+	}
+
+	/// Фоновая очередь: просыпается по таймеру или сразу после `emit`.
+	pub async fn run_worker(&self) {
+		let mut interval = tokio::time::interval(std::time::Duration::from_secs(WORKER_INTERVAL_SECONDS));
+		let mut last_cleanup = std::time::Instant::now();
+		loop {
+			tokio::select! {
+				_ = interval.tick() => {}
+				_ = self.wake.notified() => {}
+			}
+
+			if let Err(e) = self.process_due().await {
+				error!("Ошибка при доставке вебхуков: {:?}", e);
+			}
+			if last_cleanup.elapsed() > std::time::Duration::from_secs(3600) {
+				last_cleanup = std::time::Instant::now();
+				match self.cleanup_log().await {
+					Ok(count) if count > 0 => info!("Удалено старых доставок вебхуков: {}", count),
+					Ok(_) => {}
+					Err(e) => error!("Ошибка при очистке журнала вебхуков: {:?}", e),
+				}
+			}
+		}
+	}
+}